@@ -1,5 +1,8 @@
 pub mod client;
+pub mod connection;
+pub mod formatting;
 pub mod parser;
+pub mod transport;
 
 #[cfg(test)]
 mod test;
@@ -1,51 +1,1559 @@
+pub mod authenticator;
+pub mod backoff;
+pub mod batch;
 pub mod buffer;
 pub mod cap_negotiator;
+pub mod casemapping;
+pub mod channel;
+pub mod command;
+pub mod ctcp;
+pub mod highlight;
+pub mod history;
+pub mod keepalive;
+pub mod mode;
+pub mod server_info;
+pub mod splitter;
+pub mod timestamp;
 
 #[cfg(test)]
 mod test;
 
-use buffer::Buffer;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use authenticator::{mechanism::Mechanism, Authenticator};
+use batch::{Assembled, Assembler, Batch, Entry, Kind};
+use buffer::{Buffer, BufferName, LineKind};
 use cap_negotiator::CapNegotiator;
+use casemapping::Nickname;
+use channel::{Channel, Member, Topic};
+use command::UserCommand;
+use ctcp::{Ctcp, RateLimit, Replies};
+use highlight::{Highlight, Highlighter};
+use history::{Reference, Request};
+use keepalive::{Keepalive, Timeout};
+use mode::{ModeBuilder, ModeChange};
+use server_info::ServerInfo;
+use splitter::{Multiline, Splitter};
+use yew::AttrValue;
+
+use super::parser::{
+    capability::Capability,
+    generic_message::GenericMessage,
+    message::{Message, MessageType},
+    numeric,
+    numeric_reply::NumericReply,
+    typed_message::TypedMessage,
+    Command, Source, User,
+};
+
+type Messages = Vec<Message>;
+
+/// Number of alternative nick names which are tried during registration,
+/// before giving up
+const MAX_NICK_ATTEMPTS: usize = 10;
 
-use super::parser::{capability::Capability, message::Message};
+/// Maximum length of the parameters of a single `JOIN` sent to rejoin channels
+const MAX_JOIN_LENGTH: usize = 400;
 
 #[derive(Debug, PartialEq, Clone)]
-enum ClientState {
-    PreCapLs,
+pub enum ClientState {
+    /// Registration has not been started yet, or the connection was lost
+    Disconnected,
+    /// Capability negotiation in progress
     CapLs,
-    //CapReq,
+    /// SASL authentication in progress
+    Authenticating,
+    /// `CAP END` has been sent, waiting for `RPL_WELCOME`
+    Registering,
+    /// Server accepted the connection, `nickname` is the one it assigned
+    Registered,
 }
 
+#[derive(Debug)]
+pub enum Error {
+    CapNegotiator(cap_negotiator::Error),
+    /// Server did not answer our `PING`, the connection should be closed
+    Timeout(Timeout),
+}
+
+impl From<cap_negotiator::Error> for Error {
+    fn from(err: cap_negotiator::Error) -> Self {
+        Error::CapNegotiator(err)
+    }
+}
+
+/// User settings used for connection registration
 #[derive(Debug, Clone, PartialEq)]
-pub struct Error {}
+pub struct Config {
+    pub nickname: AttrValue,
+    /// Nick names to try if `nickname` is not available. Once these are used
+    /// up, `_` is appended to the last nick name.
+    pub alt_nicknames: Vec<AttrValue>,
+    pub username: AttrValue,
+    pub realname: AttrValue,
+    /// Connection password, sent with `PASS`
+    pub password: Option<AttrValue>,
+    /// SASL mechanisms in order of preference. SASL is disabled if empty.
+    pub sasl: Vec<Box<dyn Mechanism>>,
+    /// Idle time after which the connection is checked with a `PING`
+    pub ping_interval: Duration,
+    /// Time to wait for the `PONG`, before the connection is considered dead
+    pub ping_timeout: Duration,
+    /// Time after which incomplete batches are handled anyway
+    pub batch_timeout: Duration,
+    pub ctcp: Replies,
+    /// Words and patterns which highlight a message, like our nick name
+    pub highlights: Vec<Highlight>,
+}
+
+impl Config {
+    pub fn new(nickname: &str) -> Self {
+        Config {
+            nickname: nickname.to_owned().into(),
+            alt_nicknames: vec![],
+            username: nickname.to_owned().into(),
+            realname: nickname.to_owned().into(),
+            password: None,
+            sasl: vec![],
+            ping_interval: Duration::from_secs(60),
+            ping_timeout: Duration::from_secs(30),
+            batch_timeout: Duration::from_secs(60),
+            ctcp: Replies::default(),
+            highlights: vec![],
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Client {
+    config: Config,
     state: ClientState,
     cap_negotiator: CapNegotiator,
+    authenticator: Authenticator,
+    keepalive: Keepalive,
+    server_info: ServerInfo,
+    /// Current nick name. During registration this is the last one we tried.
+    nickname: Nickname,
+    nick_attempts: usize,
+    /// User name and host of our prefix, once the server told them
+    user: Option<AttrValue>,
+    host: Option<AttrValue>,
+    /// Last reference used for a batch we sent
+    batch_id: usize,
+    ctcp_limit: RateLimit,
+    highlighter: Highlighter,
+    batches: Assembler,
+    /// Buffer shown to the user, see `selected`
+    selected: Option<u64>,
     pub buffers: Vec<Buffer>,
 }
 
 impl Client {
     // Supported list of capabilities
-    pub fn supported_capabilities() -> Vec<Capability> {
-        vec![Capability::new("sasl")]
+    pub fn supported_capabilities(config: &Config) -> Vec<Capability> {
+        let mut capabilities = vec![
+            Capability::new("multi-prefix"),
+            Capability::new("userhost-in-names"),
+            Capability::new("batch"),
+            Capability::new("draft/multiline"),
+            Capability::new("server-time"),
+            Capability::new("message-tags"),
+            Capability::new("draft/chathistory"),
+        ];
+        if !config.sasl.is_empty() {
+            capabilities.push(Capability::new("sasl"));
+        }
+        capabilities
     }
 
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         Client {
-            state: ClientState::PreCapLs,
-            cap_negotiator: CapNegotiator::request(Self::supported_capabilities()),
+            state: ClientState::Disconnected,
+            cap_negotiator: CapNegotiator::request(Self::supported_capabilities(&config)),
+            authenticator: Authenticator::new(config.sasl.clone()),
+            keepalive: Keepalive::new(config.ping_interval, config.ping_timeout),
+            server_info: ServerInfo::default(),
+            nickname: Nickname::new(&config.nickname, Default::default()),
+            nick_attempts: 0,
+            user: None,
+            host: None,
+            batch_id: 0,
+            ctcp_limit: RateLimit::new(config.ctcp.max_replies, config.ctcp.period),
+            highlighter: Highlighter::new(&config.highlights),
+            batches: Assembler::new(config.batch_timeout),
+            selected: None,
             buffers: vec![],
+            config,
+        }
+    }
+
+    pub fn state(&self) -> &ClientState {
+        &self.state
+    }
+
+    pub fn nickname(&self) -> &Nickname {
+        &self.nickname
+    }
+
+    /// Parameters of the server, as advertised with `RPL_ISUPPORT`
+    pub fn server_info(&self) -> &ServerInfo {
+        &self.server_info
+    }
+
+    pub fn is_registered(&self) -> bool {
+        self.state == ClientState::Registered
+    }
+
+    /// Account we are logged in to via SASL
+    pub fn account(&self) -> Option<&AttrValue> {
+        self.authenticator.account.as_ref()
+    }
+
+    /// Buffer shown to the user, the first one if none was selected
+    pub fn selected(&self) -> Option<&Buffer> {
+        self.buffers
+            .iter()
+            .find(|buffer| Some(buffer.id) == self.selected)
+            .or(self.buffers.first())
+    }
+
+    /// Show a buffer, which marks it as read
+    pub fn select(&mut self, buffer_id: u64) {
+        if let Some(buffer) = self.buffers.iter_mut().find(|b| b.id == buffer_id) {
+            buffer.mark_read();
+            self.selected = Some(buffer_id);
+        }
+    }
+
+    /// New lines of the selected buffer are read right away
+    fn mark_selected_read(&mut self) {
+        if let Some(id) = self.selected().map(|buffer| buffer.id) {
+            self.select(id);
         }
     }
 
-    pub fn request_capabilities(&mut self) -> Message {
+    /// Round trip time to the server, measured with the last `PING`
+    pub fn lag(&self) -> Option<Duration> {
+        self.keepalive.lag()
+    }
+
+    /// Start connection registration. Registration is held back by the
+    /// server until capability negotiation finished with `CAP END`.
+    pub fn register(&mut self) -> Messages {
+        // capabilities and authentication are negotiated per connection
+        self.cap_negotiator = CapNegotiator::request(Self::supported_capabilities(&self.config));
+        self.authenticator = Authenticator::new(self.config.sasl.clone());
+        self.set_server_info(ServerInfo::default());
+
         self.state = ClientState::CapLs;
-        self.cap_negotiator.ls(Some("302"))
+        self.nickname = self.server_info.nickname(&self.config.nickname);
+        self.nick_attempts = 0;
+        self.user = None;
+        self.host = None;
+        self.batches = Assembler::new(self.config.batch_timeout);
+        // a server which stalls during registration times out as well
+        self.keepalive.restart();
+
+        let mut messages = vec![self.cap_negotiator.ls(Some("302"))];
+        if let Some(password) = &self.config.password {
+            messages.push(Self::typed(TypedMessage::Pass {
+                password: password.clone(),
+            }));
+        }
+        messages.push(Self::typed(TypedMessage::Nick {
+            nickname: self.nickname.as_attr_value(),
+        }));
+        messages.push(Self::typed(TypedMessage::User {
+            username: self.config.username.clone(),
+            realname: self.config.realname.clone(),
+        }));
+        messages
+    }
+
+    /// Connection to the server was lost. Buffers are kept, so channels can be
+    /// rejoined after registering again. Incomplete batches are handled with
+    /// the messages received so far.
+    pub fn disconnected(&mut self) {
+        self.state = ClientState::Disconnected;
+        for batch in self.batches.flush() {
+            let started = batch.started;
+            self.handle_batch(batch, started);
+        }
+        for buffer in &mut self.buffers {
+            buffer.channel = None;
+            buffer.history.pending = None;
+        }
+    }
+
+    /// Join a channel, its buffer is created if it does not exist yet. The key
+    /// is remembered to rejoin the channel after reconnecting, and used when
+    /// joining without one.
+    pub fn join(&mut self, channel: &str, key: Option<&str>) -> Result<Messages, command::Error> {
+        let channels: Vec<AttrValue> = vec![channel.to_owned().into()];
+        let keys: Vec<AttrValue> = key.map(|key| key.to_owned().into()).into_iter().collect();
+        let command = UserCommand::Join {
+            channels: channels.clone(),
+            keys: keys.clone(),
+        };
+        command::check_limits(&command, &self.server_info)?;
+        Ok(self.join_channels(channels, keys))
+    }
+
+    /// Handle the input of a buffer, e.g. `/join #rust` or text to send to
+    /// the channel. Returns the messages to send, errors are shown in the
+    /// buffer. `now` is the time of the lines added to the buffers.
+    pub fn command(&mut self, buffer_id: u64, input: &str, now: Duration) -> Messages {
+        let Some(idx) = self.buffers.iter().position(|b| b.id == buffer_id) else {
+            return vec![];
+        };
+        let command = match command::parse(input, &self.buffers[idx].name, &self.server_info) {
+            Ok(command) => command,
+            Err(err) => {
+                self.buffers[idx].push(None, LineKind::Error, err.to_string().into(), now);
+                return vec![];
+            }
+        };
+
+        let message = match command {
+            UserCommand::Message { target, text } => {
+                return self.send_text(TypedMessage::Privmsg { target, text }, now)
+            }
+            UserCommand::Action { target, text } => {
+                return self.send_text(
+                    TypedMessage::Privmsg {
+                        target,
+                        text: Ctcp::new("ACTION", Some(&text)).to_string().into(),
+                    },
+                    now,
+                )
+            }
+            UserCommand::Ctcp {
+                target,
+                command,
+                params,
+            } => {
+                return self.send_text(
+                    TypedMessage::Privmsg {
+                        target,
+                        text: Ctcp::new(&command, params.as_deref()).to_string().into(),
+                    },
+                    now,
+                )
+            }
+            UserCommand::Notice { target, text } => {
+                return self.send_text(TypedMessage::Notice { target, text }, now)
+            }
+            UserCommand::Join { channels, keys } => return self.join_channels(channels, keys),
+            UserCommand::Part { channel, reason } => TypedMessage::Part {
+                channels: vec![channel],
+                reason,
+            },
+            UserCommand::Query { nick, text } => {
+                let name = BufferName::Query(self.server_info.nickname(&nick));
+                let id = Self::buffer_entry(&mut self.buffers, name).id;
+                self.select(id);
+                return match text {
+                    Some(text) => self.send_text(TypedMessage::Privmsg { target: nick, text }, now),
+                    None => vec![],
+                };
+            }
+            UserCommand::Nick { nickname } => TypedMessage::Nick { nickname },
+            UserCommand::Topic { channel, topic } => TypedMessage::Topic { channel, topic },
+            UserCommand::Mode {
+                target,
+                modestring,
+                arguments,
+            } => TypedMessage::Mode {
+                target,
+                modestring,
+                arguments,
+            },
+            UserCommand::Kick {
+                channel,
+                nick,
+                reason,
+            } => TypedMessage::Kick {
+                channel,
+                users: vec![nick],
+                comment: reason,
+            },
+            UserCommand::Ban { channel, mask } => {
+                return ModeBuilder::new(&channel)
+                    .change(ModeChange::List {
+                        set: true,
+                        mode: 'b',
+                        mask,
+                    })
+                    .build(&self.server_info)
+            }
+            UserCommand::Invite { nick, channel } => TypedMessage::Invite {
+                nickname: nick,
+                channel,
+            },
+            UserCommand::Whois { nick } => TypedMessage::Whois {
+                server: None,
+                nickname: nick,
+            },
+            UserCommand::Away { message } => TypedMessage::Away { text: message },
+            UserCommand::Quit { reason } => TypedMessage::Quit { reason },
+            UserCommand::Quote { message } => return vec![message],
+            UserCommand::Close => {
+                let buffer = self.buffers.remove(idx);
+                match (buffer.name, buffer.channel) {
+                    (BufferName::Channel(channel), Some(_)) => TypedMessage::Part {
+                        channels: vec![channel.as_attr_value()],
+                        reason: None,
+                    },
+                    _ => return vec![],
+                }
+            }
+        };
+        vec![Self::typed(message)]
+    }
+
+    /// Send a message or notice, which is added to our buffers right away.
+    /// Long texts are split into several messages, see `Splitter`.
+    fn send_text(&mut self, msg: TypedMessage, now: Duration) -> Messages {
+        let echo = Message::typed(msg.clone())
+            .user(User {
+                nick: self.nickname.as_attr_value(),
+                user: self.user.clone(),
+                host: self.host.clone(),
+            })
+            .build();
+        if let MessageType::Generic(generic) = &echo.msg_type {
+            self.route(&echo, generic, now);
+        }
+        // our own messages do not count as unread
+        let nick = self.nickname.clone();
+        let echoed = match &msg {
+            TypedMessage::Privmsg { target, .. } | TypedMessage::Notice { target, .. } => {
+                self.message_buffer(target, Some(&nick))
+            }
+            _ => None,
+        };
+        if let Some(buffer) = self
+            .buffers
+            .iter_mut()
+            .find(|b| Some(&b.name) == echoed.as_ref())
+        {
+            buffer.mark_read();
+        }
+
+        let (command, target, text) = match &msg {
+            TypedMessage::Privmsg { target, text } => ("PRIVMSG", target, text),
+            TypedMessage::Notice { target, text } => ("NOTICE", target, text),
+            _ => return vec![Self::typed(msg)],
+        };
+        let multiline = match self.cap_negotiator.is_enabled("draft/multiline") {
+            true => self
+                .cap_negotiator
+                .available("draft/multiline")
+                .and_then(Multiline::parse),
+            false => None,
+        };
+        let splitter = Splitter::new(
+            self.nickname.as_str(),
+            self.user.as_deref(),
+            self.host.as_deref(),
+        )
+        .multiline(multiline);
+
+        self.batch_id += 1;
+        let batch_ref = format!("ml{}", self.batch_id);
+        match splitter.messages(command, target, text, &[], &batch_ref) {
+            Ok(messages) => messages,
+            Err(err) => {
+                let nick = self.nickname.clone();
+                let buffer = match self.message_buffer(target, Some(&nick)) {
+                    Some(name) => Self::buffer_entry(&mut self.buffers, name),
+                    None => self.server_buffer(&None),
+                };
+                buffer.push(None, LineKind::Error, err.to_string().into(), now);
+                vec![]
+            }
+        }
+    }
+
+    /// Request older messages of a buffer, e.g. when the user scrolled to its
+    /// top. Nothing is sent while a request is pending, or if there are no
+    /// older messages.
+    pub fn load_history(&mut self, buffer_id: u64) -> Messages {
+        let Some(buffer) = self.buffers.iter().find(|b| b.id == buffer_id) else {
+            return vec![];
+        };
+        if buffer.history.complete || matches!(buffer.name, BufferName::Server(_)) {
+            return vec![];
+        }
+        let request = match buffer.lines.first() {
+            Some(line) => Request::Before(Reference::line(line)),
+            None => Request::Latest,
+        };
+        let name = buffer.name.clone();
+        self.request_history(&name, request).into_iter().collect()
+    }
+
+    /// Handle a message from the server, returns the messages to send back.
+    /// `now` is the time the message was received, see `Keepalive`.
+    pub fn handle(&mut self, message: &Message, now: Duration) -> Result<Messages, Error> {
+        self.keepalive.received(now);
+
+        let mut messages = match &message.msg_type {
+            MessageType::Capability(msg_cap) => self.cap_negotiator.handle(msg_cap.clone())?,
+            MessageType::Generic(msg) => match self.batches.add(message, now) {
+                Assembled::Unbatched => self.handle_generic(message, msg, now),
+                Assembled::Collected => vec![],
+                Assembled::Complete(batch) => self.handle_batch(batch, now),
+            },
+        };
+
+        if self.state == ClientState::Authenticating {
+            messages.extend(self.authenticate(message));
+        }
+
+        if self.state == ClientState::CapLs && self.cap_negotiator.ready_to_end() {
+            messages.extend(self.start_authentication());
+        }
+
+        Ok(messages)
+    }
+
+    /// Check the connection, should be called regularly, e.g. every second.
+    /// After a timeout the client is `Disconnected` and the connection should
+    /// be closed.
+    pub fn tick(&mut self, now: Duration) -> Result<Messages, Error> {
+        let mut messages = vec![];
+        for batch in self.batches.expire(now) {
+            messages.extend(self.handle_batch(batch, now));
+        }
+        if self.state == ClientState::Disconnected {
+            return Ok(messages);
+        }
+
+        match self.keepalive.tick(now) {
+            Ok(ping) => {
+                messages.extend(ping);
+                Ok(messages)
+            }
+            Err(timeout) => {
+                self.state = ClientState::Disconnected;
+                Err(Error::Timeout(timeout))
+            }
+        }
+    }
+
+    fn handle_generic(
+        &mut self,
+        message: &Message,
+        msg: &GenericMessage,
+        now: Duration,
+    ) -> Messages {
+        let time = self.message_time(message).unwrap_or(now);
+
+        // decided before our `JOIN` is added to the buffers
+        let missing = self.missing_history(message, msg, time);
+        self.route_tagged(message, msg, time, false);
+        self.mark_selected_read();
+        self.update_channels(message, msg);
+        self.update_hostmask(message, msg);
+
+        let mut messages = match &msg.command {
+            Command::Digit3(numeric::RPL_WELCOME) => {
+                if let Some(nickname) = msg.parameters.first() {
+                    self.nickname = self.server_info.nickname(nickname);
+                }
+                self.state = ClientState::Registered;
+                self.keepalive.reset(now);
+                let mut messages = self.rejoin();
+                if self.cap_negotiator.is_enabled("draft/chathistory") {
+                    messages.push(history::targets(Duration::ZERO, now, self.history_limit()));
+                }
+                messages
+            }
+            Command::Digit3(
+                numeric::ERR_ERRONEUSNICKNAME
+                | numeric::ERR_NICKNAMEINUSE
+                | numeric::ERR_NICKCOLLISION,
+            ) if !self.is_registered() => self.next_nickname(),
+            Command::Digit3(numeric::RPL_ISUPPORT) => {
+                if let Ok(NumericReply::ISupport { tokens, .. }) = NumericReply::try_from(msg) {
+                    let mut server_info = self.server_info.clone();
+                    server_info.apply(&tokens);
+                    self.set_server_info(server_info);
+                }
+                vec![]
+            }
+            Command::Cmd(cmd)
+                if cmd == "FAIL" && msg.parameters.first().is_some_and(|p| p == "CHATHISTORY") =>
+            {
+                // the failed request is not known, so allow new requests
+                for buffer in &mut self.buffers {
+                    buffer.history.pending = None;
+                }
+                vec![]
+            }
+            Command::Cmd(_) => match (TypedMessage::try_from(msg), &message.source) {
+                (Ok(TypedMessage::Ping { token }), _) => {
+                    vec![Self::typed(TypedMessage::Pong {
+                        server: None,
+                        token,
+                    })]
+                }
+                (Ok(TypedMessage::Pong { token, .. }), _) => {
+                    self.keepalive.pong(&token, now);
+                    vec![]
+                }
+                (Ok(TypedMessage::Nick { nickname }), Some(Source::User(user)))
+                    if self.nickname == *user.nick =>
+                {
+                    self.nickname = self.server_info.nickname(&nickname);
+                    vec![]
+                }
+                (Ok(TypedMessage::Privmsg { text, .. }), Some(Source::User(user)))
+                    if self.nickname != *user.nick =>
+                {
+                    self.ctcp_reply(&user.nick, &text, now)
+                }
+                _ => vec![],
+            },
+            _ => vec![],
+        };
+
+        for (name, request) in missing {
+            messages.extend(self.request_history(&name, request));
+        }
+        messages
+    }
+
+    /// Number of messages per `CHATHISTORY` request
+    fn history_limit(&self) -> usize {
+        match self.server_info.chathistory {
+            Some(max) if max > 0 => max.min(history::DEFAULT_LIMIT),
+            _ => history::DEFAULT_LIMIT,
+        }
+    }
+
+    /// Request history of a buffer, unless another request is pending
+    fn request_history(&mut self, name: &BufferName, request: Request) -> Option<Message> {
+        if !self.cap_negotiator.is_enabled("draft/chathistory") {
+            return None;
+        }
+        let limit = self.history_limit();
+        let buffer = self.buffers.iter_mut().find(|b| b.name == *name)?;
+        if buffer.history.pending.is_some() {
+            return None;
+        }
+        let message = request.message(buffer.name.as_str(), limit);
+        buffer.history.pending = Some(request);
+        Some(message)
+    }
+
+    /// Messages missed in a buffer: the latest ones, or those since its last
+    /// line, e.g. after reconnecting
+    fn missing_since(&self, name: &BufferName, now: Duration) -> Request {
+        let last = self
+            .buffers
+            .iter()
+            .find(|b| b.name == *name)
+            .and_then(|buffer| buffer.lines.last());
+        match last {
+            Some(line) => Request::Between(Reference::line(line), Reference::Timestamp(now)),
+            None => Request::Latest,
+        }
+    }
+
+    /// History to request for the channels we join
+    fn missing_history(
+        &self,
+        message: &Message,
+        msg: &GenericMessage,
+        time: Duration,
+    ) -> Vec<(BufferName, Request)> {
+        let own =
+            matches!(&message.source, Some(Source::User(user)) if self.nickname == *user.nick);
+        match TypedMessage::try_from(msg) {
+            Ok(TypedMessage::Join { channels, .. }) if own => channels
+                .iter()
+                .map(|channel| {
+                    let name = BufferName::Channel(self.server_info.channel_name(channel));
+                    let request = self.missing_since(&name, time);
+                    (name, request)
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Handle a batch once it ended, or incomplete after a timeout or once
+    /// the connection was lost
+    fn handle_batch(&mut self, batch: Batch, now: Duration) -> Messages {
+        match &batch.kind {
+            Kind::Netsplit { server1, server2 } => {
+                let servers = format!("{server1} {server2}").into();
+                self.netsplit(&batch, servers, false, now);
+                vec![]
+            }
+            Kind::Netjoin { server1, server2 } => {
+                let servers = format!("{server1} {server2}").into();
+                self.netsplit(&batch, servers, true, now);
+                vec![]
+            }
+            Kind::Chathistory { target } => {
+                let target = target.clone();
+                self.chathistory(&batch, &target, now);
+                vec![]
+            }
+            Kind::ChathistoryTargets => self.chathistory_targets(&batch, now),
+            Kind::Multiline { .. } => match batch.multiline_message() {
+                Some(message) => self.handle_unbatched(&message, now),
+                None => vec![],
+            },
+            Kind::LabeledResponse | Kind::Other { .. } => {
+                let mut messages = vec![];
+                for entry in batch.entries {
+                    messages.extend(match entry {
+                        Entry::Message(message) => self.handle_unbatched(&message, now),
+                        Entry::Batch(batch) => self.handle_batch(batch, now),
+                    });
+                }
+                messages
+            }
+        }
+    }
+
+    /// Message of a batch, handled as if it was sent on its own
+    fn handle_unbatched(&mut self, message: &Message, now: Duration) -> Messages {
+        match &message.msg_type {
+            MessageType::Generic(msg) => self.handle_generic(message, msg, now),
+            MessageType::Capability(_) => vec![],
+        }
+    }
+
+    /// Users quit or came back because servers split or joined again, which
+    /// is shown as a single line per buffer
+    fn netsplit(&mut self, batch: &Batch, servers: AttrValue, join: bool, now: Duration) {
+        let time = batch
+            .messages()
+            .find_map(|message| self.message_time(message))
+            .unwrap_or(now);
+        let mut nicks: BTreeMap<u64, Vec<AttrValue>> = BTreeMap::new();
+
+        for message in batch.messages() {
+            let (MessageType::Generic(msg), Some(Source::User(user))) =
+                (&message.msg_type, &message.source)
+            else {
+                continue;
+            };
+            let nick = self.server_info.nickname(&user.nick);
+            let buffers: Vec<u64> = match TypedMessage::try_from(msg) {
+                Ok(TypedMessage::Quit { .. }) if !join => {
+                    self.user_buffers(&nick).map(|buffer| buffer.id).collect()
+                }
+                Ok(TypedMessage::Join { channels, .. }) if join => channels
+                    .iter()
+                    .filter_map(|channel| self.channel_buffer(channel).map(|buffer| buffer.id))
+                    .collect(),
+                _ => vec![],
+            };
+            for id in buffers {
+                nicks.entry(id).or_default().push(user.nick.clone());
+            }
+            self.update_channels(message, msg);
+        }
+
+        for buffer in &mut self.buffers {
+            if let Some(nicks) = nicks.remove(&buffer.id) {
+                let kind = match join {
+                    true => LineKind::Netjoin { nicks },
+                    false => LineKind::Netsplit { nicks },
+                };
+                buffer.push(None, kind, servers.clone(), time);
+            }
+        }
+        self.mark_selected_read();
+    }
+
+    /// Reply to a `CHATHISTORY` request, which is sorted into the buffer of
+    /// the target
+    fn chathistory(&mut self, batch: &Batch, target: &str, now: Duration) {
+        let mut count = 0;
+        for entry in &batch.entries {
+            let message = match entry {
+                Entry::Message(message) => Some(message.clone()),
+                Entry::Batch(nested) => nested.multiline_message(),
+            };
+            if let Some(message) = message {
+                if let MessageType::Generic(msg) = &message.msg_type {
+                    count += 1;
+                    let time = self.message_time(&message).unwrap_or(now);
+                    self.route_tagged(&message, msg, time, true);
+                }
+            }
+        }
+
+        let limit = self.history_limit();
+        let name = self.target_buffer(target);
+        if let Some(buffer) = self.buffers.iter_mut().find(|b| b.name == name) {
+            if let Some(request) = buffer.history.pending.take() {
+                buffer.history.complete |= batch.complete && request.completes(count, limit);
+            }
+        }
+    }
+
+    /// Targets of recent messages, which open query buffers with their history
+    fn chathistory_targets(&mut self, batch: &Batch, now: Duration) -> Messages {
+        let mut messages = vec![];
+        for message in batch.messages() {
+            let MessageType::Generic(msg) = &message.msg_type else {
+                continue;
+            };
+            // `CHATHISTORY TARGETS alice 2024-12-24T18:30:00.000Z`
+            let target = match msg.parameters.as_slice() {
+                [subcommand, target, ..] if subcommand == "TARGETS" => target,
+                _ => continue,
+            };
+            if self.server_info.is_channel(target) {
+                continue;
+            }
+            let name = self.target_buffer(target);
+            Self::buffer_entry(&mut self.buffers, name.clone());
+            let request = self.missing_since(&name, now);
+            messages.extend(self.request_history(&name, request));
+        }
+        messages
+    }
+
+    /// Buffer of a channel or query target
+    fn target_buffer(&self, target: &str) -> BufferName {
+        match self.server_info.is_channel(target) {
+            true => BufferName::Channel(self.server_info.channel_name(target)),
+            false => BufferName::Query(self.server_info.nickname(target)),
+        }
+    }
+
+    /// Route a message and tag its lines with its `msgid`. History is sorted
+    /// into the buffers and does not count as unread.
+    fn route_tagged(
+        &mut self,
+        message: &Message,
+        msg: &GenericMessage,
+        time: Duration,
+        history: bool,
+    ) {
+        let marks = self
+            .buffers
+            .iter()
+            .map(|buffer| (buffer.id, buffer.mark()))
+            .collect::<HashMap<_, _>>();
+        self.route(message, msg, time);
+
+        let msgid: Option<AttrValue> = message.tags.get("msgid").cloned().flatten().map(Into::into);
+        for buffer in &mut self.buffers {
+            let mark = marks.get(&buffer.id).copied().unwrap_or_default();
+            for line in buffer.lines_since(&mark) {
+                line.msgid = msgid.clone();
+            }
+            if history {
+                buffer.into_history(&mark);
+            }
+        }
+    }
+
+    /// Automatic reply to a CTCP query of `nick`, if enabled and not rate
+    /// limited
+    fn ctcp_reply(&mut self, nick: &AttrValue, text: &str, now: Duration) -> Messages {
+        let Some(reply) = Ctcp::parse(text).and_then(|query| self.config.ctcp.reply(&query, now))
+        else {
+            return vec![];
+        };
+        if !self.ctcp_limit.allow(now) {
+            return vec![];
+        }
+        vec![Self::typed(TypedMessage::Notice {
+            target: nick.clone(),
+            text: reply.to_string().into(),
+        })]
+    }
+
+    /// Time the message was sent, if the server tells with `server-time`
+    fn message_time(&self, message: &Message) -> Option<Duration> {
+        if !self.cap_negotiator.is_enabled("server-time") {
+            return None;
+        }
+        let time = message.tags.get("time")?.as_deref()?;
+        timestamp::parse(time)
+    }
+
+    /// Learn user name and host of our prefix, which `Splitter` needs to
+    /// know how much text fits into a message
+    fn update_hostmask(&mut self, message: &Message, msg: &GenericMessage) {
+        if let Some(Source::User(user)) = &message.source {
+            if self.nickname == *user.nick {
+                if user.user.is_some() && user.host.is_some() {
+                    self.user = user.user.clone();
+                    self.host = user.host.clone();
+                }
+                if msg.command == Command::Cmd("CHGHOST".into()) {
+                    if let [user, host, ..] = msg.parameters.as_slice() {
+                        self.user = Some(user.clone());
+                        self.host = Some(host.clone());
+                    }
+                }
+            }
+        }
+
+        // `Welcome to the network, nick!user@host`
+        if msg.command == Command::Digit3(numeric::RPL_WELCOME) {
+            let mask = msg
+                .parameters
+                .last()
+                .and_then(|text| text.split(' ').next_back());
+            if let Some((_, userhost)) = mask.and_then(|mask| mask.split_once('!')) {
+                if let Some((user, host)) = userhost.split_once('@') {
+                    self.user = Some(user.to_owned().into());
+                    self.host = Some(host.to_owned().into());
+                }
+            }
+        }
+    }
+
+    /// Add a message to the buffers it belongs to. This happens before the
+    /// channel state is updated, so the channels of a quitting user are
+    /// still known.
+    fn route(&mut self, message: &Message, msg: &GenericMessage, time: Duration) {
+        if let Command::Digit3(_) = msg.command {
+            return self.route_numeric(message, msg, time);
+        }
+
+        let source = message.source.clone();
+        let nick = match &source {
+            Some(Source::User(user)) => Some(self.server_info.nickname(&user.nick)),
+            _ => None,
+        };
+
+        match TypedMessage::try_from(msg) {
+            Ok(TypedMessage::Privmsg { target, text }) => {
+                let (kind, text) = match Ctcp::parse(&text) {
+                    Some(ctcp) if ctcp.command == "ACTION" => {
+                        (LineKind::Action, ctcp.params.unwrap_or_default())
+                    }
+                    Some(ctcp) => {
+                        // queries do not open a query buffer
+                        let name = self.existing_message_buffer(&target, nick.as_ref());
+                        let buffer = match name {
+                            Some(name) => Self::buffer_entry(&mut self.buffers, name),
+                            None => self.server_buffer(&source),
+                        };
+                        let kind = LineKind::Ctcp {
+                            command: ctcp.command,
+                        };
+                        buffer.push(source, kind, ctcp.params.unwrap_or_default(), time);
+                        return;
+                    }
+                    None => (LineKind::Message, text),
+                };
+                let highlight = self.is_highlight(nick.as_ref(), &text);
+                let buffer = match self.message_buffer(&target, nick.as_ref()) {
+                    Some(name) => Self::buffer_entry(&mut self.buffers, name),
+                    None => self.server_buffer(&source),
+                };
+                match highlight {
+                    true => buffer.push_highlight(source, kind, text, time),
+                    false => buffer.push(source, kind, text, time),
+                }
+            }
+            Ok(TypedMessage::Notice { target, text }) => {
+                let (kind, text, highlight) = match Ctcp::parse(&text) {
+                    Some(ctcp) => (
+                        LineKind::CtcpReply {
+                            command: ctcp.command,
+                        },
+                        ctcp.params.unwrap_or_default(),
+                        false,
+                    ),
+                    None => {
+                        let highlight = self.is_highlight(nick.as_ref(), &text);
+                        (LineKind::Notice, text, highlight)
+                    }
+                };
+                // notices of users only open a query if there is one already
+                let name = self.existing_message_buffer(&target, nick.as_ref());
+                let buffer = match name {
+                    Some(name) => Self::buffer_entry(&mut self.buffers, name),
+                    None => self.server_buffer(&source),
+                };
+                match highlight {
+                    true => buffer.push_highlight(source, kind, text, time),
+                    false => buffer.push(source, kind, text, time),
+                }
+            }
+            Ok(TypedMessage::Join { channels, .. }) => {
+                for channel in channels {
+                    let name = BufferName::Channel(self.server_info.channel_name(&channel));
+                    Self::buffer_entry(&mut self.buffers, name).push(
+                        source.clone(),
+                        LineKind::Join,
+                        "".into(),
+                        time,
+                    );
+                }
+            }
+            Ok(TypedMessage::Part { channels, reason }) => {
+                for channel in channels {
+                    if let Some(buffer) = self.channel_buffer(&channel) {
+                        buffer.push(
+                            source.clone(),
+                            LineKind::Part,
+                            reason.clone().unwrap_or_default(),
+                            time,
+                        );
+                    }
+                }
+            }
+            Ok(TypedMessage::Kick {
+                channel,
+                users,
+                comment,
+            }) => {
+                if let Some(buffer) = self.channel_buffer(&channel) {
+                    for nick in users {
+                        buffer.push(
+                            source.clone(),
+                            LineKind::Kick { nick },
+                            comment.clone().unwrap_or_default(),
+                            time,
+                        );
+                    }
+                }
+            }
+            Ok(TypedMessage::Quit { reason }) => {
+                let Some(nick) = nick else { return };
+                for buffer in self.user_buffers(&nick) {
+                    buffer.push(
+                        source.clone(),
+                        LineKind::Quit,
+                        reason.clone().unwrap_or_default(),
+                        time,
+                    );
+                }
+            }
+            Ok(TypedMessage::Nick { nickname }) => {
+                let Some(nick) = nick else { return };
+                let renamed = BufferName::Query(self.server_info.nickname(&nickname));
+                // keep the query, if the new nick has one already
+                let taken = renamed != BufferName::Query(nick.clone())
+                    && self.buffers.iter().any(|buffer| buffer.name == renamed);
+                for buffer in self.user_buffers(&nick) {
+                    buffer.push(
+                        source.clone(),
+                        LineKind::Nick {
+                            nickname: nickname.clone(),
+                        },
+                        "".into(),
+                        time,
+                    );
+                    if matches!(buffer.name, BufferName::Query(_)) && !taken {
+                        buffer.name = renamed.clone();
+                    }
+                }
+            }
+            Ok(TypedMessage::Topic {
+                channel,
+                topic: Some(topic),
+            }) => {
+                if let Some(buffer) = self.channel_buffer(&channel) {
+                    buffer.push(source, LineKind::Topic, topic, time);
+                }
+            }
+            Ok(TypedMessage::Mode {
+                target,
+                modestring: Some(modestring),
+                arguments,
+            }) => {
+                let text = std::iter::once(modestring)
+                    .chain(arguments)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let buffer = match self.server_info.is_channel(&target) {
+                    true => self.channel_buffer(&target),
+                    false => Some(self.server_buffer(&source)),
+                };
+                if let Some(buffer) = buffer {
+                    buffer.push(source, LineKind::Mode, text.into(), time);
+                }
+            }
+            Ok(TypedMessage::Error { reason }) => {
+                self.server_buffer(&source)
+                    .push(source, LineKind::Error, reason, time);
+            }
+            _ => (),
+        }
+    }
+
+    fn route_numeric(&mut self, message: &Message, msg: &GenericMessage, time: Duration) {
+        let source = message.source.clone();
+        let Some(numeric) = msg.command.numeric() else {
+            return;
+        };
+        // the first parameter is our nick name
+        let parameters = msg.parameters.get(1..).unwrap_or_default();
+
+        match numeric.code {
+            numeric::RPL_TOPIC => {
+                if let (Some(channel), Some(topic)) = (parameters.first(), parameters.get(1)) {
+                    if let Some(buffer) = self.channel_buffer(channel) {
+                        buffer.push(source, LineKind::Topic, topic.clone(), time);
+                    }
+                }
+            }
+            // shown as part of the channel state
+            numeric::RPL_ISUPPORT
+            | numeric::RPL_CHANNELMODEIS
+            | numeric::RPL_CREATIONTIME
+            | numeric::RPL_TOPICWHOTIME
+            | numeric::RPL_NAMREPLY
+            | numeric::RPL_ENDOFNAMES => (),
+            _ => {
+                let text = parameters.join(" ").into();
+                let kind = match numeric.is_error() {
+                    true => LineKind::Error,
+                    false => LineKind::Message,
+                };
+                // errors about a channel are shown in its buffer, if there is one
+                let channel = parameters
+                    .first()
+                    .filter(|_| numeric.is_error() && parameters.len() > 1)
+                    .map(|channel| BufferName::Channel(self.server_info.channel_name(channel)));
+                match channel.and_then(|name| self.buffers.iter().position(|b| b.name == name)) {
+                    Some(idx) => self.buffers[idx].push(source, kind, text, time),
+                    None => self.server_buffer(&source).push(source, kind, text, time),
+                }
+            }
+        }
+    }
+
+    /// Buffer of a message sent to `target`, which is either the channel or a
+    /// query with the other user. `None` for messages of the server.
+    fn message_buffer(&self, target: &str, nick: Option<&Nickname>) -> Option<BufferName> {
+        let channel = target.trim_start_matches(self.server_info.statusmsg.as_slice());
+        if self.server_info.is_channel(channel) {
+            return Some(BufferName::Channel(self.server_info.channel_name(channel)));
+        }
+
+        let nick = nick?;
+        if *nick == self.nickname {
+            // our own message, echoed by the server
+            Some(BufferName::Query(self.server_info.nickname(target)))
+        } else {
+            Some(BufferName::Query(nick.clone()))
+        }
+    }
+
+    /// Message of another user which mentions us
+    fn is_highlight(&self, nick: Option<&Nickname>, text: &str) -> bool {
+        nick.is_some_and(|nick| *nick != self.nickname)
+            && self.highlighter.matches(text, &self.nickname)
+    }
+
+    /// Buffer of a message like `message_buffer`, but queries only if the
+    /// buffer exists already
+    fn existing_message_buffer(&self, target: &str, nick: Option<&Nickname>) -> Option<BufferName> {
+        self.message_buffer(target, nick)
+            .filter(|name| name.channel().is_some() || self.buffers.iter().any(|b| b.name == *name))
+    }
+
+    /// Existing buffer of a channel
+    fn channel_buffer(&mut self, channel: &str) -> Option<&mut Buffer> {
+        let name = BufferName::Channel(self.server_info.channel_name(channel));
+        self.buffers.iter_mut().find(|buffer| buffer.name == name)
+    }
+
+    /// Buffers of channels the user is in, and the query with the user
+    fn user_buffers<'a>(&'a mut self, nick: &'a Nickname) -> impl Iterator<Item = &'a mut Buffer> {
+        self.buffers
+            .iter_mut()
+            .filter(move |buffer| match &buffer.name {
+                BufferName::Query(query) => query == nick,
+                _ => buffer
+                    .channel
+                    .as_ref()
+                    .is_some_and(|channel| channel.members.contains_key(nick)),
+            })
+    }
+
+    /// Buffer of the server, which is created as the first buffer
+    fn server_buffer(&mut self, source: &Option<Source>) -> &mut Buffer {
+        let idx = match self
+            .buffers
+            .iter()
+            .position(|buffer| matches!(buffer.name, BufferName::Server(_)))
+        {
+            Some(idx) => idx,
+            None => {
+                let name = match source {
+                    Some(Source::Host(host)) => host.clone(),
+                    _ => "server".into(),
+                };
+                let id = Self::next_buffer_id(&self.buffers);
+                self.buffers
+                    .insert(0, Buffer::new(id, BufferName::Server(name)));
+                0
+            }
+        };
+        &mut self.buffers[idx]
+    }
+
+    /// Buffer with the name, which is created if it does not exist yet
+    fn buffer_entry(buffers: &mut Vec<Buffer>, name: BufferName) -> &mut Buffer {
+        let idx = match buffers.iter().position(|buffer| buffer.name == name) {
+            Some(idx) => idx,
+            None => {
+                buffers.push(Buffer::new(Self::next_buffer_id(buffers), name));
+                buffers.len() - 1
+            }
+        };
+        &mut buffers[idx]
+    }
+
+    fn next_buffer_id(buffers: &[Buffer]) -> u64 {
+        buffers.iter().map(|b| b.id + 1).max().unwrap_or(0)
+    }
+
+    /// Keep the state of joined channels up to date
+    fn update_channels(&mut self, message: &Message, msg: &GenericMessage) {
+        let server_info = &self.server_info;
+        let buffers = &mut self.buffers;
+
+        if let Command::Digit3(_) = msg.command {
+            match NumericReply::try_from(msg) {
+                Ok(NumericReply::NamReply { channel, nicks, .. }) => {
+                    if let Some(channel) = Self::channel_mut(buffers, server_info, &channel) {
+                        channel.names(&nicks, server_info);
+                    }
+                }
+                Ok(NumericReply::EndOfNames { channel, .. }) => {
+                    if let Some(channel) = Self::channel_mut(buffers, server_info, &channel) {
+                        channel.end_of_names();
+                    }
+                }
+                Ok(NumericReply::Topic { channel, topic, .. }) => {
+                    if let Some(channel) = Self::channel_mut(buffers, server_info, &channel) {
+                        channel.topic = Some(Topic {
+                            text: topic,
+                            setter: None,
+                            set_at: None,
+                        });
+                    }
+                }
+                Ok(NumericReply::NoTopic { channel, .. }) => {
+                    if let Some(channel) = Self::channel_mut(buffers, server_info, &channel) {
+                        channel.topic = None;
+                    }
+                }
+                Ok(NumericReply::TopicWhoTime {
+                    channel,
+                    setter,
+                    set_at,
+                    ..
+                }) => {
+                    if let Some(topic) = Self::channel_mut(buffers, server_info, &channel)
+                        .and_then(|channel| channel.topic.as_mut())
+                    {
+                        topic.setter = Some(setter);
+                        topic.set_at = Some(set_at);
+                    }
+                }
+                Ok(NumericReply::ChannelModeIs {
+                    channel,
+                    modestring,
+                    arguments,
+                    ..
+                }) => {
+                    let channel = Self::channel_mut(buffers, server_info, &channel);
+                    if let (Some(channel), Ok(changes)) =
+                        (channel, mode::parse(&modestring, &arguments, server_info))
+                    {
+                        channel.modes.clear();
+                        channel.apply_modes(&changes, server_info);
+                    }
+                }
+                Ok(NumericReply::CreationTime {
+                    channel, created, ..
+                }) => {
+                    if let Some(channel) = Self::channel_mut(buffers, server_info, &channel) {
+                        channel.created = Some(created);
+                    }
+                }
+                _ => (),
+            }
+            return;
+        }
+
+        let Some(Source::User(user)) = &message.source else {
+            return;
+        };
+        let nick = server_info.nickname(&user.nick);
+        let own = nick == self.nickname;
+
+        match TypedMessage::try_from(msg) {
+            Ok(TypedMessage::Join { channels, .. }) => {
+                for name in channels {
+                    if own {
+                        let name = BufferName::Channel(server_info.channel_name(&name));
+                        let buffer = Self::buffer_entry(buffers, name);
+                        buffer.channel = Some(Channel::default());
+                        buffer.rejoin = true;
+                    }
+
+                    if let Some(channel) = Self::channel_mut(buffers, server_info, &name) {
+                        channel.join(Member {
+                            user: user.user.clone(),
+                            host: user.host.clone(),
+                            ..Member::new(nick.clone())
+                        });
+                    }
+                }
+            }
+            Ok(TypedMessage::Part { channels, .. }) => {
+                for name in channels {
+                    Self::leave(buffers, server_info, &name, &nick, own);
+                }
+            }
+            Ok(TypedMessage::Kick { channel, users, .. }) => {
+                for kicked in users {
+                    let kicked = server_info.nickname(&kicked);
+                    let own = kicked == self.nickname;
+                    Self::leave(buffers, server_info, &channel, &kicked, own);
+                }
+            }
+            Ok(TypedMessage::Quit { .. }) => {
+                for channel in buffers.iter_mut().filter_map(|b| b.channel.as_mut()) {
+                    channel.remove(&nick);
+                }
+            }
+            Ok(TypedMessage::Nick { nickname }) => {
+                let nickname = server_info.nickname(&nickname);
+                for channel in buffers.iter_mut().filter_map(|b| b.channel.as_mut()) {
+                    channel.rename(&nick, nickname.clone());
+                }
+            }
+            Ok(TypedMessage::Topic {
+                channel,
+                topic: Some(text),
+            }) => {
+                if let Some(channel) = Self::channel_mut(buffers, server_info, &channel) {
+                    channel.topic = (!text.is_empty()).then(|| Topic {
+                        text,
+                        setter: Some(user.nick.clone()),
+                        set_at: None,
+                    });
+                }
+            }
+            Ok(TypedMessage::Mode {
+                target,
+                modestring: Some(modestring),
+                arguments,
+            }) => {
+                let channel = Self::channel_mut(buffers, server_info, &target);
+                if let (Some(channel), Ok(changes)) =
+                    (channel, mode::parse(&modestring, &arguments, server_info))
+                {
+                    channel.apply_modes(&changes, server_info);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// State of a joined channel
+    fn channel_mut<'a>(
+        buffers: &'a mut [Buffer],
+        server_info: &ServerInfo,
+        name: &str,
+    ) -> Option<&'a mut Channel> {
+        if !server_info.is_channel(name) {
+            return None;
+        }
+        let name = BufferName::Channel(server_info.channel_name(name));
+        buffers
+            .iter_mut()
+            .find(|buffer| buffer.name == name)
+            .and_then(|buffer| buffer.channel.as_mut())
+    }
+
+    /// Someone left a channel. If it was us the channel is not joined anymore,
+    /// and not rejoined after reconnecting.
+    fn leave(
+        buffers: &mut [Buffer],
+        server_info: &ServerInfo,
+        name: &str,
+        nick: &Nickname,
+        own: bool,
+    ) {
+        if own {
+            let name = BufferName::Channel(server_info.channel_name(name));
+            if let Some(buffer) = buffers.iter_mut().find(|buffer| buffer.name == name) {
+                buffer.channel = None;
+                buffer.rejoin = false;
+            }
+        } else if let Some(channel) = Self::channel_mut(buffers, server_info, name) {
+            channel.remove(nick);
+        }
+    }
+
+    /// Join the channels we were in or asked to join, e.g. after
+    /// reconnecting
+    fn rejoin(&self) -> Messages {
+        let channels = self
+            .buffers
+            .iter()
+            .filter(|buffer| buffer.rejoin && buffer.name.channel().is_some())
+            .map(|buffer| (buffer.name.as_attr_value(), buffer.key.clone()))
+            .collect();
+        self.join_messages(channels)
+    }
+
+    /// `JOIN`s for channels with optional keys, split to stay within the line
+    /// length and the `TARGMAX` of the server. Channels with keys are listed
+    /// first, since keys are matched by position.
+    /// Create the buffers of channels to join, and remember their keys
+    fn join_channels(&mut self, channels: Vec<AttrValue>, keys: Vec<AttrValue>) -> Messages {
+        let mut keys = keys.into_iter();
+        let mut joins = vec![];
+        for channel in channels {
+            let name = BufferName::Channel(self.server_info.channel_name(&channel));
+            let buffer = Self::buffer_entry(&mut self.buffers, name);
+            // without a key, the one we know is used
+            if let Some(key) = keys.next() {
+                buffer.key = Some(key);
+            }
+            buffer.rejoin = true;
+            joins.push((channel, buffer.key.clone()));
+        }
+        self.join_messages(joins)
+    }
+
+    fn join_messages(&self, channels: Vec<(AttrValue, Option<AttrValue>)>) -> Messages {
+        let (mut channels, without_key): (Vec<_>, Vec<_>) =
+            channels.into_iter().partition(|(_, key)| key.is_some());
+        channels.extend(without_key);
+        let max_targets = self.server_info.max_targets("JOIN");
+
+        let mut messages = vec![];
+        let mut batch = vec![];
+        let mut length = 0;
+
+        for (channel, key) in channels {
+            let channel_length = channel.len() + key.as_ref().map_or(0, |k| k.len() + 1) + 1;
+            let full = max_targets.is_some_and(|max| batch.len() >= max);
+            if !batch.is_empty() && (full || length + channel_length > MAX_JOIN_LENGTH) {
+                messages.push(Self::join_message(std::mem::take(&mut batch)));
+                length = 0;
+            }
+            length += channel_length;
+            batch.push((channel, key));
+        }
+
+        if !batch.is_empty() {
+            messages.push(Self::join_message(batch));
+        }
+        messages
+    }
+
+    fn join_message(channels: Vec<(AttrValue, Option<AttrValue>)>) -> Message {
+        let (channels, keys): (Vec<_>, Vec<_>) = channels.into_iter().unzip();
+        Self::typed(TypedMessage::Join {
+            channels,
+            keys: keys.into_iter().flatten().collect(),
+            account: None,
+            realname: None,
+        })
+    }
+
+    /// Names are compared according to the case mapping of the server, so
+    /// they are folded again whenever it changes
+    fn set_server_info(&mut self, server_info: ServerInfo) {
+        let casemapping = server_info.casemapping;
+        self.server_info = server_info;
+        if self.nickname.casemapping() == casemapping {
+            return;
+        }
+
+        self.nickname = self.nickname.with_casemapping(casemapping);
+        for buffer in &mut self.buffers {
+            buffer.name = buffer.name.with_casemapping(casemapping);
+            if let Some(channel) = &mut buffer.channel {
+                channel.set_casemapping(casemapping);
+            }
+        }
+    }
+
+    /// Try the next alternative nick name
+    fn next_nickname(&mut self) -> Messages {
+        if self.nick_attempts >= MAX_NICK_ATTEMPTS {
+            return vec![];
+        }
+
+        let nickname = match self.config.alt_nicknames.get(self.nick_attempts) {
+            Some(nickname) => nickname.to_string(),
+            None => {
+                // replace the last character, if the nick name is too long
+                let mut nickname = self.nickname.to_string();
+                if let Some(nicklen) = self.server_info.nicklen {
+                    while !nickname.is_empty() && nickname.chars().count() >= nicklen {
+                        nickname.pop();
+                    }
+                }
+                format!("{nickname}_")
+            }
+        };
+        self.nickname = self.server_info.nickname(&nickname);
+        self.nick_attempts += 1;
+
+        vec![Self::typed(TypedMessage::Nick {
+            nickname: self.nickname.as_attr_value(),
+        })]
+    }
+
+    /// Capability negotiation is done, authenticate if `sasl` is enabled
+    fn start_authentication(&mut self) -> Messages {
+        if self.cap_negotiator.is_enabled("sasl") {
+            let mechanisms = self
+                .cap_negotiator
+                .available("sasl")
+                .map(|cap| cap.value_list().to_vec())
+                .unwrap_or_default();
+
+            if let Ok(messages) = self.authenticator.start(&mechanisms) {
+                self.state = ClientState::Authenticating;
+                return messages;
+            }
+        }
+        self.end_negotiation()
+    }
+
+    fn authenticate(&mut self, message: &Message) -> Messages {
+        let mut messages = match self.authenticator.handle(message) {
+            Ok(messages) => messages,
+            // the server confirms the abort with ERR_SASLABORTED
+            Err(_) => return vec![self.authenticator.abort()],
+        };
+
+        if self.authenticator.is_done() {
+            messages.extend(self.end_negotiation());
+        }
+        messages
+    }
+
+    fn end_negotiation(&mut self) -> Messages {
+        self.state = ClientState::Registering;
+        vec![self.cap_negotiator.end()]
     }
 
-    pub fn handle(&mut self, _message: &Message) -> Result<(), Error> {
-        Ok(())
+    fn typed(msg: TypedMessage) -> Message {
+        Message::typed(msg).build()
     }
 }
@@ -0,0 +1,237 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use mechanism::Mechanism;
+use yew::AttrValue;
+
+/// SASL authentication, following the spec:
+///
+/// https://ircv3.net/specs/extensions/sasl-3.1
+use crate::irc::parser::{
+    generic_message::GenericMessage,
+    message::{Message, MessageType},
+    numeric,
+    typed_message::TypedMessage,
+    Command,
+};
+
+pub mod mechanism;
+
+#[cfg(test)]
+mod test;
+
+type Result = std::result::Result<Messages, Error>;
+type Messages = Vec<Message>;
+
+/// Maximum length of a single `AUTHENTICATE` payload. Longer payloads are
+/// split into multiple messages.
+const MAX_CHUNK_LENGTH: usize = 400;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// None of our mechanisms is supported by the server
+    NoMechanism,
+    /// `AUTHENTICATE` was received while no authentication is in progress
+    NotAuthenticating,
+    /// Payload of `AUTHENTICATE` is not valid base64
+    InvalidBase64(String),
+    /// Challenge of the server could not be interpreted by the mechanism
+    InvalidChallenge(String),
+    /// Server reported an error inside of the mechanism exchange
+    ServerError(String),
+    /// Server could not prove that it knows our credentials
+    ServerSignatureMismatch,
+    /// No random numbers available for nonces
+    Random(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum State {
+    Idle,
+    Authenticating,
+    Succeeded,
+    Failed,
+    Aborted,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authenticator {
+    /// Mechanisms of the client in order of preference
+    mechanisms: Vec<Box<dyn Mechanism>>,
+    /// Mechanisms advertised by the server, empty if unknown
+    server_mechanisms: Vec<AttrValue>,
+    /// Names of mechanisms which have been tried already
+    tried: Vec<&'static str>,
+    current: Option<Box<dyn Mechanism>>,
+    /// Base64 payload of a challenge which is split into multiple messages
+    incoming: String,
+    pub state: State,
+    /// Account name, as reported by `RPL_LOGGEDIN`
+    pub account: Option<AttrValue>,
+}
+
+impl Authenticator {
+    pub fn new(mechanisms: Vec<Box<dyn Mechanism>>) -> Self {
+        Authenticator {
+            mechanisms,
+            server_mechanisms: vec![],
+            tried: vec![],
+            current: None,
+            incoming: String::new(),
+            state: State::Idle,
+            account: None,
+        }
+    }
+
+    /// Start authentication with the mechanisms of the `sasl` capability
+    /// value, e.g. `sasl=PLAIN,EXTERNAL`. An empty list tries all mechanisms.
+    pub fn start(&mut self, server_mechanisms: &[AttrValue]) -> Result {
+        self.server_mechanisms = server_mechanisms.to_vec();
+        self.tried.clear();
+        self.account = None;
+        self.next_mechanism()
+    }
+
+    /// Authentication finished, either successful or not. `CAP END` can be
+    /// sent after this.
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self.state,
+            State::Succeeded | State::Failed | State::Aborted
+        )
+    }
+
+    /// Name of the mechanism in use
+    pub fn mechanism(&self) -> Option<&'static str> {
+        self.current.as_ref().map(|mechanism| mechanism.name())
+    }
+
+    /// Abort the current exchange, e.g. after `handle` returned an error
+    pub fn abort(&mut self) -> Message {
+        self.state = State::Aborted;
+        self.current = None;
+        self.incoming.clear();
+        Self::authenticate("*")
+    }
+
+    pub fn handle(&mut self, message: &Message) -> Result {
+        let MessageType::Generic(msg) = &message.msg_type else {
+            return Ok(vec![]);
+        };
+
+        match &msg.command {
+            Command::Cmd(_) => match TypedMessage::try_from(msg) {
+                Ok(TypedMessage::Authenticate { data }) => self.challenge(&data),
+                _ => Ok(vec![]),
+            },
+            Command::Digit3(code) => self.numeric(*code, msg),
+        }
+    }
+
+    fn numeric(&mut self, code: u32, msg: &GenericMessage) -> Result {
+        match code {
+            numeric::RPL_LOGGEDIN => self.account = msg.parameters.get(2).cloned(),
+            numeric::RPL_LOGGEDOUT => self.account = None,
+            numeric::RPL_SASLSUCCESS | numeric::ERR_SASLALREADY => self.finish(State::Succeeded),
+            numeric::ERR_NICKLOCKED | numeric::ERR_SASLTOOLONG => self.finish(State::Failed),
+            numeric::ERR_SASLABORTED => self.finish(State::Aborted),
+            // mechanism failed, fall back to the next one
+            numeric::ERR_SASLFAIL if self.state == State::Authenticating => {
+                self.current = None;
+                self.incoming.clear();
+                return match self.next_mechanism() {
+                    Err(Error::NoMechanism) => Ok(vec![]),
+                    result => result,
+                };
+            }
+            numeric::ERR_SASLFAIL => self.finish(State::Failed),
+            numeric::RPL_SASLMECHS => {
+                if let Some(mechanisms) = msg.parameters.get(1) {
+                    self.server_mechanisms = mechanisms
+                        .split(',')
+                        .filter(|m| !m.is_empty())
+                        .map(|m| m.to_owned().into())
+                        .collect();
+                }
+            }
+            _ => (),
+        }
+        Ok(vec![])
+    }
+
+    fn finish(&mut self, state: State) {
+        self.state = state;
+        self.current = None;
+        self.incoming.clear();
+    }
+
+    /// Select the next mechanism which has not been tried yet
+    fn next_mechanism(&mut self) -> Result {
+        let next = self.mechanisms.iter().find(|mechanism| {
+            !self.tried.contains(&mechanism.name())
+                && (self.server_mechanisms.is_empty()
+                    || self
+                        .server_mechanisms
+                        .iter()
+                        .any(|m| m.eq_ignore_ascii_case(mechanism.name())))
+        });
+
+        match next {
+            Some(mechanism) => {
+                self.tried.push(mechanism.name());
+                self.current = Some(mechanism.clone());
+                self.state = State::Authenticating;
+                Ok(vec![Self::authenticate(mechanism.name())])
+            }
+            None => {
+                self.finish(State::Failed);
+                Err(Error::NoMechanism)
+            }
+        }
+    }
+
+    /// Collect chunks of a challenge and respond once it is complete
+    fn challenge(&mut self, data: &str) -> Result {
+        let Some(mechanism) = self.current.as_mut() else {
+            return Err(Error::NotAuthenticating);
+        };
+
+        if data != "+" {
+            self.incoming.push_str(data);
+            if data.len() == MAX_CHUNK_LENGTH {
+                return Ok(vec![]);
+            }
+        }
+
+        let payload = std::mem::take(&mut self.incoming);
+        let challenge = STANDARD
+            .decode(&payload)
+            .map_err(|_| Error::InvalidBase64(payload))?;
+
+        let response = mechanism.step(&challenge)?;
+        Ok(Self::response(&response))
+    }
+
+    /// Encode response, split into chunks of 400 bytes
+    fn response(response: &[u8]) -> Messages {
+        let encoded = STANDARD.encode(response);
+        let mut messages = encoded
+            .as_bytes()
+            .chunks(MAX_CHUNK_LENGTH)
+            // base64 is always ASCII
+            .map(|chunk| Self::authenticate(std::str::from_utf8(chunk).unwrap_or_default()))
+            .collect::<Vec<_>>();
+
+        // empty responses and responses which end on a chunk boundary are
+        // terminated by `+`
+        if encoded.len().is_multiple_of(MAX_CHUNK_LENGTH) {
+            messages.push(Self::authenticate("+"));
+        }
+        messages
+    }
+
+    fn authenticate(data: &str) -> Message {
+        Message::typed(TypedMessage::Authenticate {
+            data: data.to_owned().into(),
+        })
+        .build()
+    }
+}
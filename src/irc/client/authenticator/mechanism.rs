@@ -0,0 +1,358 @@
+use std::fmt::Debug;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use super::Error;
+
+#[cfg(test)]
+mod test;
+
+/// SASL mechanism, which produces the client responses for the challenges
+/// sent by the server. Challenges and responses are already base64 decoded.
+pub trait Mechanism: Debug {
+    /// Mechanism name as used in `AUTHENTICATE <name>`, e.g. `PLAIN`
+    fn name(&self) -> &'static str;
+
+    /// Respond to a challenge of the server. The first challenge is always
+    /// empty, so client-first mechanisms can send their initial response.
+    fn step(&mut self, challenge: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Clone into a new box, so authenticators can be cloned
+    fn boxed_clone(&self) -> Box<dyn Mechanism>;
+
+    /// Configuration of the mechanism, without the state of an exchange
+    fn credentials(&self) -> Credentials;
+}
+
+/// What a mechanism authenticates with, which makes mechanisms equal
+#[derive(Clone, PartialEq)]
+pub enum Credentials {
+    Plain {
+        authzid: String,
+        authcid: String,
+        password: String,
+    },
+    External {
+        authzid: String,
+    },
+    ScramSha256 {
+        username: String,
+        password: String,
+    },
+    /// Any other mechanism, e.g. its name and settings
+    Other(String),
+}
+
+impl Clone for Box<dyn Mechanism> {
+    fn clone(&self) -> Self {
+        self.boxed_clone()
+    }
+}
+
+impl PartialEq for Box<dyn Mechanism> {
+    fn eq(&self, other: &Self) -> bool {
+        self.credentials() == other.credentials()
+    }
+}
+
+/// `PLAIN` mechanism, following RFC 4616
+#[derive(Clone, PartialEq)]
+pub struct Plain {
+    authzid: String,
+    authcid: String,
+    password: String,
+}
+
+impl Plain {
+    pub fn new(account: &str, password: &str) -> Self {
+        Plain {
+            authzid: String::new(),
+            authcid: account.to_owned(),
+            password: password.to_owned(),
+        }
+    }
+}
+
+impl Debug for Plain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Plain")
+            .field("authcid", &self.authcid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Mechanism for Plain {
+    fn name(&self) -> &'static str {
+        "PLAIN"
+    }
+
+    fn step(&mut self, _challenge: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(format!("{}\0{}\0{}", self.authzid, self.authcid, self.password).into_bytes())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Mechanism> {
+        Box::new(self.clone())
+    }
+
+    fn credentials(&self) -> Credentials {
+        Credentials::Plain {
+            authzid: self.authzid.clone(),
+            authcid: self.authcid.clone(),
+            password: self.password.clone(),
+        }
+    }
+}
+
+/// `EXTERNAL` mechanism, following RFC 4422. Authentication happens outside
+/// of SASL, e.g. with a TLS client certificate.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct External {
+    authzid: String,
+}
+
+impl External {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request authorization as a specific account
+    pub fn authzid(authzid: &str) -> Self {
+        External {
+            authzid: authzid.to_owned(),
+        }
+    }
+}
+
+impl Mechanism for External {
+    fn name(&self) -> &'static str {
+        "EXTERNAL"
+    }
+
+    fn step(&mut self, _challenge: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(self.authzid.clone().into_bytes())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Mechanism> {
+        Box::new(self.clone())
+    }
+
+    fn credentials(&self) -> Credentials {
+        Credentials::External {
+            authzid: self.authzid.clone(),
+        }
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Clone, PartialEq)]
+enum ScramState {
+    Initial,
+    /// Client first message has been sent
+    ClientFirst {
+        nonce: String,
+        client_first_bare: String,
+    },
+    /// Client final message has been sent, server signature is expected
+    ClientFinal {
+        server_signature: Vec<u8>,
+    },
+    Done,
+}
+
+/// `SCRAM-SHA-256` mechanism, following RFC 5802 and RFC 7677. Channel binding
+/// is not supported.
+#[derive(Clone, PartialEq)]
+pub struct ScramSha256 {
+    username: String,
+    password: String,
+    /// Fixed client nonce, otherwise each exchange generates a new one
+    nonce: Option<String>,
+    state: ScramState,
+}
+
+impl ScramSha256 {
+    pub fn new(username: &str, password: &str) -> Self {
+        ScramSha256 {
+            username: username.to_owned(),
+            password: password.to_owned(),
+            nonce: None,
+            state: ScramState::Initial,
+        }
+    }
+
+    /// Create mechanism with a fixed client nonce, e.g. for test vectors
+    pub fn with_nonce(username: &str, password: &str, nonce: &str) -> Self {
+        ScramSha256 {
+            nonce: Some(nonce.to_owned()),
+            ..Self::new(username, password)
+        }
+    }
+
+    /// Client nonce of a new exchange
+    fn nonce(&self) -> Result<String, Error> {
+        if let Some(nonce) = &self.nonce {
+            return Ok(nonce.clone());
+        }
+        let mut nonce = [0u8; 24];
+        getrandom::getrandom(&mut nonce).map_err(|err| Error::Random(err.to_string()))?;
+        Ok(STANDARD.encode(nonce))
+    }
+
+    /// `saslname` escaping of the user name
+    fn saslname(username: &str) -> String {
+        username.replace('=', "=3D").replace(',', "=2C")
+    }
+
+    fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+        // HMAC accepts keys of any length
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// `Hi()` function of RFC 5802, which is PBKDF2 with a single block
+    fn hi(password: &[u8], salt: &[u8], iterations: u32) -> Vec<u8> {
+        let mut u = Self::hmac(password, &[salt, &1u32.to_be_bytes()].concat());
+        let mut result = u.clone();
+        for _ in 1..iterations {
+            u = Self::hmac(password, &u);
+            result.iter_mut().zip(&u).for_each(|(r, u)| *r ^= u);
+        }
+        result
+    }
+
+    /// Parse attributes like `r=...,s=...,i=...`
+    fn attribute(message: &str, name: char) -> Option<&str> {
+        message.split(',').find_map(|attr| {
+            let (key, value) = attr.split_once('=')?;
+            (key.len() == 1 && key.starts_with(name)).then_some(value)
+        })
+    }
+
+    fn client_first(&mut self) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce()?;
+        let client_first_bare = format!("n={},r={nonce}", Self::saslname(&self.username));
+        let response = format!("n,,{client_first_bare}");
+        self.state = ScramState::ClientFirst {
+            nonce,
+            client_first_bare,
+        };
+        Ok(response.into_bytes())
+    }
+
+    fn client_final(
+        &mut self,
+        client_nonce: &str,
+        client_first_bare: &str,
+        server_first: &str,
+    ) -> Result<Vec<u8>, Error> {
+        let invalid = || Error::InvalidChallenge(server_first.to_owned());
+
+        let nonce = Self::attribute(server_first, 'r').ok_or_else(invalid)?;
+        let salt = Self::attribute(server_first, 's').ok_or_else(invalid)?;
+        let iterations = Self::attribute(server_first, 'i').ok_or_else(invalid)?;
+
+        // server nonce has to extend our own nonce
+        if !nonce.starts_with(client_nonce) || nonce.len() == client_nonce.len() {
+            return Err(invalid());
+        }
+        let salt = STANDARD.decode(salt).map_err(|_| invalid())?;
+        let iterations: u32 = iterations.parse().map_err(|_| invalid())?;
+        if iterations == 0 {
+            return Err(invalid());
+        }
+
+        let salted_password = Self::hi(self.password.as_bytes(), &salt, iterations);
+        let client_key = Self::hmac(&salted_password, b"Client Key");
+        let stored_key = Sha256::digest(&client_key);
+        let server_key = Self::hmac(&salted_password, b"Server Key");
+
+        // "biws" is the base64 encoded GS2 header "n,,"
+        let client_final_without_proof = format!("c=biws,r={nonce}");
+        let auth_message =
+            format!("{client_first_bare},{server_first},{client_final_without_proof}");
+
+        let client_signature = Self::hmac(&stored_key, auth_message.as_bytes());
+        let client_proof = client_key
+            .iter()
+            .zip(&client_signature)
+            .map(|(key, signature)| key ^ signature)
+            .collect::<Vec<u8>>();
+
+        self.state = ScramState::ClientFinal {
+            server_signature: Self::hmac(&server_key, auth_message.as_bytes()),
+        };
+
+        Ok(format!(
+            "{client_final_without_proof},p={}",
+            STANDARD.encode(client_proof)
+        )
+        .into_bytes())
+    }
+
+    fn verify_server(
+        &mut self,
+        server_signature: &[u8],
+        server_final: &str,
+    ) -> Result<Vec<u8>, Error> {
+        if let Some(err) = Self::attribute(server_final, 'e') {
+            return Err(Error::ServerError(err.to_owned()));
+        }
+
+        let verifier = Self::attribute(server_final, 'v')
+            .and_then(|v| STANDARD.decode(v).ok())
+            .ok_or_else(|| Error::InvalidChallenge(server_final.to_owned()))?;
+
+        if verifier != server_signature {
+            return Err(Error::ServerSignatureMismatch);
+        }
+
+        self.state = ScramState::Done;
+        Ok(vec![])
+    }
+}
+
+impl Debug for ScramSha256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScramSha256")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Mechanism for ScramSha256 {
+    fn name(&self) -> &'static str {
+        "SCRAM-SHA-256"
+    }
+
+    fn step(&mut self, challenge: &[u8]) -> Result<Vec<u8>, Error> {
+        let challenge = std::str::from_utf8(challenge)
+            .map_err(|_| Error::InvalidChallenge(String::from_utf8_lossy(challenge).into()))?;
+
+        match self.state.clone() {
+            ScramState::Initial => self.client_first(),
+            ScramState::ClientFirst {
+                nonce,
+                client_first_bare,
+            } => self.client_final(&nonce, &client_first_bare, challenge),
+            ScramState::ClientFinal { server_signature } => {
+                self.verify_server(&server_signature, challenge)
+            }
+            ScramState::Done => Err(Error::InvalidChallenge(challenge.to_owned())),
+        }
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Mechanism> {
+        Box::new(self.clone())
+    }
+
+    fn credentials(&self) -> Credentials {
+        Credentials::ScramSha256 {
+            username: self.username.clone(),
+            password: self.password.clone(),
+        }
+    }
+}
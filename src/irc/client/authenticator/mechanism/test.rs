@@ -0,0 +1,120 @@
+use crate::irc::client::authenticator::Error;
+
+use super::{External, Mechanism, Plain, ScramSha256};
+
+#[test]
+fn test_plain() {
+    let mut plain = Plain::new("jilles", "sesame");
+    assert_eq!(plain.name(), "PLAIN");
+    assert_eq!(plain.step(b"").unwrap(), b"\0jilles\0sesame");
+}
+
+#[test]
+fn test_plain_debug_hides_password() {
+    let plain = Plain::new("jilles", "sesame");
+    assert!(!format!("{plain:?}").contains("sesame"));
+
+    let scram = ScramSha256::with_nonce("user", "pencil", "nonce");
+    assert!(!format!("{scram:?}").contains("pencil"));
+}
+
+#[test]
+fn test_eq_compares_credentials() {
+    let plain = |password| Box::new(Plain::new("jilles", password)) as Box<dyn Mechanism>;
+    assert!(plain("sesame").eq(&plain("sesame")));
+    assert!(plain("sesame").ne(&plain("open")));
+
+    // the nonce and state of an exchange do not matter
+    let scram =
+        |nonce| Box::new(ScramSha256::with_nonce("user", "pencil", nonce)) as Box<dyn Mechanism>;
+    let mut started = scram("a");
+    started.step(b"").unwrap();
+    assert!(started.eq(&scram("b")));
+    assert!(scram("a").ne(&plain("pencil")));
+}
+
+#[test]
+fn test_external() {
+    let mut external = External::new();
+    assert_eq!(external.name(), "EXTERNAL");
+    assert_eq!(external.step(b"").unwrap(), b"");
+
+    let mut external = External::authzid("admin");
+    assert_eq!(external.step(b"").unwrap(), b"admin");
+}
+
+/// Test vector of RFC 7677
+const CLIENT_NONCE: &str = "rOprNGfwEbeRWgbNEkqO";
+const SERVER_FIRST: &str =
+    "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096";
+const CLIENT_FINAL: &str = "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=";
+const SERVER_FINAL: &str = "v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=";
+
+#[test]
+fn test_scram_sha_256() {
+    let mut scram = ScramSha256::with_nonce("user", "pencil", CLIENT_NONCE);
+    assert_eq!(scram.name(), "SCRAM-SHA-256");
+
+    let client_first = scram.step(b"").unwrap();
+    assert_eq!(client_first, b"n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+
+    let client_final = scram.step(SERVER_FIRST.as_bytes()).unwrap();
+    assert_eq!(String::from_utf8(client_final).unwrap(), CLIENT_FINAL);
+
+    assert_eq!(scram.step(SERVER_FINAL.as_bytes()).unwrap(), b"");
+}
+
+#[test]
+fn test_scram_sha_256_random_nonce() {
+    let mut first = ScramSha256::new("user", "pencil");
+    let mut second = ScramSha256::new("user", "pencil");
+    assert_ne!(first.step(b"").unwrap(), second.step(b"").unwrap());
+
+    // every exchange of the same mechanism, e.g. after reconnecting
+    let scram = Box::new(ScramSha256::new("user", "pencil")) as Box<dyn Mechanism>;
+    let mut first = scram.clone();
+    let mut second = scram.clone();
+    assert_ne!(first.step(b"").unwrap(), second.step(b"").unwrap());
+}
+
+#[test]
+fn test_scram_sha_256_escape_username() {
+    let mut scram = ScramSha256::with_nonce("a=b,c", "pencil", "nonce");
+    assert_eq!(scram.step(b"").unwrap(), b"n,,n=a=3Db=2Cc,r=nonce");
+}
+
+#[test]
+fn test_scram_sha_256_server_signature_mismatch() {
+    let mut scram = ScramSha256::with_nonce("user", "wrong", CLIENT_NONCE);
+    scram.step(b"").unwrap();
+    scram.step(SERVER_FIRST.as_bytes()).unwrap();
+    assert_eq!(
+        scram.step(SERVER_FINAL.as_bytes()),
+        Err(Error::ServerSignatureMismatch)
+    );
+}
+
+#[test]
+fn test_scram_sha_256_server_error() {
+    let mut scram = ScramSha256::with_nonce("user", "pencil", CLIENT_NONCE);
+    scram.step(b"").unwrap();
+    scram.step(SERVER_FIRST.as_bytes()).unwrap();
+    assert_eq!(
+        scram.step(b"e=invalid-proof"),
+        Err(Error::ServerError("invalid-proof".into()))
+    );
+}
+
+#[test]
+fn test_scram_sha_256_invalid_server_nonce() {
+    let mut scram = ScramSha256::with_nonce("user", "pencil", "other-nonce");
+    scram.step(b"").unwrap();
+    assert_eq!(
+        scram.step(SERVER_FIRST.as_bytes()),
+        Err(Error::InvalidChallenge(SERVER_FIRST.into()))
+    );
+
+    let mut scram = ScramSha256::with_nonce("user", "pencil", CLIENT_NONCE);
+    scram.step(b"").unwrap();
+    assert!(scram.step(b"r=rOprNGfwEbeRWgbNEkqO,s=W22Z,i=1").is_err());
+}
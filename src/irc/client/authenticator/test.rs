@@ -0,0 +1,296 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use yew::AttrValue;
+
+use crate::irc::parser::message::Message;
+
+use super::{
+    mechanism::{Credentials, External, Mechanism, Plain, ScramSha256},
+    Authenticator, Error, State,
+};
+
+/// Feed a scripted server line to the authenticator, returns the responses
+fn handle(authenticator: &mut Authenticator, line: &str) -> Vec<String> {
+    let message = Message::parse(line).unwrap();
+    authenticator
+        .handle(&message)
+        .unwrap()
+        .iter()
+        .map(|msg| msg.to_string())
+        .collect()
+}
+
+fn plain() -> Authenticator {
+    Authenticator::new(vec![Box::new(Plain::new("jilles", "sesame"))])
+}
+
+fn mechs(mechanisms: &[&str]) -> Vec<AttrValue> {
+    mechanisms
+        .iter()
+        .map(|m| AttrValue::from(m.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_plain_success() {
+    let mut auth = plain();
+    assert_eq!(auth.state, State::Idle);
+
+    let msgs = auth.start(&mechs(&["PLAIN", "EXTERNAL"])).unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].to_string(), "AUTHENTICATE PLAIN");
+    assert_eq!(auth.state, State::Authenticating);
+    assert_eq!(auth.mechanism(), Some("PLAIN"));
+
+    assert_eq!(
+        handle(&mut auth, "AUTHENTICATE +"),
+        vec!["AUTHENTICATE AGppbGxlcwBzZXNhbWU="]
+    );
+
+    let none = Vec::<String>::new();
+    assert_eq!(
+        handle(
+            &mut auth,
+            ":server 900 jilles jilles!jilles@localhost jilles :You are now logged in as jilles"
+        ),
+        none
+    );
+    assert_eq!(auth.account, Some("jilles".into()));
+    assert!(!auth.is_done());
+
+    assert_eq!(
+        handle(
+            &mut auth,
+            ":server 903 jilles :SASL authentication successful"
+        ),
+        none
+    );
+    assert_eq!(auth.state, State::Succeeded);
+    assert!(auth.is_done());
+}
+
+#[test]
+fn test_mechanism_preference() {
+    let mut auth = Authenticator::new(vec![
+        Box::new(External::new()),
+        Box::new(Plain::new("jilles", "sesame")),
+    ]);
+
+    // server does not support EXTERNAL
+    let msgs = auth.start(&mechs(&["PLAIN"])).unwrap();
+    assert_eq!(msgs[0].to_string(), "AUTHENTICATE PLAIN");
+
+    // unknown server mechanisms, the first one is tried
+    let msgs = auth.start(&[]).unwrap();
+    assert_eq!(msgs[0].to_string(), "AUTHENTICATE EXTERNAL");
+}
+
+#[test]
+fn test_no_mechanism() {
+    let mut auth = plain();
+    assert_eq!(auth.start(&mechs(&["EXTERNAL"])), Err(Error::NoMechanism));
+    assert_eq!(auth.state, State::Failed);
+    assert!(auth.is_done());
+}
+
+#[test]
+fn test_fallback_after_failure() {
+    let mut auth = Authenticator::new(vec![
+        Box::new(External::new()),
+        Box::new(Plain::new("jilles", "sesame")),
+    ]);
+    auth.start(&[]).unwrap();
+
+    assert_eq!(handle(&mut auth, "AUTHENTICATE +"), vec!["AUTHENTICATE +"]);
+    assert_eq!(
+        handle(&mut auth, ":server 904 * :SASL authentication failed"),
+        vec!["AUTHENTICATE PLAIN"]
+    );
+    assert_eq!(auth.mechanism(), Some("PLAIN"));
+
+    // no more mechanisms left
+    let none = Vec::<String>::new();
+    assert_eq!(
+        handle(&mut auth, ":server 904 * :SASL authentication failed"),
+        none
+    );
+    assert_eq!(auth.state, State::Failed);
+}
+
+#[test]
+fn test_mechanism_list_from_server() {
+    let mut auth = Authenticator::new(vec![
+        Box::new(ScramSha256::with_nonce("jilles", "sesame", "nonce")),
+        Box::new(External::new()),
+        Box::new(Plain::new("jilles", "sesame")),
+    ]);
+    auth.start(&[]).unwrap();
+    assert_eq!(auth.mechanism(), Some("SCRAM-SHA-256"));
+
+    handle(
+        &mut auth,
+        ":server 908 * PLAIN,SCRAM-SHA-1 :are available SASL mechanisms",
+    );
+    // EXTERNAL is skipped, since the server does not support it
+    assert_eq!(
+        handle(&mut auth, ":server 904 * :SASL authentication failed"),
+        vec!["AUTHENTICATE PLAIN"]
+    );
+}
+
+#[test]
+fn test_failure_numerics() {
+    for (line, state) in [
+        (
+            ":server 902 * :You must use a nick assigned to you",
+            State::Failed,
+        ),
+        (":server 905 * :SASL message too long", State::Failed),
+        (":server 906 * :SASL authentication aborted", State::Aborted),
+        (
+            ":server 907 * :You have already authenticated using SASL",
+            State::Succeeded,
+        ),
+    ] {
+        let mut auth = plain();
+        auth.start(&[]).unwrap();
+        handle(&mut auth, line);
+        assert_eq!(auth.state, state, "{line}");
+    }
+}
+
+#[test]
+fn test_logged_out() {
+    let mut auth = plain();
+    auth.account = Some("jilles".into());
+    handle(
+        &mut auth,
+        ":server 901 jilles jilles!jilles@localhost :You are now logged out",
+    );
+    assert_eq!(auth.account, None);
+}
+
+#[test]
+fn test_abort() {
+    let mut auth = plain();
+    auth.start(&[]).unwrap();
+    assert_eq!(auth.abort().to_string(), "AUTHENTICATE *");
+    assert_eq!(auth.state, State::Aborted);
+}
+
+#[test]
+fn test_unexpected_authenticate() {
+    let mut auth = plain();
+    let msg = Message::parse("AUTHENTICATE +").unwrap();
+    assert_eq!(auth.handle(&msg), Err(Error::NotAuthenticating));
+}
+
+#[test]
+fn test_invalid_base64() {
+    let mut auth = plain();
+    auth.start(&[]).unwrap();
+    let msg = Message::parse("AUTHENTICATE !!!").unwrap();
+    assert_eq!(auth.handle(&msg), Err(Error::InvalidBase64("!!!".into())));
+}
+
+#[test]
+fn test_response_chunks() {
+    // 300 byte response are 400 bytes of base64, so a final `+` is required
+    let password = "x".repeat(300 - "\0jilles\0".len());
+    let mut auth = Authenticator::new(vec![Box::new(Plain::new("jilles", &password))]);
+    auth.start(&[]).unwrap();
+
+    let msgs = handle(&mut auth, "AUTHENTICATE +");
+    assert_eq!(msgs.len(), 2);
+    assert_eq!(msgs[0].len(), "AUTHENTICATE ".len() + 400);
+    assert_eq!(msgs[1], "AUTHENTICATE +");
+
+    // 301 bytes are split into 400 and 4 bytes
+    let password = "x".repeat(301 - "\0jilles\0".len());
+    let mut auth = Authenticator::new(vec![Box::new(Plain::new("jilles", &password))]);
+    auth.start(&[]).unwrap();
+
+    let msgs = handle(&mut auth, "AUTHENTICATE +");
+    assert_eq!(msgs.len(), 2);
+    assert_eq!(msgs[0].len(), "AUTHENTICATE ".len() + 400);
+    assert_eq!(msgs[1].len(), "AUTHENTICATE ".len() + 4);
+}
+
+/// Test mechanism which responds with the length of the challenge
+#[derive(Debug, Clone)]
+struct ChallengeLength;
+
+impl Mechanism for ChallengeLength {
+    fn name(&self) -> &'static str {
+        "LENGTH"
+    }
+
+    fn step(&mut self, challenge: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(challenge.len().to_string().into_bytes())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Mechanism> {
+        Box::new(self.clone())
+    }
+
+    fn credentials(&self) -> Credentials {
+        Credentials::Other("LENGTH".to_owned())
+    }
+}
+
+#[test]
+fn test_challenge_chunks() {
+    let mut auth = Authenticator::new(vec![Box::new(ChallengeLength)]);
+    auth.start(&[]).unwrap();
+
+    let encoded = STANDARD.encode([0u8; 600]);
+    assert_eq!(encoded.len(), 800);
+
+    let none = Vec::<String>::new();
+    let (first, second) = encoded.split_at(400);
+    assert_eq!(handle(&mut auth, &format!("AUTHENTICATE {first}")), none);
+    assert_eq!(handle(&mut auth, &format!("AUTHENTICATE {second}")), none);
+
+    let response = STANDARD.encode("600");
+    assert_eq!(
+        handle(&mut auth, "AUTHENTICATE +"),
+        vec![format!("AUTHENTICATE {response}")]
+    );
+}
+
+#[test]
+fn test_scram_sha_256_transcript() {
+    let mut auth = Authenticator::new(vec![Box::new(ScramSha256::with_nonce(
+        "user",
+        "pencil",
+        "rOprNGfwEbeRWgbNEkqO",
+    ))]);
+    let msgs = auth.start(&mechs(&["SCRAM-SHA-256"])).unwrap();
+    assert_eq!(msgs[0].to_string(), "AUTHENTICATE SCRAM-SHA-256");
+
+    let client_first = STANDARD.encode("n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+    assert_eq!(
+        handle(&mut auth, "AUTHENTICATE +"),
+        vec![format!("AUTHENTICATE {client_first}")]
+    );
+
+    let server_first = STANDARD.encode(
+        "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+    );
+    let client_final = STANDARD.encode("c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=");
+    assert_eq!(
+        handle(&mut auth, &format!("AUTHENTICATE {server_first}")),
+        vec![format!("AUTHENTICATE {client_final}")]
+    );
+
+    let server_final = STANDARD.encode("v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=");
+    assert_eq!(
+        handle(&mut auth, &format!("AUTHENTICATE {server_final}")),
+        vec!["AUTHENTICATE +"]
+    );
+
+    handle(
+        &mut auth,
+        ":server 903 user :SASL authentication successful",
+    );
+    assert_eq!(auth.state, State::Succeeded);
+}
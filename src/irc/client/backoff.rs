@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+#[cfg(test)]
+mod test;
+
+/// Exponential backoff for reconnect attempts. Delays double with every
+/// attempt, up to `max`, and are randomized to avoid reconnect storms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Number of attempts since the last `reset`
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Connection succeeded, start over with the initial delay
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Delay before the next attempt, with random jitter
+    pub fn next_delay(&mut self) -> Duration {
+        let mut random = [0u8; 4];
+        let jitter = match getrandom::getrandom(&mut random) {
+            Ok(()) => f64::from(u32::from_le_bytes(random)) / f64::from(u32::MAX),
+            Err(_) => 1.0,
+        };
+        self.next_delay_with(jitter)
+    }
+
+    /// Delay before the next attempt. `jitter` between 0 and 1 picks a delay
+    /// between half and the full exponential delay.
+    pub fn next_delay_with(&mut self, jitter: f64) -> Duration {
+        let delay = 2u32
+            .checked_pow(self.attempt)
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max));
+        self.attempt = self.attempt.saturating_add(1);
+
+        delay / 2 + (delay / 2).mul_f64(jitter.clamp(0.0, 1.0))
+    }
+}
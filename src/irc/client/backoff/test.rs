@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use super::Backoff;
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn test_exponential() {
+    let mut backoff = Backoff::new(secs(2), secs(60));
+    let delays = (0..7)
+        .map(|_| backoff.next_delay_with(1.0))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        delays,
+        vec![
+            secs(2),
+            secs(4),
+            secs(8),
+            secs(16),
+            secs(32),
+            secs(60),
+            secs(60)
+        ]
+    );
+    assert_eq!(backoff.attempt(), 7);
+}
+
+#[test]
+fn test_jitter() {
+    let mut backoff = Backoff::new(secs(10), secs(60));
+    assert_eq!(backoff.next_delay_with(0.0), secs(5));
+    assert_eq!(backoff.next_delay_with(0.5), secs(15));
+
+    // random delays stay within half and full delay
+    backoff.reset();
+    let delay = backoff.next_delay();
+    assert!(secs(5) <= delay && delay <= secs(10), "{delay:?}");
+}
+
+#[test]
+fn test_reset() {
+    let mut backoff = Backoff::new(secs(1), secs(60));
+    backoff.next_delay_with(1.0);
+    backoff.next_delay_with(1.0);
+    backoff.reset();
+    assert_eq!(backoff.attempt(), 0);
+    assert_eq!(backoff.next_delay_with(1.0), secs(1));
+}
+
+#[test]
+fn test_many_attempts() {
+    let mut backoff = Backoff::new(secs(1), secs(300));
+    for _ in 0..100 {
+        backoff.next_delay_with(1.0);
+    }
+    assert_eq!(backoff.next_delay_with(1.0), secs(300));
+}
@@ -0,0 +1,272 @@
+use std::{cmp::Reverse, collections::BTreeMap, time::Duration};
+
+use yew::AttrValue;
+
+use crate::irc::parser::{
+    message::{Message, MessageType},
+    Command,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Type of a batch with its parameters
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// Users quit because the servers lost their connection
+    Netsplit {
+        server1: AttrValue,
+        server2: AttrValue,
+    },
+    /// Users of a netsplit are back
+    Netjoin {
+        server1: AttrValue,
+        server2: AttrValue,
+    },
+    /// Reply to a `CHATHISTORY` request
+    Chathistory { target: AttrValue },
+    /// Reply to `CHATHISTORY TARGETS`
+    ChathistoryTargets,
+    /// Lines of a single message, see `Splitter`
+    Multiline { target: AttrValue },
+    /// Reply to a command sent with a `label` tag
+    LabeledResponse,
+    Other {
+        kind: AttrValue,
+        params: Vec<AttrValue>,
+    },
+}
+
+impl Kind {
+    pub fn parse(kind: &str, params: &[AttrValue]) -> Self {
+        match (kind, params) {
+            ("netsplit", [server1, server2, ..]) => Kind::Netsplit {
+                server1: server1.clone(),
+                server2: server2.clone(),
+            },
+            ("netjoin", [server1, server2, ..]) => Kind::Netjoin {
+                server1: server1.clone(),
+                server2: server2.clone(),
+            },
+            ("chathistory", [target, ..]) => Kind::Chathistory {
+                target: target.clone(),
+            },
+            ("draft/chathistory-targets", _) => Kind::ChathistoryTargets,
+            ("draft/multiline", [target, ..]) => Kind::Multiline {
+                target: target.clone(),
+            },
+            ("labeled-response", _) => Kind::LabeledResponse,
+            _ => Kind::Other {
+                kind: kind.to_owned().into(),
+                params: params.to_vec(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    Message(Message),
+    Batch(Batch),
+}
+
+/// Messages grouped by the server with `BATCH`, e.g. the quits of a netsplit
+/// between `BATCH +ref netsplit irc.a irc.b` and `BATCH -ref`, following the
+/// spec:
+///
+/// https://ircv3.net/specs/extensions/batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    pub reference: AttrValue,
+    pub kind: Kind,
+    /// Tags of the `BATCH` which started the batch, e.g. the `msgid` of a
+    /// multiline message
+    pub tags: BTreeMap<String, Option<String>>,
+    /// Messages and nested batches in the order they were received
+    pub entries: Vec<Entry>,
+    /// Time the batch was started
+    pub started: Duration,
+    /// The server ended the batch. Incomplete batches are delivered after a
+    /// timeout or once the connection was lost.
+    pub complete: bool,
+}
+
+impl Batch {
+    /// Messages of the batch, without those of nested batches
+    pub fn messages(&self) -> impl Iterator<Item = &Message> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Message(message) => Some(message),
+            Entry::Batch(_) => None,
+        })
+    }
+
+    /// Lines of a `draft/multiline` batch joined to a single message. Lines
+    /// tagged with `draft/multiline-concat` continue the previous line.
+    pub fn multiline_message(&self) -> Option<Message> {
+        let mut messages = self.messages();
+        let mut combined = messages.next()?.clone();
+        let MessageType::Generic(msg) = &mut combined.msg_type else {
+            return None;
+        };
+        let mut text = msg.parameters.last()?.to_string();
+
+        for message in messages {
+            let MessageType::Generic(line) = &message.msg_type else {
+                continue;
+            };
+            if !message.tags.contains_key("draft/multiline-concat") {
+                text.push('\n');
+            }
+            text.push_str(line.parameters.last().map_or("", AttrValue::as_str));
+        }
+        if let Some(last) = msg.parameters.last_mut() {
+            *last = text.into();
+        }
+
+        // tags of the batch apply to the whole message
+        combined.tags.remove("draft/multiline-concat");
+        combined.tags.extend(self.tags.clone());
+        combined.tags.remove("batch");
+        Some(combined)
+    }
+}
+
+/// Result of adding a message to the `Assembler`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assembled {
+    /// The message is not part of a batch
+    Unbatched,
+    /// The message was added to an open batch, or started or ended a nested
+    /// one
+    Collected,
+    Complete(Batch),
+}
+
+/// Collects the messages of batches, until the outermost batch ends
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assembler {
+    /// Time after which incomplete batches are delivered anyway
+    timeout: Duration,
+    /// Open batches by reference, with the reference of their parent
+    open: BTreeMap<String, (Batch, Option<String>)>,
+}
+
+impl Assembler {
+    pub fn new(timeout: Duration) -> Self {
+        Assembler {
+            timeout,
+            open: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, message: &Message, now: Duration) -> Assembled {
+        let parent = message
+            .tags
+            .get("batch")
+            .cloned()
+            .flatten()
+            .filter(|parent| self.open.contains_key(parent));
+
+        if let MessageType::Generic(msg) = &message.msg_type {
+            let reference = msg.parameters.first();
+            if let (Command::Cmd(cmd), Some(reference)) = (&msg.command, reference) {
+                if cmd.eq_ignore_ascii_case("BATCH") {
+                    if let Some(reference) = reference.strip_prefix('+') {
+                        let kind = msg.parameters.get(1).map_or("", AttrValue::as_str);
+                        let params = msg.parameters.get(2..).unwrap_or_default();
+                        let batch = Batch {
+                            reference: reference.to_owned().into(),
+                            kind: Kind::parse(kind, params),
+                            tags: message.tags.clone(),
+                            entries: vec![],
+                            started: now,
+                            complete: false,
+                        };
+                        self.open.insert(reference.to_owned(), (batch, parent));
+                        return Assembled::Collected;
+                    }
+                    if let Some(reference) = reference.strip_prefix('-') {
+                        return match self.close(reference, true) {
+                            Some(batch) => Assembled::Complete(batch),
+                            None => Assembled::Collected,
+                        };
+                    }
+                }
+            }
+        }
+
+        match parent.and_then(|parent| self.open.get_mut(&parent)) {
+            Some((batch, _)) => {
+                batch.entries.push(Entry::Message(message.clone()));
+                Assembled::Collected
+            }
+            None => Assembled::Unbatched,
+        }
+    }
+
+    /// Incomplete batches which were started more than the timeout ago
+    pub fn expire(&mut self, now: Duration) -> Vec<Batch> {
+        let expired = self
+            .open
+            .keys()
+            .filter(|reference| {
+                let (root, _) = &self.open[self.root(reference)];
+                now.saturating_sub(root.started) >= self.timeout
+            })
+            .cloned()
+            .collect();
+        self.close_all(expired)
+    }
+
+    /// All open batches as incomplete, e.g. once the connection was lost
+    pub fn flush(&mut self) -> Vec<Batch> {
+        let open = self.open.keys().cloned().collect();
+        self.close_all(open)
+    }
+
+    /// End a batch. Nested batches are added to their parent, outermost
+    /// batches are returned.
+    fn close(&mut self, reference: &str, complete: bool) -> Option<Batch> {
+        let (mut batch, parent) = self.open.remove(reference)?;
+        batch.complete = complete;
+        match parent.and_then(|parent| self.open.get_mut(&parent)) {
+            Some((parent, _)) => {
+                parent.entries.push(Entry::Batch(batch));
+                None
+            }
+            None => Some(batch),
+        }
+    }
+
+    /// End batches as incomplete, nested ones before their parents
+    fn close_all(&mut self, mut references: Vec<String>) -> Vec<Batch> {
+        references.sort_by_key(|reference| Reverse(self.depth(reference)));
+        references
+            .iter()
+            .filter_map(|reference| self.close(reference, false))
+            .collect()
+    }
+
+    /// Reference of the outermost batch an open batch is nested in
+    fn root<'a>(&'a self, reference: &'a str) -> &'a str {
+        self.ancestors(reference).last().unwrap_or(reference)
+    }
+
+    fn depth(&self, reference: &str) -> usize {
+        self.ancestors(reference).count()
+    }
+
+    /// Open batches an open batch is nested in, innermost first. A parent may
+    /// have ended before its nested batch, and references may be reused, so
+    /// at most all open batches are visited.
+    fn ancestors<'a>(&'a self, reference: &'a str) -> impl Iterator<Item = &'a str> {
+        std::iter::successors(Some(reference), |reference| {
+            self.open
+                .get(*reference)
+                .and_then(|(_, parent)| parent.as_deref())
+                .filter(|parent| self.open.contains_key(*parent))
+        })
+        .skip(1)
+        .take(self.open.len())
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use crate::irc::parser::message::Message;
+
+use super::{Assembled, Assembler, Batch, Entry, Kind};
+
+fn add(assembler: &mut Assembler, line: &str, secs: u64) -> Assembled {
+    let message = Message::parse(line).unwrap();
+    assembler.add(&message, Duration::from_secs(secs))
+}
+
+fn complete(assembled: Assembled) -> Batch {
+    match assembled {
+        Assembled::Complete(batch) => batch,
+        other => panic!("batch is not complete: {other:?}"),
+    }
+}
+
+fn lines(batch: &Batch) -> Vec<String> {
+    batch
+        .messages()
+        .map(|message| message.to_string())
+        .collect()
+}
+
+#[test]
+fn test_kind() {
+    assert_eq!(
+        Kind::parse("netsplit", &["irc.a".into(), "irc.b".into()]),
+        Kind::Netsplit {
+            server1: "irc.a".into(),
+            server2: "irc.b".into()
+        }
+    );
+    assert_eq!(
+        Kind::parse("chathistory", &["#rust".into()]),
+        Kind::Chathistory {
+            target: "#rust".into()
+        }
+    );
+    // parameters are required
+    assert_eq!(
+        Kind::parse("netjoin", &["irc.a".into()]),
+        Kind::Other {
+            kind: "netjoin".into(),
+            params: vec!["irc.a".into()]
+        }
+    );
+}
+
+#[test]
+fn test_unbatched() {
+    let mut assembler = Assembler::new(Duration::from_secs(30));
+    assert_eq!(
+        add(&mut assembler, ":alice!a@host PRIVMSG #rust hi", 0),
+        Assembled::Unbatched
+    );
+    // unknown batches are ignored
+    assert_eq!(
+        add(&mut assembler, "@batch=x :alice!a@host PRIVMSG #rust hi", 0),
+        Assembled::Unbatched
+    );
+    assert_eq!(
+        add(&mut assembler, ":server BATCH -x", 0),
+        Assembled::Collected
+    );
+}
+
+#[test]
+fn test_batch() {
+    let mut assembler = Assembler::new(Duration::from_secs(30));
+    assert_eq!(
+        add(&mut assembler, ":server BATCH +s netsplit irc.a irc.b", 0),
+        Assembled::Collected
+    );
+    assert_eq!(
+        add(
+            &mut assembler,
+            "@batch=s :alice!a@host QUIT :irc.a irc.b",
+            0
+        ),
+        Assembled::Collected
+    );
+    add(&mut assembler, "@batch=s :bob!b@host QUIT :irc.a irc.b", 0);
+
+    let batch = complete(add(&mut assembler, ":server BATCH -s", 1));
+    assert_eq!(batch.reference, "s");
+    assert!(batch.complete);
+    assert_eq!(
+        lines(&batch),
+        vec![
+            "@batch=s :alice!a@host QUIT :irc.a irc.b",
+            "@batch=s :bob!b@host QUIT :irc.a irc.b"
+        ]
+    );
+}
+
+#[test]
+fn test_nested() {
+    let mut assembler = Assembler::new(Duration::from_secs(30));
+    add(&mut assembler, ":server BATCH +h chathistory #rust", 0);
+    add(
+        &mut assembler,
+        "@batch=h :alice!a@host PRIVMSG #rust one",
+        0,
+    );
+    add(
+        &mut assembler,
+        "@batch=h;msgid=m :server BATCH +ml draft/multiline #rust",
+        0,
+    );
+    add(
+        &mut assembler,
+        "@batch=ml :alice!a@host PRIVMSG #rust two",
+        0,
+    );
+    // the nested batch ends, but not the outer one
+    assert_eq!(
+        add(&mut assembler, ":server BATCH -ml", 0),
+        Assembled::Collected
+    );
+
+    let batch = complete(add(&mut assembler, ":server BATCH -h", 0));
+    assert_eq!(batch.entries.len(), 2);
+    let Entry::Batch(nested) = &batch.entries[1] else {
+        panic!("no nested batch");
+    };
+    assert_eq!(
+        nested.kind,
+        Kind::Multiline {
+            target: "#rust".into()
+        }
+    );
+    assert!(nested.complete);
+}
+
+#[test]
+fn test_multiline_message() {
+    let mut assembler = Assembler::new(Duration::from_secs(30));
+    add(
+        &mut assembler,
+        "@msgid=m :alice!a@host BATCH +ml draft/multiline #rust",
+        0,
+    );
+    add(
+        &mut assembler,
+        "@batch=ml :alice!a@host PRIVMSG #rust :one",
+        0,
+    );
+    add(
+        &mut assembler,
+        "@batch=ml :alice!a@host PRIVMSG #rust :two ",
+        0,
+    );
+    add(
+        &mut assembler,
+        "@batch=ml;draft/multiline-concat :alice!a@host PRIVMSG #rust three",
+        0,
+    );
+    let batch = complete(add(&mut assembler, ":alice!a@host BATCH -ml", 0));
+
+    assert_eq!(
+        batch.multiline_message().unwrap().to_string(),
+        "@msgid=m :alice!a@host PRIVMSG #rust :one\ntwo three"
+    );
+}
+
+#[test]
+fn test_expire() {
+    let mut assembler = Assembler::new(Duration::from_secs(30));
+    add(&mut assembler, ":server BATCH +a netsplit irc.a irc.b", 0);
+    add(
+        &mut assembler,
+        "@batch=a :server BATCH +n labeled-response",
+        20,
+    );
+    add(&mut assembler, ":server BATCH +b netjoin irc.a irc.b", 10);
+
+    assert!(assembler.expire(Duration::from_secs(29)).is_empty());
+
+    // nested batches expire with the outermost batch
+    let expired = assembler.expire(Duration::from_secs(30));
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].reference, "a");
+    assert!(!expired[0].complete);
+    assert!(matches!(&expired[0].entries[0], Entry::Batch(nested) if !nested.complete));
+
+    let flushed = assembler.flush();
+    assert_eq!(flushed.len(), 1);
+    assert_eq!(flushed[0].reference, "b");
+    assert!(assembler.flush().is_empty());
+}
@@ -1,21 +1,361 @@
+use std::time::Duration;
+
 use yew::AttrValue;
 
 use crate::irc::parser::Source;
 
+use super::{
+    casemapping::{CaseMapping, ChannelName, Nickname},
+    channel::Channel,
+    history::History,
+};
+
 #[cfg(test)]
 mod test;
 
+/// Lines kept in a buffer, older ones are dropped
+pub const MAX_LINES: usize = 10_000;
+
+/// Most a server time may differ from the time we echoed our own message at
+const ECHO_TOLERANCE: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Buffer {
     pub id: u64,
-    pub name: AttrValue,
+    pub name: BufferName,
     pub motd: Option<AttrValue>,
+    /// Channel key, used to rejoin after reconnecting
+    pub key: Option<AttrValue>,
+    /// Join the channel again after reconnecting, until we leave it
+    pub rejoin: bool,
+    /// State of the channel while we are joined
+    pub channel: Option<Channel>,
     pub lines: Vec<Line>,
+    /// Messages since the buffer was last read
+    pub unread: usize,
+    /// Highlighted lines since the buffer was last read
+    pub highlights: usize,
+    pub activity: Activity,
+    pub history: History,
+    /// Id of the next line pushed
+    next_id: i64,
+    /// Lowest id of all lines, history is inserted below it
+    min_id: i64,
+    /// Lines to keep, history which was loaded adds to `MAX_LINES`
+    max_lines: usize,
+}
+
+impl Buffer {
+    pub fn new(id: u64, name: BufferName) -> Self {
+        Buffer {
+            id,
+            name,
+            motd: None,
+            key: None,
+            rejoin: false,
+            channel: None,
+            lines: vec![],
+            unread: 0,
+            highlights: 0,
+            activity: Activity::None,
+            history: History::default(),
+            next_id: 0,
+            min_id: 0,
+            max_lines: MAX_LINES,
+        }
+    }
+
+    /// Append a line, its id is unique within the buffer
+    pub fn push(
+        &mut self,
+        source: Option<Source>,
+        kind: LineKind,
+        message: AttrValue,
+        time: Duration,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let activity = kind_activity(&kind);
+        self.lines.push(Line {
+            source,
+            id,
+            kind,
+            message,
+            time,
+            highlight: false,
+            msgid: None,
+        });
+
+        if activity == Activity::Messages {
+            self.unread += 1;
+        }
+        self.activity = self.activity.max(activity);
+
+        // trim in steps, so not every line moves all others
+        if self.lines.len() > self.max_lines + MAX_LINES / 10 {
+            self.trim();
+            // loaded history is only kept for a while
+            self.max_lines = self.max_lines.saturating_sub(MAX_LINES / 10).max(MAX_LINES);
+        }
+    }
+
+    /// Drop the oldest lines above `max_lines`
+    fn trim(&mut self) {
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        self.lines.drain(..excess);
+    }
+
+    /// Append a line which mentions us, see `Highlighter`
+    pub fn push_highlight(
+        &mut self,
+        source: Option<Source>,
+        kind: LineKind,
+        message: AttrValue,
+        time: Duration,
+    ) {
+        self.push(source, kind, message, time);
+        if let Some(line) = self.lines.last_mut() {
+            line.highlight = true;
+        }
+        self.highlights += 1;
+        self.activity = Activity::Highlight;
+    }
+
+    /// Reset the counters, once the user has seen the buffer
+    pub fn mark_read(&mut self) {
+        self.unread = 0;
+        self.highlights = 0;
+        self.activity = Activity::None;
+    }
+
+    /// Current state, to find the lines added afterwards
+    pub fn mark(&self) -> Mark {
+        Mark {
+            next_id: self.next_id,
+            unread: self.unread,
+            highlights: self.highlights,
+            activity: self.activity,
+        }
+    }
+
+    /// Lines added since `mark`
+    pub fn lines_since(&mut self, mark: &Mark) -> &mut [Line] {
+        let start = self.start(mark);
+        &mut self.lines[start..]
+    }
+
+    /// Index of the first line added since `mark`. New lines are appended
+    /// with increasing ids, so this holds even if older lines were trimmed.
+    fn start(&self, mark: &Mark) -> usize {
+        self.lines
+            .iter()
+            .rposition(|line| line.id < mark.next_id)
+            .map_or(0, |idx| idx + 1)
+    }
+
+    /// Turn the lines added since `mark` into history: they are sorted in by
+    /// time, lines with a known `msgid` are dropped, and they are not unread.
+    /// Lines we echoed ourselves have no `msgid`, they take the one of the
+    /// same message in the history instead. The history is kept on top of
+    /// `MAX_LINES`, until newer lines push it out.
+    pub fn into_history(&mut self, mark: &Mark) {
+        let start = self.start(mark);
+        let added = self.lines.split_off(start);
+        self.unread = mark.unread;
+        self.highlights = mark.highlights;
+        self.activity = mark.activity;
+        self.trim();
+
+        for mut line in added {
+            let known = line.msgid.as_ref().is_some_and(|msgid| {
+                self.lines
+                    .iter()
+                    .any(|other| other.msgid.as_ref() == Some(msgid))
+            });
+            if known {
+                continue;
+            }
+            if line.msgid.is_some() {
+                if let Some(echo) = self.lines.iter_mut().find(|other| other.echoes(&line)) {
+                    echo.msgid = line.msgid;
+                    continue;
+                }
+            }
+            self.min_id -= 1;
+            line.id = self.min_id;
+            let idx = self.lines.partition_point(|other| other.time <= line.time);
+            self.lines.insert(idx, line);
+            self.max_lines += 1;
+        }
+    }
+}
+
+/// State of a buffer, see `Buffer::mark`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Mark {
+    /// Id of the first line added afterwards
+    next_id: i64,
+    unread: usize,
+    highlights: usize,
+    activity: Activity,
+}
+
+/// What happened in a buffer since it was last read, in increasing order of
+/// importance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Activity {
+    #[default]
+    None,
+    /// Only joins, parts and other events
+    Events,
+    Messages,
+    Highlight,
+}
+
+fn kind_activity(kind: &LineKind) -> Activity {
+    match kind {
+        LineKind::Message
+        | LineKind::Action
+        | LineKind::Notice
+        | LineKind::Ctcp { .. }
+        | LineKind::CtcpReply { .. }
+        | LineKind::Error => Activity::Messages,
+        LineKind::Join
+        | LineKind::Part
+        | LineKind::Quit
+        | LineKind::Kick { .. }
+        | LineKind::Mode
+        | LineKind::Topic
+        | LineKind::Nick { .. }
+        | LineKind::Netsplit { .. }
+        | LineKind::Netjoin { .. } => Activity::Events,
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Line {
-    pub source: Source,
+    /// Sender of the message, `None` for lines of the client itself
+    pub source: Option<Source>,
     pub id: i64,
+    pub kind: LineKind,
+    /// Text of the line, depending on its kind, e.g. the reason of a `PART`
     pub message: AttrValue,
+    /// Time since the UNIX epoch, as sent by the server with `server-time`
+    pub time: Duration,
+    /// The line mentions us
+    pub highlight: bool,
+    /// Id of the message on the server, with `message-tags`
+    pub msgid: Option<AttrValue>,
+}
+
+impl Line {
+    /// This line has no `msgid` yet and is the same message as `other`, e.g.
+    /// our own message as echoed by the client and then sent by the server
+    fn echoes(&self, other: &Line) -> bool {
+        self.msgid.is_none()
+            && nick(&self.source).is_some()
+            && nick(&self.source) == nick(&other.source)
+            && self.kind == other.kind
+            && self.message == other.message
+            && self.time.abs_diff(other.time) <= ECHO_TOLERANCE
+    }
+}
+
+fn nick(source: &Option<Source>) -> Option<&str> {
+    match source {
+        Some(Source::User(user)) => Some(&user.nick),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineKind {
+    Message,
+    /// CTCP `ACTION`, e.g. `/me waves`
+    Action,
+    Notice,
+    Join,
+    Part,
+    Quit,
+    Kick {
+        nick: AttrValue,
+    },
+    /// Mode string with its parameters
+    Mode,
+    Topic,
+    Nick {
+        nickname: AttrValue,
+    },
+    /// Users who quit at once because servers split, with the servers as
+    /// message
+    Netsplit {
+        nicks: Vec<AttrValue>,
+    },
+    /// Users who are back after a netsplit, with the servers as message
+    Netjoin {
+        nicks: Vec<AttrValue>,
+    },
+    /// CTCP query other than `ACTION`, with its parameters as message
+    Ctcp {
+        command: AttrValue,
+    },
+    /// Answer to a CTCP query, with its parameters as message
+    CtcpReply {
+        command: AttrValue,
+    },
+    Error,
+}
+
+/// What a buffer shows, which also identifies it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BufferName {
+    /// Messages of the server itself, named after the server
+    Server(AttrValue),
+    Channel(ChannelName),
+    /// Private messages with a user
+    Query(Nickname),
+}
+
+impl BufferName {
+    pub fn as_str(&self) -> &str {
+        match self {
+            BufferName::Server(name) => name,
+            BufferName::Channel(channel) => channel.as_str(),
+            BufferName::Query(nickname) => nickname.as_str(),
+        }
+    }
+
+    pub fn as_attr_value(&self) -> AttrValue {
+        match self {
+            BufferName::Server(name) => name.clone(),
+            BufferName::Channel(channel) => channel.as_attr_value(),
+            BufferName::Query(nickname) => nickname.as_attr_value(),
+        }
+    }
+
+    pub fn channel(&self) -> Option<&ChannelName> {
+        match self {
+            BufferName::Channel(channel) => Some(channel),
+            _ => None,
+        }
+    }
+
+    /// Same name with another case mapping
+    pub fn with_casemapping(&self, casemapping: CaseMapping) -> Self {
+        match self {
+            BufferName::Server(name) => BufferName::Server(name.clone()),
+            BufferName::Channel(channel) => {
+                BufferName::Channel(channel.with_casemapping(casemapping))
+            }
+            BufferName::Query(nickname) => {
+                BufferName::Query(nickname.with_casemapping(casemapping))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BufferName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
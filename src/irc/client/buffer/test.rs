@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use crate::irc::parser::{Source, User};
+
+use super::{Activity, Buffer, BufferName, LineKind, MAX_LINES};
+
+fn buffer() -> Buffer {
+    Buffer::new(0, BufferName::Server("server".into()))
+}
+
+fn push(buffer: &mut Buffer, message: &str, secs: u64, msgid: Option<&str>) {
+    let time = Duration::from_secs(secs);
+    buffer.push(None, LineKind::Message, message.to_owned().into(), time);
+    let line = buffer.lines.last_mut().unwrap();
+    line.msgid = msgid.map(|msgid| msgid.to_owned().into());
+}
+
+fn messages(buffer: &Buffer) -> Vec<&str> {
+    buffer
+        .lines
+        .iter()
+        .map(|line| line.message.as_str())
+        .collect()
+}
+
+#[test]
+fn test_into_history() {
+    let mut buffer = buffer();
+    push(&mut buffer, "live", 30, Some("c"));
+    buffer.mark_read();
+
+    let mark = buffer.mark();
+    push(&mut buffer, "first", 10, Some("a"));
+    push(&mut buffer, "second", 20, None);
+    push(&mut buffer, "duplicate", 30, Some("c"));
+    buffer.into_history(&mark);
+
+    assert_eq!(messages(&buffer), vec!["first", "second", "live"]);
+    assert_eq!(buffer.unread, 0);
+    assert_eq!(buffer.activity, Activity::None);
+
+    // ids stay unique
+    push(&mut buffer, "new", 40, None);
+    let mut ids = buffer.lines.iter().map(|line| line.id).collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+}
+
+#[test]
+fn test_into_history_echo() {
+    let mut buffer = buffer();
+    let dan = Some(Source::User(User {
+        nick: "dan".into(),
+        user: None,
+        host: None,
+    }));
+    let time = Duration::from_secs(100);
+    buffer.push(dan.clone(), LineKind::Message, "mine".into(), time);
+
+    let mark = buffer.mark();
+    let time = Duration::from_millis(100_250);
+    buffer.push(dan.clone(), LineKind::Message, "mine".into(), time);
+    buffer.lines_since(&mark)[0].msgid = Some("a".into());
+    // far later, so this is another message
+    let time = Duration::from_secs(1000);
+    buffer.push(dan, LineKind::Message, "mine".into(), time);
+    buffer.lines_since(&mark)[1].msgid = Some("b".into());
+    buffer.into_history(&mark);
+
+    let msgids = buffer.lines.iter().map(|line| line.msgid.as_deref());
+    assert_eq!(msgids.collect::<Vec<_>>(), vec![Some("a"), Some("b")]);
+}
+
+#[test]
+fn test_push_trims() {
+    let mut buffer = buffer();
+    for secs in 0..(MAX_LINES + MAX_LINES / 10 + 1) as u64 {
+        push(&mut buffer, "line", secs, None);
+    }
+    assert_eq!(buffer.lines.len(), MAX_LINES);
+    let last = buffer.lines.last().unwrap();
+    assert_eq!(last.id, (MAX_LINES + MAX_LINES / 10) as i64);
+}
+
+#[test]
+fn test_trim_keeps_marks_and_history() {
+    let mut buffer = buffer();
+    for secs in 0..(MAX_LINES + MAX_LINES / 10) as u64 {
+        push(&mut buffer, "line", secs + 100, None);
+    }
+
+    // the trim happens while the mark is taken
+    let mark = buffer.mark();
+    push(&mut buffer, "new", 20_000, None);
+    assert_eq!(buffer.lines.len(), MAX_LINES);
+    let lines = buffer.lines_since(&mark);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].message, "new");
+
+    // history is sorted in, and not dropped by the next trim
+    let mark = buffer.mark();
+    push(&mut buffer, "old", 0, Some("a"));
+    buffer.into_history(&mark);
+    push(&mut buffer, "newer", 20_001, None);
+    assert_eq!(buffer.lines[0].message, "old");
+    assert_eq!(buffer.lines.last().unwrap().message, "newer");
+}
+
+#[test]
+fn test_lines_since() {
+    let mut buffer = buffer();
+    push(&mut buffer, "old", 10, None);
+    let mark = buffer.mark();
+    push(&mut buffer, "new", 20, None);
+
+    let lines = buffer.lines_since(&mark);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].message, "new");
+}
@@ -17,6 +17,10 @@ mod test;
 type Result = std::result::Result<Messages, Error>;
 type Messages = Vec<Message>;
 
+/// Maximum length of the capability list in a single `CAP REQ`, so the full
+/// line stays well below the 512 byte limit
+const MAX_REQ_LENGTH: usize = 400;
+
 #[derive(Debug)]
 pub enum Error {
     UnexpectedCommand(Command),
@@ -26,17 +30,32 @@ pub enum Error {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CapNegotiator {
+    /// Capabilities supported by the client. Values restrict which values of
+    /// the server are accepted, e.g. `sasl=PLAIN,EXTERNAL`.
+    pub supported: Vec<Capability>,
+    /// Capabilities advertised by the server via `CAP LS` and `CAP NEW`
+    pub available: Vec<Capability>,
+    /// Capabilities which still wait for `ACK` or `NAK`. Before the server
+    /// listed its capabilities, these are all supported capabilities.
     pub requested: Vec<Capability>,
     pub acknowledged: Vec<Capability>,
     pub not_acknowledged: Vec<Capability>,
+    /// Set while a multiline `CAP LS` reply is received
+    ls_pending: bool,
+    /// Set once the last line of the first `CAP LS` reply has been received
+    ls_complete: bool,
 }
 
 impl CapNegotiator {
     pub fn request(capabilities: Vec<Capability>) -> Self {
         CapNegotiator {
+            supported: capabilities.clone(),
+            available: vec![],
             requested: capabilities,
             acknowledged: vec![],
             not_acknowledged: vec![],
+            ls_pending: false,
+            ls_complete: false,
         }
     }
 
@@ -54,79 +73,188 @@ impl CapNegotiator {
         Message::generic(GenericMessage::cmd("CAP").param("END")).build()
     }
 
+    /// Check if negotiation is done, i.e. the server listed its capabilities
+    /// and all requests have been answered. `CAP END` should be sent after
+    /// this (and after SASL authentication, if enabled).
+    pub fn ready_to_end(&self) -> bool {
+        self.ls_complete && self.requested.is_empty()
+    }
+
+    /// List of enabled capabilities
+    pub fn enabled(&self) -> &[Capability] {
+        &self.acknowledged
+    }
+
+    pub fn is_enabled(&self, key: &str) -> bool {
+        self.acknowledged.iter().any(|cap| cap.key() == key)
+    }
+
+    /// Capability as advertised by the server, including its values
+    pub fn available(&self, key: &str) -> Option<&Capability> {
+        self.available.iter().find(|cap| cap.key() == key)
+    }
+
     pub fn handle(&mut self, message: MsgCap) -> Result {
-        let nick = &message.nick;
-        let subcmd = &message.sub_command;
+        match message.sub_command {
+            SubCommand::LS(multiline, capabilities) => self.ls_reply(multiline, capabilities),
+            SubCommand::LIST(multiline, capabilities) => self.list_reply(multiline, capabilities),
+            SubCommand::ACK(capabilities) => self.ack(capabilities),
+            SubCommand::NAK(capabilities) => self.nak(capabilities),
+            SubCommand::NEW(capabilities) => self.new_capabilities(capabilities),
+            SubCommand::DEL(capabilities) => self.del(capabilities),
+            subcmd @ SubCommand::REQ(_) => Err(Error::UnexpectedSubcommand(subcmd.to_string())),
+        }
+    }
+
+    fn ls_reply(&mut self, multiline: bool, capabilities: Vec<Capability>) -> Result {
+        // a new LS reply replaces all previously advertised capabilities
+        if !self.ls_pending {
+            self.available.clear();
+        }
+        self.available.extend(capabilities);
+        self.ls_pending = multiline;
+
+        if multiline {
+            return Ok(vec![]);
+        }
 
-        println!("{nick:?} {subcmd:?}");
+        // only the first LS reply starts negotiation
+        if self.ls_complete {
+            return Ok(vec![]);
+        }
+        self.ls_complete = true;
+
+        let request = self.match_listed_capabilities(&self.available);
+        self.requested = request.clone();
+        Ok(Self::req(request))
+    }
 
-        match subcmd {
-            SubCommand::LS(_, capabilities) => self.match_listed_capabilities(capabilities),
-            //"LS" => self.match_listed_capabilities(param.to_vec()),
-            //"ACK" => self.ack(param.to_vec()),
-            //"NAK" => self.nak(param.to_vec()),
-            _ => Err(Error::UnexpectedSubcommand(subcmd.to_string())),
+    fn list_reply(&mut self, multiline: bool, capabilities: Vec<Capability>) -> Result {
+        // LIST replies contain all enabled capabilities
+        if !self.ls_pending {
+            self.acknowledged.clear();
         }
+        self.acknowledged.extend(capabilities);
+        self.ls_pending = multiline;
+        Ok(vec![])
     }
 
-    // TODO:
-    #[allow(dead_code)]
-    fn match_listed_capabilities(&self, capabilities: &Vec<Capability>) -> Result {
+    /// Filter capabilities for the ones supported by this client
+    fn match_listed_capabilities(&self, capabilities: &[Capability]) -> Vec<Capability> {
+        capabilities
+            .iter()
+            .filter(|cap| self.is_supported(cap))
+            .filter(|cap| !self.is_enabled(cap.key()))
+            .map(|cap| Capability::Single(cap.key().clone()))
+            .collect()
+    }
+
+    fn is_supported(&self, capability: &Capability) -> bool {
+        if let Capability::Disabled(_) = capability {
+            return false;
+        }
+
+        self.supported.iter().any(|supported| {
+            if supported.key() != capability.key() {
+                return false;
+            }
+            // servers may not advertise values at all (CAP LS 301)
+            let values = capability.value_list();
+            let supported_values = supported.value_list();
+            values.is_empty()
+                || supported_values.is_empty()
+                || values.iter().any(|value| supported_values.contains(value))
+        })
+    }
+
+    /// Build `CAP REQ` messages, split into multiple messages if necessary
+    fn req(capabilities: Vec<Capability>) -> Messages {
+        let mut messages = vec![];
         let mut request: Vec<AttrValue> = vec![];
+        let mut length = 0;
 
-        // check if input parameters contain any requested capabilities
         for capability in capabilities {
-            if self.requested.contains(capability) {
-                request.push(capability.to_string().into());
+            let capability: AttrValue = capability.to_string().into();
+            if !request.is_empty() && length + 1 + capability.len() > MAX_REQ_LENGTH {
+                messages.push(Self::req_message(&request));
+                request.clear();
+                length = 0;
             }
+            length += capability.len() + usize::from(!request.is_empty());
+            request.push(capability);
         }
 
         // do not send unnecessary empty requests
-        if request.is_empty() {
-            Ok(vec![])
-        } else {
-            Ok(vec![Message::cap(
-                MsgCap::req().capabilities(request).build(),
-            )
-            .build()])
-            //.param("REQ")
-            //.param(&request.join(" "))
-            //.build()])
+        if !request.is_empty() {
+            messages.push(Self::req_message(&request));
         }
+        messages
     }
 
-    // TODO:
-    #[allow(dead_code)]
-    fn nak(&mut self, params: Vec<AttrValue>) -> Result {
-        for param in params {
-            for cap in param.split(" ") {
-                // insert capability into list of acknowledged capabilities
-                let cap = Capability::new(cap);
-                self.not_acknowledged.push(cap.clone());
+    fn req_message(request: &[AttrValue]) -> Message {
+        Message::generic(
+            GenericMessage::cmd("CAP")
+                .param("REQ")
+                .param(&request.join(" ")),
+        )
+        .build()
+    }
 
-                // remove from list of requested capabilities
-                if let Some(requested_idx) = self.requested.iter().position(|c| c == &cap) {
-                    self.requested.swap_remove(requested_idx);
-                }
+    /// Remove capability from the list of pending requests
+    fn remove_requested(&mut self, key: &str) -> bool {
+        match self.requested.iter().position(|cap| cap.key() == key) {
+            Some(idx) => {
+                self.requested.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn ack(&mut self, capabilities: Vec<Capability>) -> Result {
+        for cap in capabilities {
+            self.remove_requested(cap.key());
+            match cap {
+                // `-cap` acknowledges that a capability has been disabled
+                Capability::Disabled(key) => self.acknowledged.retain(|c| c.key() != &key),
+                cap if !self.is_enabled(cap.key()) => self.acknowledged.push(cap),
+                _ => (),
             }
         }
         Ok(vec![])
     }
 
-    // TODO:
-    #[allow(dead_code)]
-    fn ack(&mut self, params: Vec<AttrValue>) -> Result {
-        for param in params {
-            for cap in param.split(" ") {
-                // insert capability into list of acknowledged capabilities
-                let cap = Capability::new(cap);
-                self.acknowledged.push(cap.clone());
+    fn nak(&mut self, capabilities: Vec<Capability>) -> Result {
+        for cap in capabilities {
+            // the server rejected the whole request, nothing changes
+            self.remove_requested(cap.key());
+            self.not_acknowledged.push(cap);
+        }
+        Ok(vec![])
+    }
 
-                // remove from list of requested capabilities
-                if let Some(requested_idx) = self.requested.iter().position(|c| c == &cap) {
-                    self.requested.swap_remove(requested_idx);
-                }
-            }
+    /// `cap-notify`: server advertises new capabilities
+    fn new_capabilities(&mut self, capabilities: Vec<Capability>) -> Result {
+        for cap in &capabilities {
+            self.available.retain(|c| c.key() != cap.key());
+            self.available.push(cap.clone());
+        }
+
+        let request = self
+            .match_listed_capabilities(&capabilities)
+            .into_iter()
+            .filter(|cap| !self.requested.iter().any(|r| r.key() == cap.key()))
+            .collect::<Vec<_>>();
+        self.requested.extend(request.iter().cloned());
+        Ok(Self::req(request))
+    }
+
+    /// `cap-notify`: server removed capabilities, which are disabled now
+    fn del(&mut self, capabilities: Vec<Capability>) -> Result {
+        for cap in capabilities {
+            self.available.retain(|c| c.key() != cap.key());
+            self.acknowledged.retain(|c| c.key() != cap.key());
+            self.remove_requested(cap.key());
         }
         Ok(vec![])
     }
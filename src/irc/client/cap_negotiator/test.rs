@@ -1,4 +1,7 @@
-use crate::irc::{client::Capability, parser::msg_cap::MsgCap};
+use crate::irc::{
+    client::Capability,
+    parser::{message::Message, msg_cap::MsgCap},
+};
 
 use super::CapNegotiator;
 
@@ -7,7 +10,7 @@ fn test_empty_request() {
     let mut negotiator = CapNegotiator::request(vec![]);
     assert_eq!(negotiator.requested.len(), 0);
 
-    let ls = negotiator.ls(Some("302".into()));
+    let ls = negotiator.ls(Some("302"));
 
     assert_eq!(ls.to_string(), "CAP LS 302");
 
@@ -18,7 +21,8 @@ fn test_empty_request() {
                 .ls(false)
                 .single("multi-prefix")
                 .single("sasl")
-                .build(),
+                .build()
+                .unwrap(),
         )
         .unwrap();
 
@@ -29,12 +33,11 @@ fn test_empty_request() {
 }
 
 #[test]
-#[ignore = "need to be correctly implemented again"]
 fn test_sinlge_request() {
     let mut negotiator = CapNegotiator::request(vec![Capability::new("sasl")]);
     assert_eq!(negotiator.requested.len(), 1);
 
-    let ls = negotiator.ls(Some("302".into()));
+    let ls = negotiator.ls(Some("302"));
 
     assert_eq!(ls.to_string(), "CAP LS 302");
 
@@ -45,7 +48,8 @@ fn test_sinlge_request() {
                 .ls(false)
                 .single("multi-prefix")
                 .single("sasl")
-                .build(),
+                .build()
+                .unwrap(),
         )
         .unwrap();
 
@@ -57,7 +61,6 @@ fn test_sinlge_request() {
 }
 
 #[test]
-#[ignore = "need to be correctly implemented again"]
 fn test_multiple_requests() {
     let mut negotiator = CapNegotiator::request(vec![
         Capability::new("sasl"),
@@ -65,7 +68,7 @@ fn test_multiple_requests() {
     ]);
     assert_eq!(negotiator.requested.len(), 2);
 
-    let ls = negotiator.ls(Some("302".into()));
+    let ls = negotiator.ls(Some("302"));
 
     assert_eq!(ls.to_string(), "CAP LS 302");
 
@@ -76,7 +79,8 @@ fn test_multiple_requests() {
                 .ls(false)
                 .single("multi-prefix")
                 .single("sasl")
-                .build(),
+                .build()
+                .unwrap(),
         )
         .unwrap();
 
@@ -88,7 +92,14 @@ fn test_multiple_requests() {
 
     // test ack/nak
     let msgs = negotiator
-        .handle(MsgCap::builder().star().ack().single("sasl").build())
+        .handle(
+            MsgCap::builder()
+                .star()
+                .ack()
+                .single("sasl")
+                .build()
+                .unwrap(),
+        )
         .unwrap();
     assert_eq!(msgs, vec![]);
 
@@ -100,7 +111,8 @@ fn test_multiple_requests() {
                 .star()
                 .nak()
                 .single("multi-prefix")
-                .build(),
+                .build()
+                .unwrap(),
         )
         .unwrap();
     assert_eq!(msgs, vec![]);
@@ -115,3 +127,138 @@ fn test_multiple_requests() {
 
     assert_eq!(msgs.to_string(), "CAP END");
 }
+
+/// Parse CAP message as sent by the server
+fn cap(input: &str) -> MsgCap {
+    use crate::irc::parser::message::MessageType;
+
+    match Message::parse(input).unwrap().msg_type {
+        MessageType::Capability(cap) => cap,
+        msg_type => panic!("Not a CAP message: {msg_type:?}"),
+    }
+}
+
+#[test]
+fn test_multiline_ls() {
+    let mut negotiator = CapNegotiator::request(vec![
+        Capability::new("sasl"),
+        Capability::new("server-time"),
+    ]);
+
+    let msgs = negotiator
+        .handle(cap("CAP * LS * :multi-prefix sasl"))
+        .unwrap();
+    assert_eq!(msgs, vec![]);
+    assert!(!negotiator.ready_to_end());
+
+    let msgs = negotiator
+        .handle(cap("CAP * LS :server-time batch"))
+        .unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].to_string(), "CAP REQ :sasl server-time");
+    assert_eq!(negotiator.available.len(), 4);
+
+    negotiator
+        .handle(cap("CAP * ACK :sasl server-time"))
+        .unwrap();
+    assert!(negotiator.ready_to_end());
+    assert!(negotiator.is_enabled("sasl"));
+    assert!(negotiator.is_enabled("server-time"));
+    assert!(!negotiator.is_enabled("batch"));
+}
+
+#[test]
+fn test_nothing_to_request() {
+    let mut negotiator = CapNegotiator::request(vec![Capability::new("sasl")]);
+    assert!(!negotiator.ready_to_end());
+
+    let msgs = negotiator.handle(cap("CAP * LS :batch")).unwrap();
+    assert_eq!(msgs, vec![]);
+    assert!(negotiator.ready_to_end());
+    assert!(negotiator.enabled().is_empty());
+}
+
+#[test]
+fn test_value_constraints() {
+    let mut negotiator =
+        CapNegotiator::request(vec![Capability::values("sasl", vec!["PLAIN", "EXTERNAL"])]);
+
+    // no supported mechanism
+    let msgs = negotiator
+        .handle(cap("CAP * LS :sasl=SCRAM-SHA-256"))
+        .unwrap();
+    assert_eq!(msgs, vec![]);
+
+    let mut negotiator =
+        CapNegotiator::request(vec![Capability::values("sasl", vec!["PLAIN", "EXTERNAL"])]);
+
+    let msgs = negotiator
+        .handle(cap("CAP * LS :sasl=SCRAM-SHA-256,PLAIN"))
+        .unwrap();
+    assert_eq!(msgs[0].to_string(), "CAP REQ sasl");
+    assert_eq!(
+        negotiator.available("sasl"),
+        Some(&Capability::values("sasl", vec!["SCRAM-SHA-256", "PLAIN"]))
+    );
+}
+
+#[test]
+fn test_ack_disable() {
+    let mut negotiator = CapNegotiator::request(vec![Capability::new("multi-prefix")]);
+    negotiator.handle(cap("CAP * LS :multi-prefix")).unwrap();
+    negotiator.handle(cap("CAP * ACK :multi-prefix")).unwrap();
+    assert!(negotiator.is_enabled("multi-prefix"));
+
+    negotiator.handle(cap("CAP * ACK :-multi-prefix")).unwrap();
+    assert!(!negotiator.is_enabled("multi-prefix"));
+}
+
+#[test]
+fn test_cap_notify() {
+    let mut negotiator = CapNegotiator::request(vec![
+        Capability::new("sasl"),
+        Capability::new("away-notify"),
+    ]);
+    negotiator.handle(cap("CAP * LS :sasl")).unwrap();
+    negotiator.handle(cap("CAP * ACK :sasl")).unwrap();
+    assert!(negotiator.ready_to_end());
+
+    // newly advertised capability is requested
+    let msgs = negotiator
+        .handle(cap("CAP dan NEW :away-notify batch"))
+        .unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].to_string(), "CAP REQ away-notify");
+
+    negotiator.handle(cap("CAP dan ACK :away-notify")).unwrap();
+    assert!(negotiator.is_enabled("away-notify"));
+
+    // deleted capabilities are disabled
+    let msgs = negotiator.handle(cap("CAP dan DEL :sasl")).unwrap();
+    assert_eq!(msgs, vec![]);
+    assert!(!negotiator.is_enabled("sasl"));
+    assert!(negotiator.available("sasl").is_none());
+
+    // advertising an enabled capability again does not request it twice
+    let msgs = negotiator.handle(cap("CAP dan NEW :away-notify")).unwrap();
+    assert_eq!(msgs, vec![]);
+}
+
+#[test]
+fn test_long_request_is_split() {
+    let names = (0..60)
+        .map(|i| format!("vendor.example.com/capability-{i}"))
+        .collect::<Vec<_>>();
+
+    let mut negotiator =
+        CapNegotiator::request(names.iter().map(|name| Capability::new(name)).collect());
+
+    let msgs = negotiator
+        .handle(cap(&format!("CAP * LS :{}", names.join(" "))))
+        .unwrap();
+    assert!(msgs.len() > 1);
+    for msg in &msgs {
+        assert!(msg.to_string().len() < 512);
+    }
+    assert_eq!(negotiator.requested.len(), 60);
+}
@@ -0,0 +1,163 @@
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+use yew::AttrValue;
+
+#[cfg(test)]
+mod test;
+
+/// Case insensitive comparison of nick and channel names. Which characters
+/// are considered equal depends on the `CASEMAPPING` of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMapping {
+    /// Only `A-Z` map to `a-z`
+    Ascii,
+    /// Like `Ascii`, additionally `[]\~` map to `{}|^`
+    #[default]
+    Rfc1459,
+    /// Like `Ascii`, additionally `[]\` map to `{}|`
+    StrictRfc1459,
+    /// Unicode lower case
+    Rfc7613,
+}
+
+impl CaseMapping {
+    /// Parse the value of the `CASEMAPPING` token, unknown values fall back
+    /// to the default
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "ascii" => CaseMapping::Ascii,
+            "strict-rfc1459" | "rfc1459-strict" => CaseMapping::StrictRfc1459,
+            "rfc7613" => CaseMapping::Rfc7613,
+            _ => CaseMapping::Rfc1459,
+        }
+    }
+
+    /// Normalize a nick or channel name for comparison
+    pub fn casefold(&self, name: &str) -> String {
+        match self {
+            CaseMapping::Ascii => name.to_ascii_lowercase(),
+            CaseMapping::Rfc7613 => name.to_lowercase(),
+            CaseMapping::Rfc1459 | CaseMapping::StrictRfc1459 => name
+                .chars()
+                .map(|c| match c {
+                    '[' => '{',
+                    ']' => '}',
+                    '\\' => '|',
+                    '~' if *self == CaseMapping::Rfc1459 => '^',
+                    c => c.to_ascii_lowercase(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Defines a name type, which keeps the name as sent by the server for display
+/// but compares, orders and hashes by its case folded form
+macro_rules! casemapped_name {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            name: AttrValue,
+            folded: AttrValue,
+            casemapping: CaseMapping,
+        }
+
+        impl $name {
+            pub fn new(name: &str, casemapping: CaseMapping) -> Self {
+                $name {
+                    name: name.to_owned().into(),
+                    folded: casemapping.casefold(name).into(),
+                    casemapping,
+                }
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.name
+            }
+
+            pub fn as_attr_value(&self) -> AttrValue {
+                self.name.clone()
+            }
+
+            /// Case folded name, which is used for comparison
+            pub fn folded(&self) -> &str {
+                &self.folded
+            }
+
+            pub fn casemapping(&self) -> CaseMapping {
+                self.casemapping
+            }
+
+            /// Same name with another case mapping, e.g. after the server
+            /// advertised its `CASEMAPPING`
+            pub fn with_casemapping(&self, casemapping: CaseMapping) -> Self {
+                Self::new(&self.name, casemapping)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.folded == other.folded
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                *self.folded == self.casemapping.casefold(other)
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self == *other
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.folded.hash(state)
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.folded.cmp(&other.folded)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.name)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.name
+            }
+        }
+    };
+}
+
+casemapped_name!(
+    /// Nick name of a user, e.g. `Dan` which is equal to `dan`
+    Nickname
+);
+
+casemapped_name!(
+    /// Name of a channel including its prefix, e.g. `#Rust` which is equal
+    /// to `#rust`
+    ChannelName
+);
@@ -0,0 +1,62 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::{CaseMapping, ChannelName, Nickname};
+
+#[test]
+fn test_parse() {
+    assert_eq!(CaseMapping::parse("ascii"), CaseMapping::Ascii);
+    assert_eq!(CaseMapping::parse("rfc1459"), CaseMapping::Rfc1459);
+    assert_eq!(
+        CaseMapping::parse("strict-rfc1459"),
+        CaseMapping::StrictRfc1459
+    );
+    assert_eq!(
+        CaseMapping::parse("rfc1459-strict"),
+        CaseMapping::StrictRfc1459
+    );
+    assert_eq!(CaseMapping::parse("rfc7613"), CaseMapping::Rfc7613);
+    assert_eq!(CaseMapping::parse("unknown"), CaseMapping::Rfc1459);
+}
+
+#[test]
+fn test_casefold() {
+    assert_eq!(CaseMapping::Ascii.casefold("Dan[M]~"), "dan[m]~");
+    assert_eq!(CaseMapping::Rfc1459.casefold("Dan[M]\\~"), "dan{m}|^");
+    assert_eq!(CaseMapping::StrictRfc1459.casefold("Dan[M]\\~"), "dan{m}|~");
+    assert_eq!(CaseMapping::Rfc7613.casefold("JÖRG"), "jörg");
+}
+
+#[test]
+fn test_equality() {
+    let rust = ChannelName::new("#Rust", CaseMapping::Rfc1459);
+    assert_eq!(rust, ChannelName::new("#rust", CaseMapping::Rfc1459));
+    assert_eq!(rust, "#RUST");
+    assert_ne!(rust, "#rust2");
+
+    // original spelling is kept for display
+    assert_eq!(rust.as_str(), "#Rust");
+    assert_eq!(rust.to_string(), "#Rust");
+    assert_eq!(rust.folded(), "#rust");
+
+    let dan = Nickname::new("Dan[m]", CaseMapping::Rfc1459);
+    assert_eq!(dan, "dan{M}");
+    assert_ne!(dan.with_casemapping(CaseMapping::Ascii), "dan{M}");
+}
+
+#[test]
+fn test_keys() {
+    let mut set = HashSet::new();
+    set.insert(ChannelName::new("#Rust", CaseMapping::Rfc1459));
+    assert!(!set.insert(ChannelName::new("#rust", CaseMapping::Rfc1459)));
+
+    let mut map = BTreeMap::new();
+    map.insert(Nickname::new("Dan", CaseMapping::Ascii), 1);
+    map.insert(Nickname::new("DAN", CaseMapping::Ascii), 2);
+    map.insert(Nickname::new("alice", CaseMapping::Ascii), 3);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&Nickname::new("dan", CaseMapping::Ascii)), Some(&2));
+
+    // ordered by folded name
+    let names = map.keys().map(|n| n.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["alice", "Dan"]);
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+use yew::AttrValue;
+
+use super::{
+    casemapping::{CaseMapping, Nickname},
+    mode::ModeChange,
+    server_info::ServerInfo,
+};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub nick: Nickname,
+    pub user: Option<AttrValue>,
+    pub host: Option<AttrValue>,
+    /// Membership modes, e.g. `o` for operators, highest rank first
+    pub modes: Vec<char>,
+}
+
+impl Member {
+    pub fn new(nick: Nickname) -> Self {
+        Member {
+            nick,
+            user: None,
+            host: None,
+            modes: vec![],
+        }
+    }
+
+    /// Prefix of the highest membership mode, e.g. `@`
+    pub fn prefix(&self, server_info: &ServerInfo) -> Option<char> {
+        self.modes
+            .first()
+            .and_then(|mode| server_info.mode_prefix(*mode))
+    }
+
+    fn set_mode(&mut self, mode: char, set: bool, server_info: &ServerInfo) {
+        self.modes.retain(|m| *m != mode);
+        if set {
+            self.modes.push(mode);
+            self.modes.sort_by_key(|m| rank(*m, server_info));
+        }
+    }
+}
+
+/// Position of a membership mode in `PREFIX`, lower is higher
+fn rank(mode: char, server_info: &ServerInfo) -> usize {
+    server_info
+        .prefix
+        .iter()
+        .position(|(m, _)| *m == mode)
+        .unwrap_or(usize::MAX)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic {
+    pub text: AttrValue,
+    /// Nick name or mask of who set the topic
+    pub setter: Option<AttrValue>,
+    /// Unix timestamp of when the topic was set
+    pub set_at: Option<u64>,
+}
+
+/// State of a joined channel, which is kept up to date from `JOIN`, `PART`,
+/// `NAMES`, `TOPIC`, `MODE` and related messages
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Channel {
+    pub members: BTreeMap<Nickname, Member>,
+    pub topic: Option<Topic>,
+    /// Channel modes with their parameter, e.g. `k` with the key. List modes
+    /// like bans are not tracked.
+    pub modes: BTreeMap<char, Option<AttrValue>>,
+    /// Unix timestamp of when the channel was created
+    pub created: Option<u64>,
+    /// Set while a `RPL_NAMREPLY` list is received, the member list is
+    /// replaced by the new one
+    names_pending: bool,
+}
+
+impl Channel {
+    /// Add members of a `RPL_NAMREPLY`. Entries carry their membership
+    /// prefixes, all of them with `multi-prefix`, and with
+    /// `userhost-in-names` also the full mask, e.g. `@+dan!d@host`.
+    pub fn names(&mut self, names: &[AttrValue], server_info: &ServerInfo) {
+        if !self.names_pending {
+            self.members.clear();
+            self.names_pending = true;
+        }
+
+        for name in names {
+            let mut modes = vec![];
+            let mut rest = name.as_str();
+            while let Some((c, mode)) = rest
+                .chars()
+                .next()
+                .and_then(|c| server_info.prefix_mode(c).map(|mode| (c, mode)))
+            {
+                modes.push(mode);
+                rest = &rest[c.len_utf8()..];
+            }
+
+            let (nick, user, host) = split_mask(rest);
+            if nick.is_empty() {
+                continue;
+            }
+
+            let mut member = Member::new(server_info.nickname(nick));
+            member.user = user.map(|user| user.to_owned().into());
+            member.host = host.map(|host| host.to_owned().into());
+            for mode in modes {
+                member.set_mode(mode, true, server_info);
+            }
+            self.members.insert(member.nick.clone(), member);
+        }
+    }
+
+    /// `RPL_ENDOFNAMES` was received
+    pub fn end_of_names(&mut self) {
+        self.names_pending = false;
+    }
+
+    pub fn join(&mut self, member: Member) {
+        self.members.insert(member.nick.clone(), member);
+    }
+
+    pub fn remove(&mut self, nick: &Nickname) -> Option<Member> {
+        self.members.remove(nick)
+    }
+
+    pub fn rename(&mut self, old: &Nickname, new: Nickname) {
+        if let Some(mut member) = self.members.remove(old) {
+            member.nick = new.clone();
+            self.members.insert(new, member);
+        }
+    }
+
+    /// Apply changes of a `MODE` message or `RPL_CHANNELMODEIS`
+    pub fn apply_modes(&mut self, changes: &[ModeChange], server_info: &ServerInfo) {
+        for change in changes {
+            match change {
+                ModeChange::Member { set, mode, nick } => {
+                    if let Some(member) = self.members.get_mut(&server_info.nickname(nick)) {
+                        member.set_mode(*mode, *set, server_info);
+                    }
+                }
+                ModeChange::List { .. } => (),
+                ModeChange::Parameter {
+                    set: true,
+                    mode,
+                    value,
+                } => {
+                    self.modes.insert(*mode, value.clone());
+                }
+                ModeChange::Flag { set: true, mode } => {
+                    self.modes.insert(*mode, None);
+                }
+                ModeChange::Parameter { mode, .. } | ModeChange::Flag { mode, .. } => {
+                    self.modes.remove(mode);
+                }
+            }
+        }
+    }
+
+    /// Members sorted by their highest membership mode, then by nick name
+    pub fn sorted_members(&self, server_info: &ServerInfo) -> Vec<&Member> {
+        let mut members = self.members.values().collect::<Vec<_>>();
+        members.sort_by_key(|member| {
+            let rank = member.modes.first().map(|mode| rank(*mode, server_info));
+            (rank.unwrap_or(usize::MAX), member.nick.clone())
+        });
+        members
+    }
+
+    /// Fold nick names again, after the case mapping of the server changed
+    pub fn set_casemapping(&mut self, casemapping: CaseMapping) {
+        self.members = std::mem::take(&mut self.members)
+            .into_values()
+            .map(|mut member| {
+                member.nick = member.nick.with_casemapping(casemapping);
+                (member.nick.clone(), member)
+            })
+            .collect();
+    }
+}
+
+/// Split `nick!user@host` into its parts
+fn split_mask(mask: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (rest, host) = match mask.split_once('@') {
+        Some((rest, host)) => (rest, Some(host)),
+        None => (mask, None),
+    };
+    match rest.split_once('!') {
+        Some((nick, user)) => (nick, Some(user), host),
+        None => (rest, None, host),
+    }
+}
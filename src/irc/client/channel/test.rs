@@ -0,0 +1,106 @@
+use yew::AttrValue;
+
+use crate::irc::client::{casemapping::CaseMapping, mode, server_info::ServerInfo};
+
+use super::Channel;
+
+fn names(channel: &Channel, server_info: &ServerInfo) -> Vec<String> {
+    channel
+        .sorted_members(server_info)
+        .iter()
+        .map(|member| {
+            let prefix = member.prefix(server_info).map(String::from);
+            format!("{}{}", prefix.unwrap_or_default(), member.nick)
+        })
+        .collect()
+}
+
+fn values(values: &[&str]) -> Vec<AttrValue> {
+    values.iter().map(|v| v.to_string().into()).collect()
+}
+
+#[test]
+fn test_names() {
+    let mut info = ServerInfo::default();
+    info.apply(&values(&["PREFIX=(qaohv)~&@%+"]));
+
+    let mut channel = Channel::default();
+    channel.names(&values(&["carol", "@+bob", "+alice"]), &info);
+    channel.names(&values(&["~zed!z@host", "%dave"]), &info);
+    channel.end_of_names();
+    assert_eq!(
+        names(&channel, &info),
+        vec!["~zed", "@bob", "%dave", "+alice", "carol"]
+    );
+
+    let bob = &channel.members[&info.nickname("BOB")];
+    assert_eq!(bob.modes, vec!['o', 'v']);
+    let zed = &channel.members[&info.nickname("zed")];
+    assert_eq!(zed.user.as_deref(), Some("z"));
+    assert_eq!(zed.host.as_deref(), Some("host"));
+
+    // a new reply replaces the list
+    channel.names(&values(&["carol"]), &info);
+    channel.end_of_names();
+    assert_eq!(names(&channel, &info), vec!["carol"]);
+}
+
+#[test]
+fn test_names_non_ascii_prefix() {
+    let mut info = ServerInfo::default();
+    info.apply(&values(&["PREFIX=(Yo)★@"]));
+
+    let mut channel = Channel::default();
+    channel.names(&values(&["★@dan", "★"]), &info);
+    channel.end_of_names();
+    assert_eq!(names(&channel, &info), vec!["★dan"]);
+    assert_eq!(channel.members[&info.nickname("dan")].modes, vec!['Y', 'o']);
+}
+
+#[test]
+fn test_modes() {
+    let info = ServerInfo::default();
+    let mut channel = Channel::default();
+    channel.names(&values(&["alice", "bob"]), &info);
+    channel.end_of_names();
+
+    let changes = mode::parse(
+        "+ovntk-b+l",
+        &values(&["alice", "Bob", "key", "*!*@spam", "10"]),
+        &info,
+    )
+    .unwrap();
+    channel.apply_modes(&changes, &info);
+    assert_eq!(names(&channel, &info), vec!["@alice", "+bob"]);
+    assert_eq!(
+        channel.modes.iter().collect::<Vec<_>>(),
+        vec![
+            (&'k', &Some("key".into())),
+            (&'l', &Some("10".into())),
+            (&'n', &None),
+            (&'t', &None),
+        ]
+    );
+
+    // removing the user limit takes no parameter, removing the key does
+    let changes = mode::parse("-lko+v", &values(&["key", "alice", "alice"]), &info).unwrap();
+    channel.apply_modes(&changes, &info);
+    assert_eq!(names(&channel, &info), vec!["+alice", "+bob"]);
+    assert_eq!(channel.modes.keys().collect::<Vec<_>>(), vec![&'n', &'t']);
+}
+
+#[test]
+fn test_join_part_rename() {
+    let info = ServerInfo::default();
+    let mut channel = Channel::default();
+    channel.names(&values(&["@alice", "bob"]), &info);
+    channel.end_of_names();
+
+    channel.rename(&info.nickname("ALICE"), info.nickname("alice2"));
+    channel.remove(&info.nickname("bob"));
+    assert_eq!(names(&channel, &info), vec!["@alice2"]);
+
+    channel.set_casemapping(CaseMapping::Ascii);
+    let alice = channel.members.keys().next().unwrap();
+    assert_eq!(alice.casemapping(), CaseMapping::Ascii);
+}
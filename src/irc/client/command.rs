@@ -0,0 +1,393 @@
+use std::fmt::Display;
+
+use yew::AttrValue;
+
+use crate::irc::parser::{message::Message, ParseError};
+
+use super::{buffer::BufferName, server_info::ServerInfo};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    UnknownCommand(AttrValue),
+    MissingArgument {
+        command: AttrValue,
+        argument: &'static str,
+    },
+    /// Command only works in a channel, or needs a channel as argument
+    NotInChannel(AttrValue),
+    /// Messages can not be sent to the server buffer
+    NoTarget,
+    /// Raw line of `/quote` is not a valid message
+    InvalidMessage(AttrValue),
+    /// The server buffer can not be closed
+    CannotClose,
+    /// Argument is longer than the server allows, e.g. with `TOPICLEN`
+    TooLong {
+        argument: &'static str,
+        max: usize,
+    },
+    /// More targets than the server allows with `TARGMAX`
+    TooManyTargets {
+        command: &'static str,
+        max: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownCommand(command) => write!(f, "Unknown command: /{command}"),
+            Error::MissingArgument { command, argument } => {
+                write!(f, "Usage: /{command}, missing <{argument}>")
+            }
+            Error::NotInChannel(command) => {
+                write!(
+                    f,
+                    "/{command} only works in a channel or with a channel argument"
+                )
+            }
+            Error::NoTarget => write!(
+                f,
+                "Messages can not be sent to the server, use /msg or /quote"
+            ),
+            Error::InvalidMessage(err) => write!(f, "Invalid message: {err}"),
+            Error::CannotClose => write!(f, "The server buffer can not be closed"),
+            Error::TooLong { argument, max } => {
+                write!(
+                    f,
+                    "The {argument} is too long, the server allows {max} bytes"
+                )
+            }
+            Error::TooManyTargets { command, max } => {
+                write!(f, "/{command} allows at most {max} targets")
+            }
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::InvalidMessage(err.to_string().into())
+    }
+}
+
+/// A parsed command, with targets resolved against the current buffer
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserCommand {
+    Message {
+        target: AttrValue,
+        text: AttrValue,
+    },
+    /// `/me`
+    Action {
+        target: AttrValue,
+        text: AttrValue,
+    },
+    Notice {
+        target: AttrValue,
+        text: AttrValue,
+    },
+    /// CTCP query, e.g. `/ctcp dan VERSION`
+    Ctcp {
+        target: AttrValue,
+        command: AttrValue,
+        params: Option<AttrValue>,
+    },
+    Join {
+        channels: Vec<AttrValue>,
+        keys: Vec<AttrValue>,
+    },
+    Part {
+        channel: AttrValue,
+        reason: Option<AttrValue>,
+    },
+    /// Open a query buffer, optionally sending a first message
+    Query {
+        nick: AttrValue,
+        text: Option<AttrValue>,
+    },
+    Nick {
+        nickname: AttrValue,
+    },
+    /// Show the topic, or set it if `topic` is given
+    Topic {
+        channel: AttrValue,
+        topic: Option<AttrValue>,
+    },
+    Mode {
+        target: AttrValue,
+        modestring: Option<AttrValue>,
+        arguments: Vec<AttrValue>,
+    },
+    Kick {
+        channel: AttrValue,
+        nick: AttrValue,
+        reason: Option<AttrValue>,
+    },
+    Ban {
+        channel: AttrValue,
+        mask: AttrValue,
+    },
+    Invite {
+        nick: AttrValue,
+        channel: AttrValue,
+    },
+    Whois {
+        nick: AttrValue,
+    },
+    /// Set away, or come back if `message` is `None`
+    Away {
+        message: Option<AttrValue>,
+    },
+    Quit {
+        reason: Option<AttrValue>,
+    },
+    /// Raw message, sent as is
+    Quote {
+        message: Message,
+    },
+    /// Close the buffer, leaving the channel if joined
+    Close,
+}
+
+/// Splits the arguments of a command
+struct Args<'a> {
+    command: AttrValue,
+    rest: &'a str,
+}
+
+impl<'a> Args<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start_matches(' ');
+        if rest.is_empty() {
+            return None;
+        }
+        let (arg, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        self.rest = rest;
+        Some(arg)
+    }
+
+    fn required(&mut self, argument: &'static str) -> Result<AttrValue, Error> {
+        self.next()
+            .map(|arg| arg.to_owned().into())
+            .ok_or_else(|| Error::MissingArgument {
+                command: self.command.clone(),
+                argument,
+            })
+    }
+
+    /// Next argument, if it satisfies the predicate
+    fn next_if(&mut self, predicate: impl Fn(&str) -> bool) -> Option<AttrValue> {
+        let rest = self.rest;
+        match self.next() {
+            Some(arg) if predicate(arg) => Some(arg.to_owned().into()),
+            _ => {
+                self.rest = rest;
+                None
+            }
+        }
+    }
+
+    /// All remaining text, `None` if empty
+    fn text(&mut self) -> Option<AttrValue> {
+        let text = self.rest.trim_start_matches(' ');
+        self.rest = "";
+        (!text.is_empty()).then(|| text.to_owned().into())
+    }
+
+    fn required_text(&mut self, argument: &'static str) -> Result<AttrValue, Error> {
+        self.text().ok_or_else(|| Error::MissingArgument {
+            command: self.command.clone(),
+            argument,
+        })
+    }
+}
+
+/// Parse the input of the buffer `buffer`, e.g. `/join #rust`. Text without
+/// a leading `/` is sent to the channel or user of the buffer.
+pub fn parse(
+    input: &str,
+    buffer: &BufferName,
+    server_info: &ServerInfo,
+) -> Result<UserCommand, Error> {
+    let input = input.trim_end_matches(['\r', '\n']);
+
+    // `//text` sends `/text`
+    let Some(command_line) = input.strip_prefix('/').filter(|s| !s.starts_with('/')) else {
+        let text = input.strip_prefix('/').unwrap_or(input);
+        return Ok(UserCommand::Message {
+            target: target(buffer)?,
+            text: text.to_owned().into(),
+        });
+    };
+
+    let (name, rest) = command_line.split_once(' ').unwrap_or((command_line, ""));
+    let name = name.to_ascii_lowercase();
+    let mut args = Args {
+        command: name.clone().into(),
+        rest,
+    };
+
+    let is_channel = |arg: &str| server_info.is_channel(arg);
+    // channel given as argument, or the one of the buffer
+    let channel = |args: &mut Args| {
+        args.next_if(is_channel)
+            .or_else(|| buffer.channel().map(|channel| channel.as_attr_value()))
+            .ok_or_else(|| Error::NotInChannel(args.command.clone()))
+    };
+
+    let command = match name.as_str() {
+        "join" | "j" => {
+            let channels = args.required("channel")?;
+            let mut keys = args.next().unwrap_or_default().split(',');
+            // empty channels are dropped with their keys, e.g. `#a,,#b`
+            let (channels, mut keys): (Vec<_>, Vec<_>) = channels
+                .split(',')
+                .map(|channel| (channel, keys.next().unwrap_or_default()))
+                .filter(|(channel, _)| !channel.is_empty())
+                .unzip();
+            while keys.last().is_some_and(|key| key.is_empty()) {
+                keys.pop();
+            }
+            if channels.is_empty() {
+                return Err(Error::MissingArgument {
+                    command: args.command,
+                    argument: "channel",
+                });
+            }
+            let values = |list: Vec<&str>| list.into_iter().map(|s| s.to_owned().into()).collect();
+            UserCommand::Join {
+                channels: values(channels),
+                keys: values(keys),
+            }
+        }
+        "part" | "leave" => UserCommand::Part {
+            channel: channel(&mut args)?,
+            reason: args.text(),
+        },
+        "msg" => UserCommand::Message {
+            target: args.required("target")?,
+            text: args.required_text("text")?,
+        },
+        "query" => UserCommand::Query {
+            nick: args.required("nick")?,
+            text: args.text(),
+        },
+        "me" => UserCommand::Action {
+            target: target(buffer)?,
+            text: args.required_text("text")?,
+        },
+        "notice" => UserCommand::Notice {
+            target: args.required("target")?,
+            text: args.required_text("text")?,
+        },
+        "ctcp" => UserCommand::Ctcp {
+            target: args.required("target")?,
+            command: args.required("command")?,
+            params: args.text(),
+        },
+        "nick" => UserCommand::Nick {
+            nickname: args.required("nick")?,
+        },
+        "topic" => UserCommand::Topic {
+            channel: channel(&mut args)?,
+            topic: args.text(),
+        },
+        "mode" => {
+            // the target defaults to the channel of the buffer
+            let target = match args.next_if(|arg| !arg.starts_with(['+', '-'])) {
+                Some(target) => target,
+                None => channel(&mut args)?,
+            };
+            UserCommand::Mode {
+                target,
+                modestring: args.next().map(|s| s.to_owned().into()),
+                arguments: std::iter::from_fn(|| args.next())
+                    .map(|s| s.to_owned().into())
+                    .collect(),
+            }
+        }
+        "kick" => UserCommand::Kick {
+            channel: channel(&mut args)?,
+            nick: args.required("nick")?,
+            reason: args.text(),
+        },
+        "ban" => {
+            let channel = channel(&mut args)?;
+            let mask = args.required("nick or mask")?;
+            UserCommand::Ban {
+                channel,
+                mask: match mask.contains(['!', '@']) {
+                    true => mask,
+                    false => format!("{mask}!*@*").into(),
+                },
+            }
+        }
+        "invite" => {
+            let nick = args.required("nick")?;
+            UserCommand::Invite {
+                nick,
+                channel: channel(&mut args)?,
+            }
+        }
+        "whois" => UserCommand::Whois {
+            nick: args.required("nick")?,
+        },
+        "away" => UserCommand::Away {
+            message: args.text(),
+        },
+        "quit" => UserCommand::Quit {
+            reason: args.text(),
+        },
+        "quote" | "raw" => UserCommand::Quote {
+            message: Message::parse(&args.required_text("message")?)?,
+        },
+        "close" => match buffer {
+            BufferName::Server(_) => return Err(Error::CannotClose),
+            _ => UserCommand::Close,
+        },
+        _ => return Err(Error::UnknownCommand(name.into())),
+    };
+    check_limits(&command, server_info)?;
+    Ok(command)
+}
+
+/// Check the lengths and targets of a command against the limits the server
+/// advertised. Too many channels to join are split into several `JOIN`s
+/// instead, see `Client`.
+pub fn check_limits(command: &UserCommand, server_info: &ServerInfo) -> Result<(), Error> {
+    let check = |text: Option<&AttrValue>, max: Option<usize>, argument| match (text, max) {
+        (Some(text), Some(max)) if text.len() > max => Err(Error::TooLong { argument, max }),
+        _ => Ok(()),
+    };
+    let targets = |target: &AttrValue, message: &str, command| match server_info
+        .max_targets(message)
+    {
+        Some(max) if target.split(',').count() > max => Err(Error::TooManyTargets { command, max }),
+        _ => Ok(()),
+    };
+
+    match command {
+        UserCommand::Join { channels, .. } => channels
+            .iter()
+            .try_for_each(|channel| check(Some(channel), server_info.channellen, "channel")),
+        UserCommand::Topic { topic, .. } => check(topic.as_ref(), server_info.topiclen, "topic"),
+        UserCommand::Kick { reason, .. } => check(reason.as_ref(), server_info.kicklen, "reason"),
+        UserCommand::Away { message } => check(message.as_ref(), server_info.awaylen, "message"),
+        UserCommand::Message { target, .. } => targets(target, "PRIVMSG", "msg"),
+        UserCommand::Notice { target, .. } => targets(target, "NOTICE", "notice"),
+        _ => Ok(()),
+    }
+}
+
+/// Channel or user of the buffer, which messages are sent to
+fn target(buffer: &BufferName) -> Result<AttrValue, Error> {
+    match buffer {
+        BufferName::Server(_) => Err(Error::NoTarget),
+        BufferName::Channel(channel) => Ok(channel.as_attr_value()),
+        BufferName::Query(nick) => Ok(nick.as_attr_value()),
+    }
+}
@@ -0,0 +1,340 @@
+use yew::AttrValue;
+
+use crate::irc::client::{
+    buffer::BufferName,
+    casemapping::{CaseMapping, ChannelName, Nickname},
+    server_info::ServerInfo,
+};
+
+use super::{parse, Error, UserCommand};
+
+fn channel() -> BufferName {
+    BufferName::Channel(ChannelName::new("#rust", CaseMapping::default()))
+}
+
+fn query() -> BufferName {
+    BufferName::Query(Nickname::new("alice", CaseMapping::default()))
+}
+
+fn server() -> BufferName {
+    BufferName::Server("irc.example.com".into())
+}
+
+fn run(input: &str, buffer: &BufferName) -> Result<UserCommand, Error> {
+    parse(input, buffer, &ServerInfo::default())
+}
+
+fn values(values: &[&str]) -> Vec<AttrValue> {
+    values.iter().map(|v| v.to_string().into()).collect()
+}
+
+#[test]
+fn test_text() {
+    assert_eq!(
+        run("hello world", &channel()),
+        Ok(UserCommand::Message {
+            target: "#rust".into(),
+            text: "hello world".into()
+        })
+    );
+    assert_eq!(
+        run("//slash", &query()),
+        Ok(UserCommand::Message {
+            target: "alice".into(),
+            text: "/slash".into()
+        })
+    );
+    assert_eq!(run("hello", &server()), Err(Error::NoTarget));
+}
+
+#[test]
+fn test_unknown_command() {
+    assert_eq!(
+        run("/frobnicate now", &channel()),
+        Err(Error::UnknownCommand("frobnicate".into()))
+    );
+}
+
+#[test]
+fn test_join_empty_channels() {
+    assert_eq!(
+        run("/join #a,,#b, k1,k2,k3", &server()),
+        Ok(UserCommand::Join {
+            channels: values(&["#a", "#b"]),
+            keys: values(&["k1", "k3"]),
+        })
+    );
+    assert_eq!(
+        run("/join ,#a,, ,k1", &server()),
+        Ok(UserCommand::Join {
+            channels: values(&["#a"]),
+            keys: values(&["k1"]),
+        })
+    );
+    assert_eq!(
+        run("/join #a,#b, ,k2", &server()),
+        Ok(UserCommand::Join {
+            channels: values(&["#a", "#b"]),
+            keys: values(&["", "k2"]),
+        })
+    );
+    assert_eq!(
+        run("/join ,", &server()),
+        Err(Error::MissingArgument {
+            command: "join".into(),
+            argument: "channel"
+        })
+    );
+}
+
+#[test]
+fn test_join_part() {
+    assert_eq!(
+        run("/JOIN #a,#b key", &server()),
+        Ok(UserCommand::Join {
+            channels: values(&["#a", "#b"]),
+            keys: values(&["key"]),
+        })
+    );
+    assert_eq!(
+        run("/join", &server()),
+        Err(Error::MissingArgument {
+            command: "join".into(),
+            argument: "channel"
+        })
+    );
+    assert_eq!(
+        run("/part see you", &channel()),
+        Ok(UserCommand::Part {
+            channel: "#rust".into(),
+            reason: Some("see you".into()),
+        })
+    );
+    assert_eq!(
+        run("/part #yew", &query()),
+        Ok(UserCommand::Part {
+            channel: "#yew".into(),
+            reason: None,
+        })
+    );
+    assert_eq!(
+        run("/part", &query()),
+        Err(Error::NotInChannel("part".into()))
+    );
+}
+
+#[test]
+fn test_messages() {
+    assert_eq!(
+        run("/msg bob hi  there", &server()),
+        Ok(UserCommand::Message {
+            target: "bob".into(),
+            text: "hi  there".into()
+        })
+    );
+    assert_eq!(
+        run("/msg bob", &server()),
+        Err(Error::MissingArgument {
+            command: "msg".into(),
+            argument: "text"
+        })
+    );
+    assert_eq!(
+        run("/me waves", &query()),
+        Ok(UserCommand::Action {
+            target: "alice".into(),
+            text: "waves".into()
+        })
+    );
+    assert_eq!(run("/me waves", &server()), Err(Error::NoTarget));
+    assert_eq!(
+        run("/notice #rust hi", &server()),
+        Ok(UserCommand::Notice {
+            target: "#rust".into(),
+            text: "hi".into()
+        })
+    );
+    assert_eq!(
+        run("/ctcp bob version", &server()),
+        Ok(UserCommand::Ctcp {
+            target: "bob".into(),
+            command: "version".into(),
+            params: None
+        })
+    );
+    assert_eq!(
+        run("/ctcp bob PING 1234", &server()),
+        Ok(UserCommand::Ctcp {
+            target: "bob".into(),
+            command: "PING".into(),
+            params: Some("1234".into())
+        })
+    );
+    assert_eq!(
+        run("/query bob", &server()),
+        Ok(UserCommand::Query {
+            nick: "bob".into(),
+            text: None
+        })
+    );
+}
+
+#[test]
+fn test_channel_commands() {
+    assert_eq!(
+        run("/topic", &channel()),
+        Ok(UserCommand::Topic {
+            channel: "#rust".into(),
+            topic: None
+        })
+    );
+    assert_eq!(
+        run("/topic #yew Yew things", &channel()),
+        Ok(UserCommand::Topic {
+            channel: "#yew".into(),
+            topic: Some("Yew things".into())
+        })
+    );
+    assert_eq!(
+        run("/mode +o dan", &channel()),
+        Ok(UserCommand::Mode {
+            target: "#rust".into(),
+            modestring: Some("+o".into()),
+            arguments: values(&["dan"]),
+        })
+    );
+    assert_eq!(
+        run("/mode dan +i", &server()),
+        Ok(UserCommand::Mode {
+            target: "dan".into(),
+            modestring: Some("+i".into()),
+            arguments: vec![],
+        })
+    );
+    assert_eq!(
+        run("/kick bob spamming links", &channel()),
+        Ok(UserCommand::Kick {
+            channel: "#rust".into(),
+            nick: "bob".into(),
+            reason: Some("spamming links".into()),
+        })
+    );
+    assert_eq!(
+        run("/ban bob", &channel()),
+        Ok(UserCommand::Ban {
+            channel: "#rust".into(),
+            mask: "bob!*@*".into(),
+        })
+    );
+    assert_eq!(
+        run("/ban #yew *!*@spam", &server()),
+        Ok(UserCommand::Ban {
+            channel: "#yew".into(),
+            mask: "*!*@spam".into(),
+        })
+    );
+    assert_eq!(
+        run("/invite bob", &channel()),
+        Ok(UserCommand::Invite {
+            nick: "bob".into(),
+            channel: "#rust".into(),
+        })
+    );
+    assert_eq!(
+        run("/kick bob", &server()),
+        Err(Error::NotInChannel("kick".into()))
+    );
+}
+
+#[test]
+fn test_other_commands() {
+    assert_eq!(
+        run("/nick dan2", &server()),
+        Ok(UserCommand::Nick {
+            nickname: "dan2".into()
+        })
+    );
+    assert_eq!(
+        run("/whois bob", &server()),
+        Ok(UserCommand::Whois { nick: "bob".into() })
+    );
+    assert_eq!(
+        run("/away", &server()),
+        Ok(UserCommand::Away { message: None })
+    );
+    assert_eq!(
+        run("/quit bye all", &server()),
+        Ok(UserCommand::Quit {
+            reason: Some("bye all".into())
+        })
+    );
+    assert_eq!(run("/close", &channel()), Ok(UserCommand::Close));
+    assert_eq!(run("/close", &server()), Err(Error::CannotClose));
+
+    let Ok(UserCommand::Quote { message }) = run("/quote WHO #rust %cuhnar", &server()) else {
+        panic!("quote");
+    };
+    assert_eq!(message.to_string(), "WHO #rust %cuhnar");
+    assert!(matches!(
+        run("/quote :", &server()),
+        Err(Error::InvalidMessage(_))
+    ));
+}
+
+#[test]
+fn test_limits() {
+    let mut info = ServerInfo::default();
+    info.apply(&values(&[
+        "TOPICLEN=5",
+        "KICKLEN=5",
+        "AWAYLEN=5",
+        "CHANNELLEN=6",
+        "TARGMAX=PRIVMSG:2,NOTICE:1",
+    ]));
+    let run = |input| parse(input, &channel(), &info);
+
+    assert_eq!(
+        run("/topic too long"),
+        Err(Error::TooLong {
+            argument: "topic",
+            max: 5
+        })
+    );
+    assert!(run("/topic short").is_ok());
+    assert_eq!(
+        run("/kick alice too long"),
+        Err(Error::TooLong {
+            argument: "reason",
+            max: 5
+        })
+    );
+    assert_eq!(
+        run("/away too long"),
+        Err(Error::TooLong {
+            argument: "message",
+            max: 5
+        })
+    );
+    assert_eq!(
+        run("/join #rust,#toolong"),
+        Err(Error::TooLong {
+            argument: "channel",
+            max: 6
+        })
+    );
+    assert!(run("/msg alice,bob hi").is_ok());
+    assert_eq!(
+        run("/msg alice,bob,carol hi"),
+        Err(Error::TooManyTargets {
+            command: "msg",
+            max: 2
+        })
+    );
+    assert_eq!(
+        run("/notice alice,bob hi"),
+        Err(Error::TooManyTargets {
+            command: "notice",
+            max: 1
+        })
+    );
+}
@@ -0,0 +1,161 @@
+use std::{collections::VecDeque, fmt::Display, time::Duration};
+
+use yew::AttrValue;
+
+use super::timestamp;
+
+#[cfg(test)]
+mod test;
+
+const DELIMITER: char = '\x01';
+
+/// Client-to-client protocol: queries wrapped in `\x01`, which are sent with
+/// `PRIVMSG` and answered with `NOTICE`, e.g. `\x01VERSION\x01`, following:
+///
+/// https://modern.ircdocs.horse/ctcp
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ctcp {
+    /// Upper case command, e.g. `ACTION`
+    pub command: AttrValue,
+    pub params: Option<AttrValue>,
+}
+
+impl Ctcp {
+    pub fn new(command: &str, params: Option<&str>) -> Self {
+        Ctcp {
+            command: command.to_ascii_uppercase().into(),
+            params: params
+                .filter(|params| !params.is_empty())
+                .map(|params| params.to_owned().into()),
+        }
+    }
+
+    /// CTCP message of a `PRIVMSG` or `NOTICE` text. The final delimiter is
+    /// optional, as some clients omit it.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.strip_prefix(DELIMITER)?;
+        let text = text.strip_suffix(DELIMITER).unwrap_or(text);
+        let (command, params) = match text.split_once(' ') {
+            Some((command, params)) => (command, Some(params)),
+            None => (text, None),
+        };
+        if command.is_empty() {
+            return None;
+        }
+        Some(Ctcp::new(command, params))
+    }
+}
+
+impl Display for Ctcp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.params {
+            Some(params) => write!(f, "{DELIMITER}{} {params}{DELIMITER}", self.command),
+            None => write!(f, "{DELIMITER}{}{DELIMITER}", self.command),
+        }
+    }
+}
+
+/// Automatic replies to CTCP queries. `PING` and `CLIENTINFO` are always
+/// answered, the other queries only if configured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replies {
+    pub version: Option<AttrValue>,
+    /// Where to get the client, e.g. a repository
+    pub source: Option<AttrValue>,
+    pub userinfo: Option<AttrValue>,
+    /// Answer `TIME` with the current time in UTC. The time passed to the
+    /// client is taken as time since the UNIX epoch.
+    pub time: bool,
+    /// At most `max_replies` are sent within `period`, further queries are
+    /// ignored
+    pub max_replies: usize,
+    pub period: Duration,
+}
+
+impl Default for Replies {
+    fn default() -> Self {
+        Replies {
+            version: Some(concat!("irc.rs ", env!("CARGO_PKG_VERSION")).into()),
+            source: Some("https://github.com/nilscc/irc.rs".into()),
+            userinfo: None,
+            time: true,
+            max_replies: 3,
+            period: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Replies {
+    /// Reply to a query, `None` if it is unknown or disabled
+    pub fn reply(&self, query: &Ctcp, now: Duration) -> Option<Ctcp> {
+        let params = match query.command.as_str() {
+            "PING" => query.params.clone(),
+            "CLIENTINFO" => Some(self.client_info().into()),
+            "VERSION" => Some(self.version.clone()?),
+            "SOURCE" => Some(self.source.clone()?),
+            "USERINFO" => Some(self.userinfo.clone()?),
+            "TIME" if self.time => Some(timestamp::to_iso(now).into()),
+            _ => return None,
+        };
+        Some(Ctcp {
+            command: query.command.clone(),
+            params,
+        })
+    }
+
+    /// Supported queries, e.g. `ACTION CLIENTINFO PING VERSION`
+    fn client_info(&self) -> String {
+        let optional = [
+            ("SOURCE", self.source.is_some()),
+            ("TIME", self.time),
+            ("USERINFO", self.userinfo.is_some()),
+            ("VERSION", self.version.is_some()),
+        ];
+        ["ACTION", "CLIENTINFO", "PING"]
+            .into_iter()
+            .chain(
+                optional
+                    .into_iter()
+                    .filter(|(_, enabled)| *enabled)
+                    .map(|(command, _)| command),
+            )
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Limits the number of replies, so queries can not be used to flood us off
+/// the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    max: usize,
+    period: Duration,
+    /// Times of the replies within the last period
+    sent: VecDeque<Duration>,
+}
+
+impl RateLimit {
+    pub fn new(max: usize, period: Duration) -> Self {
+        RateLimit {
+            max,
+            period,
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Check if another reply may be sent now, and count it if so
+    pub fn allow(&mut self, now: Duration) -> bool {
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.saturating_sub(*sent) >= self.period)
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.max {
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use super::{Ctcp, RateLimit, Replies};
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        Ctcp::parse("\x01ACTION waves\x01"),
+        Some(Ctcp::new("ACTION", Some("waves")))
+    );
+    // the final delimiter is optional
+    assert_eq!(
+        Ctcp::parse("\x01version"),
+        Some(Ctcp {
+            command: "VERSION".into(),
+            params: None
+        })
+    );
+    assert_eq!(Ctcp::parse("hello"), None);
+    assert_eq!(Ctcp::parse("\x01\x01"), None);
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(
+        Ctcp::new("ACTION", Some("waves")).to_string(),
+        "\x01ACTION waves\x01"
+    );
+    assert_eq!(Ctcp::new("version", None).to_string(), "\x01VERSION\x01");
+}
+
+#[test]
+fn test_replies() {
+    let replies = Replies {
+        version: Some("irc.rs".into()),
+        source: None,
+        userinfo: Some("dan".into()),
+        ..Replies::default()
+    };
+    let reply = |query: &str| {
+        replies
+            .reply(
+                &Ctcp::parse(query).unwrap(),
+                Duration::from_secs(1735065000),
+            )
+            .map(|reply| reply.to_string())
+    };
+
+    assert_eq!(
+        reply("\x01VERSION\x01"),
+        Some("\x01VERSION irc.rs\x01".into())
+    );
+    assert_eq!(reply("\x01PING 1234\x01"), Some("\x01PING 1234\x01".into()));
+    assert_eq!(
+        reply("\x01USERINFO\x01"),
+        Some("\x01USERINFO dan\x01".into())
+    );
+    assert_eq!(
+        reply("\x01TIME\x01"),
+        Some("\x01TIME 2024-12-24T18:30:00Z\x01".into())
+    );
+    assert_eq!(
+        reply("\x01CLIENTINFO\x01"),
+        Some("\x01CLIENTINFO ACTION CLIENTINFO PING TIME USERINFO VERSION\x01".into())
+    );
+
+    // disabled and unknown queries, and actions are not answered
+    assert_eq!(reply("\x01SOURCE\x01"), None);
+    assert_eq!(reply("\x01FINGER\x01"), None);
+    assert_eq!(reply("\x01ACTION waves\x01"), None);
+}
+
+#[test]
+fn test_rate_limit() {
+    let mut limit = RateLimit::new(2, Duration::from_secs(10));
+    assert!(limit.allow(Duration::from_secs(1)));
+    assert!(limit.allow(Duration::from_secs(2)));
+    assert!(!limit.allow(Duration::from_secs(3)));
+    // the first reply is older than the period
+    assert!(limit.allow(Duration::from_secs(11)));
+    assert!(!limit.allow(Duration::from_secs(11)));
+}
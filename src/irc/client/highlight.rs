@@ -0,0 +1,85 @@
+use regex_lite::Regex;
+use yew::AttrValue;
+
+use crate::irc::formatting::strip_formatting;
+
+use super::casemapping::Nickname;
+
+#[cfg(test)]
+mod test;
+
+/// Highlight in addition to our nick name
+#[derive(Debug, Clone, PartialEq)]
+pub enum Highlight {
+    /// Whole word, compared with the casemapping of the server
+    Word(AttrValue),
+    /// Regular expression, e.g. `(?i)\brust(acean)?s?\b`
+    Pattern(AttrValue),
+}
+
+/// Detection of messages which mention us, by our nick name or by words and
+/// patterns configured by the user
+#[derive(Debug, Clone)]
+pub struct Highlighter {
+    words: Vec<AttrValue>,
+    patterns: Vec<Regex>,
+}
+
+impl PartialEq for Highlighter {
+    fn eq(&self, other: &Self) -> bool {
+        self.words == other.words
+            && self
+                .patterns
+                .iter()
+                .map(Regex::as_str)
+                .eq(other.patterns.iter().map(Regex::as_str))
+    }
+}
+
+impl Highlighter {
+    /// Invalid patterns are ignored
+    pub fn new(highlights: &[Highlight]) -> Self {
+        let mut words = vec![];
+        let mut patterns = vec![];
+        for highlight in highlights {
+            match highlight {
+                Highlight::Word(word) => words.push(word.clone()),
+                Highlight::Pattern(pattern) => patterns.extend(Regex::new(pattern).ok()),
+            }
+        }
+        Highlighter { words, patterns }
+    }
+
+    /// Check if a message mentions `nick` or any of the highlights.
+    /// Formatting codes are ignored.
+    pub fn matches(&self, text: &str, nick: &Nickname) -> bool {
+        let text = strip_formatting(text);
+        let casemapping = nick.casemapping();
+        let folded = casemapping.casefold(&text);
+
+        contains_word(&folded, nick.folded())
+            || self
+                .words
+                .iter()
+                .any(|word| contains_word(&folded, &casemapping.casefold(word)))
+            || self.patterns.iter().any(|pattern| pattern.is_match(&text))
+    }
+}
+
+/// Check if `word` occurs in `text`, but not as part of a longer word
+fn contains_word(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+/// Characters of words and nick names. Brackets are not included, so
+/// `[dan]` mentions `dan`.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-`^|\\".contains(c)
+}
@@ -0,0 +1,44 @@
+use crate::irc::client::casemapping::{CaseMapping, Nickname};
+
+use super::{Highlight, Highlighter};
+
+fn dan() -> Nickname {
+    Nickname::new("dan", CaseMapping::default())
+}
+
+#[test]
+fn test_nick() {
+    let highlighter = Highlighter::new(&[]);
+    assert!(highlighter.matches("dan: hi", &dan()));
+    assert!(highlighter.matches("hi DAN!", &dan()));
+    assert!(highlighter.matches("[dan] look", &dan()));
+    assert!(highlighter.matches("\x02dan\x02, hi", &dan()));
+
+    // only whole words
+    assert!(!highlighter.matches("dance", &dan()));
+    assert!(!highlighter.matches("jordan", &dan()));
+    assert!(!highlighter.matches("dan_ is away", &dan()));
+}
+
+#[test]
+fn test_nick_casemapping() {
+    let highlighter = Highlighter::new(&[]);
+    let nick = Nickname::new("dan[m]", CaseMapping::Rfc1459);
+    assert!(highlighter.matches("hi dan{M}", &nick));
+    let nick = Nickname::new("dan[m]", CaseMapping::Ascii);
+    assert!(!highlighter.matches("hi dan{m}", &nick));
+}
+
+#[test]
+fn test_words_and_patterns() {
+    let highlighter = Highlighter::new(&[
+        Highlight::Word("Rust".into()),
+        Highlight::Pattern(r"(?i)\byew(-\w+)?\b".into()),
+        // invalid patterns are ignored
+        Highlight::Pattern("(".into()),
+    ]);
+    assert!(highlighter.matches("I like rust", &dan()));
+    assert!(!highlighter.matches("trusted", &dan()));
+    assert!(highlighter.matches("see yew-router", &dan()));
+    assert!(!highlighter.matches("nothing here", &dan()));
+}
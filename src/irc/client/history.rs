@@ -0,0 +1,101 @@
+use std::{fmt::Display, time::Duration};
+
+use yew::AttrValue;
+
+use crate::irc::parser::{generic_message::GenericMessage, message::Message};
+
+use super::{buffer::Line, timestamp};
+
+#[cfg(test)]
+mod test;
+
+/// Number of messages requested at once, unless the server allows less
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Message or time which bounds a request
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    MsgId(AttrValue),
+    Timestamp(Duration),
+}
+
+impl Reference {
+    /// Reference of a line, its `msgid` if it is known
+    pub fn line(line: &Line) -> Self {
+        match &line.msgid {
+            Some(msgid) => Reference::MsgId(msgid.clone()),
+            None => Reference::Timestamp(line.time),
+        }
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reference::MsgId(msgid) => write!(f, "msgid={msgid}"),
+            Reference::Timestamp(time) => {
+                write!(f, "timestamp={}", timestamp::to_iso_millis(*time))
+            }
+        }
+    }
+}
+
+/// Scrollback requested from the server with `draft/chathistory`, e.g.
+/// `CHATHISTORY BEFORE #rust msgid=abc 50`, following the spec:
+///
+/// https://ircv3.net/specs/extensions/chathistory
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Most recent messages, e.g. after joining
+    Latest,
+    /// Messages before the first line of a buffer, when scrolling up
+    Before(Reference),
+    /// Messages missed while we were disconnected
+    Between(Reference, Reference),
+}
+
+impl Request {
+    pub fn message(&self, target: &str, limit: usize) -> Message {
+        let msg = GenericMessage::cmd("CHATHISTORY");
+        let msg = match self {
+            Request::Latest => msg.param("LATEST").param(target).param("*"),
+            Request::Before(reference) => msg
+                .param("BEFORE")
+                .param(target)
+                .param(&reference.to_string()),
+            Request::Between(start, end) => msg
+                .param("BETWEEN")
+                .param(target)
+                .param(&start.to_string())
+                .param(&end.to_string()),
+        };
+        Message::generic(msg.param(&limit.to_string())).build()
+    }
+
+    /// Fewer messages than requested mean there are no older ones
+    pub fn completes(&self, count: usize, limit: usize) -> bool {
+        match self {
+            Request::Latest | Request::Before(_) => count < limit,
+            Request::Between(..) => false,
+        }
+    }
+}
+
+/// Request the targets with messages between two times, to discover queries
+pub fn targets(start: Duration, end: Duration, limit: usize) -> Message {
+    let msg = GenericMessage::cmd("CHATHISTORY")
+        .param("TARGETS")
+        .param(&Reference::Timestamp(start).to_string())
+        .param(&Reference::Timestamp(end).to_string())
+        .param(&limit.to_string());
+    Message::generic(msg).build()
+}
+
+/// History of a buffer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    /// Request waiting for its reply, only one is sent at a time
+    pub pending: Option<Request>,
+    /// There are no older messages on the server
+    pub complete: bool,
+}
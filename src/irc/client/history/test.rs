@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use super::{targets, Reference, Request};
+
+#[test]
+fn test_request_messages() {
+    assert_eq!(
+        Request::Latest.message("#rust", 50).to_string(),
+        "CHATHISTORY LATEST #rust * 50"
+    );
+    assert_eq!(
+        Request::Before(Reference::MsgId("abc".into()))
+            .message("alice", 20)
+            .to_string(),
+        "CHATHISTORY BEFORE alice msgid=abc 20"
+    );
+    assert_eq!(
+        Request::Between(
+            Reference::Timestamp(Duration::from_millis(1319042451620)),
+            Reference::Timestamp(Duration::from_secs(1319042460)),
+        )
+        .message("#rust", 50)
+        .to_string(),
+        "CHATHISTORY BETWEEN #rust timestamp=2011-10-19T16:40:51.620Z timestamp=2011-10-19T16:41:00.000Z 50"
+    );
+}
+
+#[test]
+fn test_targets() {
+    assert_eq!(
+        targets(Duration::ZERO, Duration::from_secs(60), 50).to_string(),
+        "CHATHISTORY TARGETS timestamp=1970-01-01T00:00:00.000Z timestamp=1970-01-01T00:01:00.000Z 50"
+    );
+}
+
+#[test]
+fn test_completes() {
+    assert!(Request::Latest.completes(10, 50));
+    assert!(!Request::Latest.completes(50, 50));
+    assert!(Request::Before(Reference::MsgId("abc".into())).completes(0, 50));
+    let between = Request::Between(
+        Reference::Timestamp(Duration::ZERO),
+        Reference::Timestamp(Duration::ZERO),
+    );
+    assert!(!between.completes(0, 50));
+}
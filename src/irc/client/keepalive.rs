@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use yew::AttrValue;
+
+use crate::irc::parser::{message::Message, typed_message::TypedMessage};
+
+#[cfg(test)]
+mod test;
+
+/// Connection is considered dead, no `PONG` was received in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeout {
+    pub since: Duration,
+}
+
+/// Client initiated `PING`s to detect dead connections and measure lag.
+///
+/// All times are durations since an arbitrary but fixed point in time, e.g.
+/// `performance.now()` in the browser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keepalive {
+    /// Idle time after which a `PING` is sent
+    pub interval: Duration,
+    /// Time to wait for the `PONG` before the connection is considered dead
+    pub timeout: Duration,
+    /// `None` until the first tick of a connection
+    last_activity: Option<Duration>,
+    /// Token and time of the `PING` which waits for its `PONG`
+    pending: Option<(AttrValue, Duration)>,
+    lag: Option<Duration>,
+}
+
+impl Keepalive {
+    pub fn new(interval: Duration, timeout: Duration) -> Self {
+        Keepalive {
+            interval,
+            timeout,
+            last_activity: None,
+            pending: None,
+            lag: None,
+        }
+    }
+
+    /// Start over, e.g. for a new connection
+    pub fn reset(&mut self, now: Duration) {
+        self.last_activity = Some(now);
+        self.pending = None;
+        self.lag = None;
+    }
+
+    /// Start over once the next tick tells the time, e.g. when registration
+    /// starts on a new connection
+    pub fn restart(&mut self) {
+        self.last_activity = None;
+        self.pending = None;
+        self.lag = None;
+    }
+
+    /// Round trip time of the last `PING`
+    pub fn lag(&self) -> Option<Duration> {
+        self.lag
+    }
+
+    /// Any message received from the server shows the connection is alive
+    pub fn received(&mut self, now: Duration) {
+        self.last_activity = Some(now);
+    }
+
+    /// Answer to one of our `PING`s
+    pub fn pong(&mut self, token: &str, now: Duration) {
+        match &self.pending {
+            Some((pending, sent_at)) if pending == token => {
+                self.lag = Some(now.saturating_sub(*sent_at));
+                self.pending = None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Check the connection, should be called regularly, e.g. every second.
+    /// Returns a `PING` once the connection has been idle for `interval`.
+    pub fn tick(&mut self, now: Duration) -> Result<Option<Message>, Timeout> {
+        if let Some((_, sent_at)) = &self.pending {
+            if now.saturating_sub(*sent_at) >= self.timeout {
+                return Err(Timeout { since: *sent_at });
+            }
+            return Ok(None);
+        }
+
+        let last_activity = *self.last_activity.get_or_insert(now);
+        if now.saturating_sub(last_activity) < self.interval {
+            return Ok(None);
+        }
+
+        let token: AttrValue = format!("irc.rs-{}", now.as_millis()).into();
+        self.pending = Some((token.clone(), now));
+        Ok(Some(Message::typed(TypedMessage::Ping { token }).build()))
+    }
+}
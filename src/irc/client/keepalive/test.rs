@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use super::{Keepalive, Timeout};
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn keepalive() -> Keepalive {
+    let mut keepalive = Keepalive::new(secs(60), secs(30));
+    keepalive.reset(secs(100));
+    keepalive
+}
+
+#[test]
+fn test_idle_ping() {
+    let mut keepalive = keepalive();
+    assert_eq!(keepalive.tick(secs(159)), Ok(None));
+
+    let ping = keepalive.tick(secs(160)).unwrap().unwrap();
+    assert_eq!(ping.to_string(), "PING irc.rs-160000");
+
+    // only one ping at a time
+    assert_eq!(keepalive.tick(secs(170)), Ok(None));
+}
+
+#[test]
+fn test_restart() {
+    let mut keepalive = keepalive();
+    keepalive.restart();
+    // the idle time counts from the first tick
+    assert_eq!(keepalive.tick(secs(500)), Ok(None));
+    assert_eq!(keepalive.tick(secs(559)), Ok(None));
+    assert!(keepalive.tick(secs(560)).unwrap().is_some());
+}
+
+#[test]
+fn test_activity_delays_ping() {
+    let mut keepalive = keepalive();
+    keepalive.received(secs(150));
+    assert_eq!(keepalive.tick(secs(200)), Ok(None));
+    assert!(keepalive.tick(secs(210)).unwrap().is_some());
+}
+
+#[test]
+fn test_lag() {
+    let mut keepalive = keepalive();
+    assert_eq!(keepalive.lag(), None);
+
+    keepalive.tick(secs(160)).unwrap();
+    keepalive.pong("unknown", secs(161));
+    assert_eq!(keepalive.lag(), None);
+
+    keepalive.pong("irc.rs-160000", Duration::from_millis(160_250));
+    assert_eq!(keepalive.lag(), Some(Duration::from_millis(250)));
+
+    // next ping after the interval
+    keepalive.received(Duration::from_millis(160_250));
+    assert_eq!(keepalive.tick(secs(200)), Ok(None));
+    assert!(keepalive.tick(secs(221)).unwrap().is_some());
+}
+
+#[test]
+fn test_timeout() {
+    let mut keepalive = keepalive();
+    keepalive.tick(secs(160)).unwrap();
+
+    // other messages do not answer the ping
+    keepalive.received(secs(170));
+    assert_eq!(keepalive.tick(secs(189)), Ok(None));
+    assert_eq!(keepalive.tick(secs(190)), Err(Timeout { since: secs(160) }));
+}
+
+#[test]
+fn test_reset() {
+    let mut keepalive = keepalive();
+    keepalive.tick(secs(160)).unwrap();
+    keepalive.reset(secs(500));
+    assert_eq!(keepalive.tick(secs(530)), Ok(None));
+}
@@ -0,0 +1,206 @@
+use yew::AttrValue;
+
+use crate::irc::parser::{message::Message, typed_message::TypedMessage};
+
+use super::server_info::{ChanModeType, ServerInfo};
+
+#[cfg(test)]
+mod test;
+
+/// Limit of changes with parameters per `MODE`, if the server does not
+/// advertise `MODES`
+const DEFAULT_MODES: usize = 3;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Mode requires a parameter, but none is left
+    MissingParameter(char),
+    /// Mode string does not start with `+` or `-`
+    MissingSign(AttrValue),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModeChange {
+    /// Membership mode of a user, e.g. `+o dan`
+    Member {
+        set: bool,
+        mode: char,
+        nick: AttrValue,
+    },
+    /// Entry of a list mode, e.g. `+b *!*@spam`
+    List {
+        set: bool,
+        mode: char,
+        mask: AttrValue,
+    },
+    /// Setting with a parameter, e.g. `+k key` or `+l 10`. The parameter is
+    /// missing for `-l`, which takes no parameter when unset.
+    Parameter {
+        set: bool,
+        mode: char,
+        value: Option<AttrValue>,
+    },
+    /// Flag without parameter, e.g. `+n`. Unknown modes are assumed to be
+    /// flags.
+    Flag { set: bool, mode: char },
+}
+
+impl ModeChange {
+    pub fn set(&self) -> bool {
+        match self {
+            ModeChange::Member { set, .. }
+            | ModeChange::List { set, .. }
+            | ModeChange::Parameter { set, .. }
+            | ModeChange::Flag { set, .. } => *set,
+        }
+    }
+
+    pub fn mode(&self) -> char {
+        match self {
+            ModeChange::Member { mode, .. }
+            | ModeChange::List { mode, .. }
+            | ModeChange::Parameter { mode, .. }
+            | ModeChange::Flag { mode, .. } => *mode,
+        }
+    }
+
+    pub fn parameter(&self) -> Option<&AttrValue> {
+        match self {
+            ModeChange::Member { nick, .. } => Some(nick),
+            ModeChange::List { mask, .. } => Some(mask),
+            ModeChange::Parameter { value, .. } => value.as_ref(),
+            ModeChange::Flag { .. } => None,
+        }
+    }
+}
+
+/// Parse a mode string with its parameters into single changes, e.g.
+/// `+ovk-b dan bob key *!*@spam`. Which modes take a parameter is advertised
+/// by the server with `CHANMODES` and `PREFIX`.
+pub fn parse(
+    modestring: &str,
+    parameters: &[AttrValue],
+    server_info: &ServerInfo,
+) -> Result<Vec<ModeChange>, Error> {
+    if !modestring.starts_with(['+', '-']) {
+        return Err(Error::MissingSign(modestring.to_owned().into()));
+    }
+
+    let mut parameters = parameters.iter().cloned();
+    let mut changes = vec![];
+    let mut set = true;
+
+    for mode in modestring.chars() {
+        let mut parameter = || parameters.next().ok_or(Error::MissingParameter(mode));
+        let change = match (mode, server_info.chanmode_type(mode)) {
+            ('+', _) => {
+                set = true;
+                continue;
+            }
+            ('-', _) => {
+                set = false;
+                continue;
+            }
+            (_, Some(ChanModeType::Prefix)) => ModeChange::Member {
+                set,
+                mode,
+                nick: parameter()?,
+            },
+            (_, Some(ChanModeType::List)) => ModeChange::List {
+                set,
+                mode,
+                mask: parameter()?,
+            },
+            (_, Some(ChanModeType::AlwaysParameter)) => ModeChange::Parameter {
+                set,
+                mode,
+                value: Some(parameter()?),
+            },
+            (_, Some(ChanModeType::SetParameter)) => ModeChange::Parameter {
+                set,
+                mode,
+                value: if set { Some(parameter()?) } else { None },
+            },
+            (_, Some(ChanModeType::NoParameter) | None) => ModeChange::Flag { set, mode },
+        };
+        changes.push(change);
+    }
+
+    Ok(changes)
+}
+
+/// Builds `MODE` messages for a list of changes. Changes are split into
+/// several messages, so each carries at most as many parameters as `MODES`
+/// allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeBuilder {
+    target: AttrValue,
+    changes: Vec<ModeChange>,
+}
+
+impl ModeBuilder {
+    pub fn new(target: &str) -> Self {
+        ModeBuilder {
+            target: target.to_owned().into(),
+            changes: vec![],
+        }
+    }
+
+    pub fn change(mut self, change: ModeChange) -> Self {
+        self.changes.push(change);
+        self
+    }
+
+    pub fn build(&self, server_info: &ServerInfo) -> Vec<Message> {
+        // `MODES` without a value means there is no limit
+        let limit = match server_info.tokens.contains_key("MODES") {
+            true => server_info.modes.unwrap_or(usize::MAX),
+            false => DEFAULT_MODES,
+        }
+        .max(1);
+
+        let mut messages = vec![];
+        let mut batch: Vec<&ModeChange> = vec![];
+        let mut count = 0;
+
+        for change in &self.changes {
+            let has_parameter = change.parameter().is_some();
+            if has_parameter && count == limit {
+                messages.push(self.message(&batch));
+                batch.clear();
+                count = 0;
+            }
+            if has_parameter {
+                count += 1;
+            }
+            batch.push(change);
+        }
+
+        if !batch.is_empty() {
+            messages.push(self.message(&batch));
+        }
+        messages
+    }
+
+    fn message(&self, changes: &[&ModeChange]) -> Message {
+        let mut modestring = String::new();
+        let mut sign = None;
+        for change in changes {
+            if sign != Some(change.set()) {
+                sign = Some(change.set());
+                modestring.push(if change.set() { '+' } else { '-' });
+            }
+            modestring.push(change.mode());
+        }
+
+        Message::typed(TypedMessage::Mode {
+            target: self.target.clone(),
+            modestring: Some(modestring.into()),
+            arguments: changes
+                .iter()
+                .filter_map(|change| change.parameter().cloned())
+                .collect(),
+        })
+        .build()
+    }
+}
@@ -0,0 +1,181 @@
+use yew::AttrValue;
+
+use crate::irc::client::server_info::ServerInfo;
+
+use super::{parse, Error, ModeBuilder, ModeChange};
+
+fn values(values: &[&str]) -> Vec<AttrValue> {
+    values.iter().map(|v| v.to_string().into()).collect()
+}
+
+fn info(tokens: &[&str]) -> ServerInfo {
+    let mut info = ServerInfo::default();
+    info.apply(&values(tokens));
+    info
+}
+
+#[test]
+fn test_parse() {
+    let info = info(&["CHANMODES=beI,k,l,imnpst", "PREFIX=(ov)@+"]);
+    assert_eq!(
+        parse(
+            "+ovk-b+l",
+            &values(&["nick1", "nick2", "key", "mask", "10"]),
+            &info
+        ),
+        Ok(vec![
+            ModeChange::Member {
+                set: true,
+                mode: 'o',
+                nick: "nick1".into()
+            },
+            ModeChange::Member {
+                set: true,
+                mode: 'v',
+                nick: "nick2".into()
+            },
+            ModeChange::Parameter {
+                set: true,
+                mode: 'k',
+                value: Some("key".into())
+            },
+            ModeChange::List {
+                set: false,
+                mode: 'b',
+                mask: "mask".into()
+            },
+            ModeChange::Parameter {
+                set: true,
+                mode: 'l',
+                value: Some("10".into())
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_parse_unset() {
+    let info = ServerInfo::default();
+    assert_eq!(
+        parse("-lkn+z", &values(&["key"]), &info),
+        Ok(vec![
+            ModeChange::Parameter {
+                set: false,
+                mode: 'l',
+                value: None
+            },
+            ModeChange::Parameter {
+                set: false,
+                mode: 'k',
+                value: Some("key".into())
+            },
+            ModeChange::Flag {
+                set: false,
+                mode: 'n'
+            },
+            // unknown modes are flags
+            ModeChange::Flag {
+                set: true,
+                mode: 'z'
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let info = ServerInfo::default();
+    assert_eq!(
+        parse("+ov", &values(&["dan"]), &info),
+        Err(Error::MissingParameter('v'))
+    );
+    assert_eq!(
+        parse("o", &values(&["dan"]), &info),
+        Err(Error::MissingSign("o".into()))
+    );
+}
+
+#[test]
+fn test_parse_uses_isupport() {
+    // `q` is a membership mode on this server, `f` takes a parameter
+    let info = info(&["CHANMODES=b,f,l,nt", "PREFIX=(qov)~@+"]);
+    assert_eq!(
+        parse("+qf", &values(&["dan", "[5:5]"]), &info),
+        Ok(vec![
+            ModeChange::Member {
+                set: true,
+                mode: 'q',
+                nick: "dan".into()
+            },
+            ModeChange::Parameter {
+                set: true,
+                mode: 'f',
+                value: Some("[5:5]".into())
+            },
+        ])
+    );
+}
+
+fn build(builder: &ModeBuilder, info: &ServerInfo) -> Vec<String> {
+    builder
+        .build(info)
+        .iter()
+        .map(|message| message.to_string())
+        .collect()
+}
+
+fn op(nick: &str) -> ModeChange {
+    ModeChange::Member {
+        set: true,
+        mode: 'o',
+        nick: nick.to_owned().into(),
+    }
+}
+
+#[test]
+fn test_build() {
+    let builder = ModeBuilder::new("#rust")
+        .change(op("alice"))
+        .change(ModeChange::Flag {
+            set: true,
+            mode: 'n',
+        })
+        .change(ModeChange::List {
+            set: false,
+            mode: 'b',
+            mask: "*!*@spam".into(),
+        })
+        .change(ModeChange::Parameter {
+            set: false,
+            mode: 'l',
+            value: None,
+        });
+    assert_eq!(
+        build(&builder, &ServerInfo::default()),
+        vec!["MODE #rust +on-bl alice *!*@spam"]
+    );
+}
+
+#[test]
+fn test_build_respects_modes() {
+    let builder = ["a", "b", "c", "d", "e"]
+        .into_iter()
+        .fold(ModeBuilder::new("#rust"), |builder, nick| {
+            builder.change(op(nick))
+        });
+
+    // three parameters per message, if the server does not say otherwise
+    assert_eq!(
+        build(&builder, &ServerInfo::default()),
+        vec!["MODE #rust +ooo a b c", "MODE #rust +oo d e"]
+    );
+    assert_eq!(
+        build(&builder, &info(&["MODES=4"])),
+        vec!["MODE #rust +oooo a b c d", "MODE #rust +o e"]
+    );
+    // no limit
+    assert_eq!(
+        build(&builder, &info(&["MODES"])),
+        vec!["MODE #rust +ooooo a b c d e"]
+    );
+}
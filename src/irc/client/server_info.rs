@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use yew::AttrValue;
+
+use super::casemapping::{CaseMapping, ChannelName, Nickname};
+
+#[cfg(test)]
+mod test;
+
+/// Type of a channel mode, which determines whether it takes a parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChanModeType {
+    /// Type A: list modes like bans, always take a parameter
+    List,
+    /// Type B: always take a parameter, e.g. the channel key
+    AlwaysParameter,
+    /// Type C: take a parameter only when set, e.g. the user limit
+    SetParameter,
+    /// Type D: flags without parameter
+    NoParameter,
+    /// Membership modes of the `PREFIX` token, e.g. `o` for operators
+    Prefix,
+}
+
+/// Channel modes grouped by type, as listed in `CHANMODES`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChanModes {
+    pub list: Vec<char>,
+    pub always_parameter: Vec<char>,
+    pub set_parameter: Vec<char>,
+    pub no_parameter: Vec<char>,
+}
+
+impl Default for ChanModes {
+    fn default() -> Self {
+        ChanModes::parse("beI,k,l,imnpst")
+    }
+}
+
+impl ChanModes {
+    fn parse(value: &str) -> Self {
+        let mut groups = value.split(',').map(|group| group.chars().collect());
+        ChanModes {
+            list: groups.next().unwrap_or_default(),
+            always_parameter: groups.next().unwrap_or_default(),
+            set_parameter: groups.next().unwrap_or_default(),
+            no_parameter: groups.next().unwrap_or_default(),
+        }
+    }
+}
+
+/// Server parameters advertised with `RPL_ISUPPORT`, following:
+///
+/// https://modern.ircdocs.horse/#rplisupport-parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerInfo {
+    pub casemapping: CaseMapping,
+    /// Channel prefixes, e.g. `#` and `&`
+    pub chantypes: Vec<char>,
+    /// Membership modes with their prefixes, highest rank first,
+    /// e.g. `('o', '@')`
+    pub prefix: Vec<(char, char)>,
+    pub chanmodes: ChanModes,
+    pub nicklen: Option<usize>,
+    pub channellen: Option<usize>,
+    pub topiclen: Option<usize>,
+    pub kicklen: Option<usize>,
+    pub awaylen: Option<usize>,
+    /// Maximum number of mode changes with parameters in a single `MODE`
+    pub modes: Option<usize>,
+    /// Maximum number of targets per command, `None` if unlimited
+    pub targmax: BTreeMap<AttrValue, Option<usize>>,
+    pub network: Option<AttrValue>,
+    /// `MONITOR` is supported, with an optional limit of targets
+    pub monitor: Option<Option<usize>>,
+    pub whox: bool,
+    pub utf8only: bool,
+    /// User mode which marks bots
+    pub bot: Option<char>,
+    /// Prefixes to message only members with a certain status, e.g. `@#rust`
+    pub statusmsg: Vec<char>,
+    /// `CHATHISTORY` is supported, with the maximum number of messages per
+    /// request, `0` if unlimited
+    pub chathistory: Option<usize>,
+    /// All tokens as advertised by the server, with unescaped values
+    pub tokens: BTreeMap<AttrValue, Option<AttrValue>>,
+}
+
+impl Default for ServerInfo {
+    fn default() -> Self {
+        ServerInfo {
+            casemapping: CaseMapping::Rfc1459,
+            chantypes: vec!['#', '&'],
+            prefix: vec![('o', '@'), ('v', '+')],
+            chanmodes: ChanModes::default(),
+            nicklen: None,
+            channellen: None,
+            topiclen: None,
+            kicklen: None,
+            awaylen: None,
+            modes: None,
+            targmax: BTreeMap::new(),
+            network: None,
+            monitor: None,
+            whox: false,
+            utf8only: false,
+            bot: None,
+            statusmsg: vec![],
+            chathistory: None,
+            tokens: BTreeMap::new(),
+        }
+    }
+}
+
+/// Unescape `\xHH` sequences of token values
+fn unescape(value: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .strip_prefix(b"x")
+            .filter(|_| byte == b'\\')
+            .and_then(|hex| hex.get(..2))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &tail[3..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+impl ServerInfo {
+    /// Apply tokens of a `RPL_ISUPPORT` reply. `-TOKEN` removes a previously
+    /// advertised token, which restores its default.
+    pub fn apply(&mut self, tokens: &[AttrValue]) {
+        for token in tokens {
+            if let Some(key) = token.strip_prefix('-') {
+                self.tokens.remove(key);
+                continue;
+            }
+            let (key, value) = match token.split_once('=') {
+                Some((key, value)) => (key, Some(unescape(value).into())),
+                None => (token.as_str(), None),
+            };
+            self.tokens.insert(key.to_owned().into(), value);
+        }
+
+        let tokens = std::mem::take(&mut self.tokens);
+        *self = Self::default();
+        for (key, value) in &tokens {
+            self.apply_token(key, value.as_deref().unwrap_or_default());
+        }
+        self.tokens = tokens;
+    }
+
+    fn apply_token(&mut self, key: &str, value: &str) {
+        let number = || value.parse::<usize>().ok();
+        match key {
+            "CASEMAPPING" => self.casemapping = CaseMapping::parse(value),
+            "CHANTYPES" => self.chantypes = value.chars().collect(),
+            "PREFIX" => self.prefix = Self::parse_prefix(value),
+            "CHANMODES" => self.chanmodes = ChanModes::parse(value),
+            "NICKLEN" => self.nicklen = number(),
+            "CHANNELLEN" => self.channellen = number(),
+            "TOPICLEN" => self.topiclen = number(),
+            "KICKLEN" => self.kicklen = number(),
+            "AWAYLEN" => self.awaylen = number(),
+            "MODES" => self.modes = number(),
+            "TARGMAX" => {
+                self.targmax = value
+                    .split(',')
+                    .filter_map(|target| target.split_once(':'))
+                    .map(|(cmd, max)| (cmd.to_ascii_uppercase().into(), max.parse().ok()))
+                    .collect()
+            }
+            "NETWORK" => self.network = Some(value.to_owned().into()),
+            "MONITOR" => self.monitor = Some(number()),
+            "WHOX" => self.whox = true,
+            "UTF8ONLY" => self.utf8only = true,
+            "BOT" => self.bot = value.chars().next(),
+            "STATUSMSG" => self.statusmsg = value.chars().collect(),
+            "CHATHISTORY" => self.chathistory = Some(number().unwrap_or(0)),
+            _ => (),
+        }
+    }
+
+    /// Parse `(ov)@+` into mode and prefix pairs
+    fn parse_prefix(value: &str) -> Vec<(char, char)> {
+        let Some((modes, prefixes)) = value
+            .strip_prefix('(')
+            .and_then(|value| value.split_once(')'))
+        else {
+            return vec![];
+        };
+        modes.chars().zip(prefixes.chars()).collect()
+    }
+
+    pub fn is_channel(&self, name: &str) -> bool {
+        name.starts_with(self.chantypes.as_slice())
+    }
+
+    /// Normalize a nick or channel name for comparison
+    pub fn casefold(&self, name: &str) -> String {
+        self.casemapping.casefold(name)
+    }
+
+    pub fn nickname(&self, name: &str) -> Nickname {
+        Nickname::new(name, self.casemapping)
+    }
+
+    pub fn channel_name(&self, name: &str) -> ChannelName {
+        ChannelName::new(name, self.casemapping)
+    }
+
+    /// Compare nick or channel names using the server's case mapping
+    pub fn eq_ignore_case(&self, a: &str, b: &str) -> bool {
+        self.casefold(a) == self.casefold(b)
+    }
+
+    /// Prefix of a membership mode, e.g. `@` for `o`
+    pub fn mode_prefix(&self, mode: char) -> Option<char> {
+        self.prefix
+            .iter()
+            .find(|(m, _)| *m == mode)
+            .map(|(_, p)| *p)
+    }
+
+    /// Membership mode of a prefix, e.g. `o` for `@`
+    pub fn prefix_mode(&self, prefix: char) -> Option<char> {
+        self.prefix
+            .iter()
+            .find(|(_, p)| *p == prefix)
+            .map(|(m, _)| *m)
+    }
+
+    pub fn chanmode_type(&self, mode: char) -> Option<ChanModeType> {
+        let chanmodes = &self.chanmodes;
+        if self.mode_prefix(mode).is_some() {
+            Some(ChanModeType::Prefix)
+        } else if chanmodes.list.contains(&mode) {
+            Some(ChanModeType::List)
+        } else if chanmodes.always_parameter.contains(&mode) {
+            Some(ChanModeType::AlwaysParameter)
+        } else if chanmodes.set_parameter.contains(&mode) {
+            Some(ChanModeType::SetParameter)
+        } else if chanmodes.no_parameter.contains(&mode) {
+            Some(ChanModeType::NoParameter)
+        } else {
+            None
+        }
+    }
+
+    /// Maximum number of targets of a command, `None` if unlimited or unknown
+    pub fn max_targets(&self, command: &str) -> Option<usize> {
+        self.targmax
+            .get(command.to_ascii_uppercase().as_str())
+            .copied()
+            .flatten()
+    }
+}
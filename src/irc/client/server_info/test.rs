@@ -0,0 +1,146 @@
+use yew::AttrValue;
+
+use super::{ChanModeType, ServerInfo};
+use crate::irc::client::casemapping::CaseMapping;
+
+fn tokens(tokens: &[&str]) -> Vec<AttrValue> {
+    tokens
+        .iter()
+        .map(|t| AttrValue::from(t.to_string()))
+        .collect()
+}
+
+fn server_info(list: &[&str]) -> ServerInfo {
+    let mut info = ServerInfo::default();
+    info.apply(&tokens(list));
+    info
+}
+
+#[test]
+fn test_defaults() {
+    let info = ServerInfo::default();
+    assert_eq!(info.casemapping, CaseMapping::Rfc1459);
+    assert!(info.is_channel("#rust"));
+    assert!(info.is_channel("&local"));
+    assert!(!info.is_channel("dan"));
+    assert_eq!(info.mode_prefix('o'), Some('@'));
+    assert_eq!(info.nicklen, None);
+}
+
+#[test]
+fn test_tokens() {
+    let info = server_info(&[
+        "CASEMAPPING=ascii",
+        "CHANTYPES=#",
+        "PREFIX=(qaohv)~&@%+",
+        "CHANMODES=beI,kfL,lj,psmntirRcOAQKVCuzNSMTGZ",
+        "NICKLEN=30",
+        "CHANNELLEN=64",
+        "TOPICLEN=390",
+        "KICKLEN=255",
+        "AWAYLEN=200",
+        "MODES=4",
+        "TARGMAX=NAMES:1,LIST:1,KICK:1,WHOIS:1,PRIVMSG:4,NOTICE:4,JOIN:",
+        "NETWORK=Libera.Chat",
+        "MONITOR=100",
+        "WHOX",
+        "UTF8ONLY",
+        "BOT=B",
+        "STATUSMSG=@+",
+        "CHATHISTORY=100",
+    ]);
+
+    assert_eq!(info.casemapping, CaseMapping::Ascii);
+    assert_eq!(info.chantypes, vec!['#']);
+    assert!(!info.is_channel("&local"));
+    assert_eq!(
+        info.prefix,
+        vec![('q', '~'), ('a', '&'), ('o', '@'), ('h', '%'), ('v', '+')]
+    );
+    assert_eq!(info.chanmodes.list, vec!['b', 'e', 'I']);
+    assert_eq!(info.chanmodes.always_parameter, vec!['k', 'f', 'L']);
+    assert_eq!(info.chanmodes.set_parameter, vec!['l', 'j']);
+    assert_eq!(info.nicklen, Some(30));
+    assert_eq!(info.channellen, Some(64));
+    assert_eq!(info.topiclen, Some(390));
+    assert_eq!(info.kicklen, Some(255));
+    assert_eq!(info.awaylen, Some(200));
+    assert_eq!(info.modes, Some(4));
+    assert_eq!(info.max_targets("privmsg"), Some(4));
+    assert_eq!(info.max_targets("JOIN"), None);
+    assert_eq!(info.max_targets("PART"), None);
+    assert_eq!(info.network, Some("Libera.Chat".into()));
+    assert_eq!(info.monitor, Some(Some(100)));
+    assert!(info.whox);
+    assert!(info.utf8only);
+    assert_eq!(info.bot, Some('B'));
+    assert_eq!(info.statusmsg, vec!['@', '+']);
+    assert_eq!(info.chathistory, Some(100));
+}
+
+#[test]
+fn test_multiple_replies() {
+    let mut info = server_info(&["NICKLEN=30", "NETWORK=Example"]);
+    info.apply(&tokens(&["TOPICLEN=300", "MONITOR"]));
+
+    assert_eq!(info.nicklen, Some(30));
+    assert_eq!(info.topiclen, Some(300));
+    assert_eq!(info.network, Some("Example".into()));
+    assert_eq!(info.monitor, Some(None));
+}
+
+#[test]
+fn test_remove_token() {
+    let mut info = server_info(&["CHANTYPES=#", "NICKLEN=30", "WHOX"]);
+    info.apply(&tokens(&["-CHANTYPES", "-WHOX"]));
+
+    assert_eq!(info.chantypes, vec!['#', '&']);
+    assert!(!info.whox);
+    assert_eq!(info.nicklen, Some(30));
+    assert!(!info.tokens.contains_key("WHOX"));
+}
+
+#[test]
+fn test_escaped_value() {
+    let info = server_info(&["NETWORK=Example\\x20Network\\x3D"]);
+    assert_eq!(info.network, Some("Example Network=".into()));
+    assert_eq!(
+        info.tokens.get("NETWORK"),
+        Some(&Some("Example Network=".into()))
+    );
+}
+
+#[test]
+fn test_empty_prefix() {
+    let info = server_info(&["PREFIX="]);
+    assert!(info.prefix.is_empty());
+}
+
+#[test]
+fn test_casemapping() {
+    let rfc1459 = ServerInfo::default();
+    assert!(rfc1459.eq_ignore_case("Dan[m]", "dan{m}"));
+    assert!(rfc1459.eq_ignore_case("a\\b~", "A|B^"));
+
+    let strict = server_info(&["CASEMAPPING=strict-rfc1459"]);
+    assert!(strict.eq_ignore_case("Dan[m]", "dan{m}"));
+    assert!(!strict.eq_ignore_case("dan~", "dan^"));
+
+    let ascii = server_info(&["CASEMAPPING=ascii"]);
+    assert!(ascii.eq_ignore_case("DAN", "dan"));
+    assert!(!ascii.eq_ignore_case("dan[m]", "dan{m}"));
+
+    let rfc7613 = server_info(&["CASEMAPPING=rfc7613"]);
+    assert!(rfc7613.eq_ignore_case("JÖRG", "jörg"));
+}
+
+#[test]
+fn test_chanmode_type() {
+    let info = ServerInfo::default();
+    assert_eq!(info.chanmode_type('b'), Some(ChanModeType::List));
+    assert_eq!(info.chanmode_type('k'), Some(ChanModeType::AlwaysParameter));
+    assert_eq!(info.chanmode_type('l'), Some(ChanModeType::SetParameter));
+    assert_eq!(info.chanmode_type('m'), Some(ChanModeType::NoParameter));
+    assert_eq!(info.chanmode_type('o'), Some(ChanModeType::Prefix));
+    assert_eq!(info.chanmode_type('X'), None);
+}
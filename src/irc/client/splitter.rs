@@ -0,0 +1,244 @@
+use std::fmt::Display;
+
+use crate::irc::parser::{
+    capability::Capability,
+    generic_message::GenericMessage,
+    message::{Message, MessageBuilder},
+};
+
+#[cfg(test)]
+mod test;
+
+/// Maximum length of a line without tags, including CRLF
+pub const MAX_LINE_LENGTH: usize = 512;
+
+/// Maximum length of the tag data sent by clients, without `@` and the space
+pub const MAX_TAGS_LENGTH: usize = 4094;
+
+/// Assumed length of our user name, as long as the server did not tell it
+const DEFAULT_USER_LENGTH: usize = 10;
+
+/// Assumed length of our host, as long as the server did not tell it
+const DEFAULT_HOST_LENGTH: usize = 63;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Tag data exceeds `MAX_TAGS_LENGTH`, with the actual length
+    TagsTooLong(usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::TagsTooLong(length) => {
+                write!(f, "Tags are too long ({length} of {MAX_TAGS_LENGTH} bytes)")
+            }
+        }
+    }
+}
+
+/// Limits of `draft/multiline` batches, as advertised by the server
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Multiline {
+    /// Maximum number of bytes of all lines, including line breaks
+    pub max_bytes: usize,
+    pub max_lines: Option<usize>,
+}
+
+impl Multiline {
+    /// Limits from the capability, e.g. `max-bytes=4096,max-lines=24`
+    pub fn parse(capability: &Capability) -> Option<Self> {
+        let value = |key: &str| {
+            capability
+                .value_list()
+                .iter()
+                .filter_map(|value| value.split_once('='))
+                .find(|(k, _)| *k == key)
+                .and_then(|(_, value)| value.parse().ok())
+        };
+        Some(Multiline {
+            max_bytes: value("max-bytes")?,
+            max_lines: value("max-lines"),
+        })
+    }
+}
+
+/// Splits outgoing `PRIVMSG` and `NOTICE` texts, so they still fit into a
+/// single line when the server relays them with our full prefix, e.g.
+/// `:dan!d@example.com PRIVMSG #rust :text`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Splitter {
+    /// Length of `nick!user@host`
+    prefix_length: usize,
+    multiline: Option<Multiline>,
+}
+
+impl Splitter {
+    pub fn new(nick: &str, user: Option<&str>, host: Option<&str>) -> Self {
+        let user = user.map_or(DEFAULT_USER_LENGTH, str::len);
+        let host = host.map_or(DEFAULT_HOST_LENGTH, str::len);
+        Splitter {
+            prefix_length: nick.len() + 1 + user + 1 + host,
+            multiline: None,
+        }
+    }
+
+    /// Send texts with several lines as `draft/multiline` batch
+    pub fn multiline(mut self, multiline: Option<Multiline>) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Bytes available for the text of `command` sent to `target`
+    pub fn available(&self, command: &str, target: &str) -> usize {
+        // `:prefix COMMAND target :text\r\n`
+        let overhead = 1 + self.prefix_length + 1 + command.len() + 1 + target.len() + 2 + 2;
+        MAX_LINE_LENGTH.saturating_sub(overhead).max(1)
+    }
+
+    /// Messages which send `text` with `command` to `target`. Each line of
+    /// the text is sent as separate message, unless a multiline batch can be
+    /// used. `tags` are added to every message, or to the batch.
+    pub fn messages(
+        &self,
+        command: &str,
+        target: &str,
+        text: &str,
+        tags: &[(&str, Option<&str>)],
+        batch_ref: &str,
+    ) -> Result<Vec<Message>, Error> {
+        let available = self.available(command, target);
+
+        // CTCP messages are split within their delimiters
+        if let Some(ctcp) = text.strip_prefix('\x01') {
+            let ctcp = ctcp.strip_suffix('\x01').unwrap_or(ctcp);
+            let (kind, body) = ctcp.split_once(' ').unwrap_or((ctcp, ""));
+            let available = available.saturating_sub(kind.len() + 3).max(1);
+            return split(body, available)
+                .into_iter()
+                .map(|chunk| {
+                    let text = match chunk.is_empty() {
+                        true => format!("\x01{kind}\x01"),
+                        false => format!("\x01{kind} {chunk}\x01"),
+                    };
+                    build(command, target, &text, tags)
+                })
+                .collect();
+        }
+
+        let lines = text.lines().collect::<Vec<_>>();
+        let chunks = lines
+            .iter()
+            .map(|line| split(line, available))
+            .collect::<Vec<_>>();
+
+        if let Some(multiline) = self.multiline {
+            let count = chunks.iter().map(Vec::len).sum::<usize>();
+            let bytes = text.trim_end_matches(['\r', '\n']).len();
+            let fits =
+                bytes <= multiline.max_bytes && multiline.max_lines.is_none_or(|max| count <= max);
+            if count > 1 && fits {
+                return self.batch(command, target, &chunks, tags, batch_ref);
+            }
+        }
+
+        chunks
+            .iter()
+            .flatten()
+            .filter(|chunk| !chunk.is_empty())
+            .map(|chunk| build(command, target, chunk, tags))
+            .collect()
+    }
+
+    fn batch(
+        &self,
+        command: &str,
+        target: &str,
+        lines: &[Vec<&str>],
+        tags: &[(&str, Option<&str>)],
+        batch_ref: &str,
+    ) -> Result<Vec<Message>, Error> {
+        let open = GenericMessage::cmd("BATCH")
+            .param(&format!("+{batch_ref}"))
+            .param("draft/multiline")
+            .param(target);
+        let mut messages = vec![with_tags(Message::generic(open), tags)?];
+
+        for chunks in lines {
+            for (idx, chunk) in chunks.iter().enumerate() {
+                let mut builder =
+                    Message::generic(GenericMessage::cmd(command).param(target).param(chunk))
+                        .tag("batch", Some(batch_ref));
+                // continuations of a split line are joined without line break
+                if idx > 0 {
+                    builder = builder.tag("draft/multiline-concat", None);
+                }
+                messages.push(builder.build());
+            }
+        }
+
+        let close = GenericMessage::cmd("BATCH").param(&format!("-{batch_ref}"));
+        messages.push(Message::generic(close).build());
+        Ok(messages)
+    }
+}
+
+fn build(
+    command: &str,
+    target: &str,
+    text: &str,
+    tags: &[(&str, Option<&str>)],
+) -> Result<Message, Error> {
+    let msg = GenericMessage::cmd(command).param(target).param(text);
+    with_tags(Message::generic(msg), tags)
+}
+
+fn with_tags(mut builder: MessageBuilder, tags: &[(&str, Option<&str>)]) -> Result<Message, Error> {
+    for (key, value) in tags {
+        builder = builder.tag(key, *value);
+    }
+    let message = builder.build();
+
+    // tag data is everything between `@` and the first space
+    let line = message.to_string();
+    let length = match line.strip_prefix('@') {
+        Some(tags) => tags.find(' ').unwrap_or(tags.len()),
+        None => 0,
+    };
+    if length > MAX_TAGS_LENGTH {
+        return Err(Error::TagsTooLong(length));
+    }
+    Ok(message)
+}
+
+/// Split text into chunks of at most `max` bytes. Chunks end after a space
+/// if possible, and never within a UTF-8 character.
+pub fn split(text: &str, max: usize) -> Vec<&str> {
+    let mut chunks = vec![];
+    let mut rest = text;
+
+    while rest.len() > max {
+        let mut end = max;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        // a single character which does not fit at all
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+
+        // keep the space with the chunk, so the chunks join to the text
+        if let Some(space) = rest[..end].rfind(' ').filter(|space| *space > 0) {
+            end = space + 1;
+        }
+
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
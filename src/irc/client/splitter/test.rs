@@ -0,0 +1,166 @@
+use crate::irc::parser::{capability::Capability, message::Message};
+
+use super::{split, Error, Multiline, Splitter, MAX_LINE_LENGTH, MAX_TAGS_LENGTH};
+
+fn lines(messages: &[Message]) -> Vec<String> {
+    messages.iter().map(|message| message.to_string()).collect()
+}
+
+#[test]
+fn test_split() {
+    assert_eq!(split("hello world", 20), vec!["hello world"]);
+    assert_eq!(split("hello world", 8), vec!["hello ", "world"]);
+    assert_eq!(split("hello big world", 12), vec!["hello big ", "world"]);
+    // words longer than a chunk are split
+    assert_eq!(split("abcdefgh", 3), vec!["abc", "def", "gh"]);
+    assert_eq!(split("", 3), vec![""]);
+}
+
+#[test]
+fn test_split_utf8() {
+    // `ä` takes two bytes
+    assert_eq!(split("äääää", 3), vec!["ä", "ä", "ä", "ä", "ä"]);
+    assert_eq!(split("aäb", 2), vec!["a", "ä", "b"]);
+    // a character longer than a chunk is kept as a whole
+    assert_eq!(split("🦀🦀", 2), vec!["🦀", "🦀"]);
+}
+
+#[test]
+fn test_available() {
+    // `:dan!d@example.com PRIVMSG #rust :` and CRLF
+    let splitter = Splitter::new("dan", Some("d"), Some("example.com"));
+    assert_eq!(splitter.available("PRIVMSG", "#rust"), MAX_LINE_LENGTH - 36);
+
+    // unknown user name and host are assumed to be long
+    let unknown = Splitter::new("dan", None, None);
+    assert!(unknown.available("PRIVMSG", "#rust") < splitter.available("PRIVMSG", "#rust"));
+}
+
+#[test]
+fn test_messages() {
+    let splitter = Splitter::new("dan", Some("d"), Some("example.com"));
+    let available = splitter.available("PRIVMSG", "#rust");
+    let text = format!("{} {}", "a".repeat(available - 1), "b".repeat(10));
+
+    let messages = splitter
+        .messages("PRIVMSG", "#rust", &text, &[], "1")
+        .unwrap();
+    assert_eq!(
+        lines(&messages),
+        vec![
+            format!("PRIVMSG #rust :{} ", "a".repeat(available - 1)),
+            format!("PRIVMSG #rust {}", "b".repeat(10)),
+        ]
+    );
+
+    // relayed by the server, each message fits into a line
+    for message in messages {
+        assert!(format!(":dan!d@example.com {message}\r\n").len() <= MAX_LINE_LENGTH);
+    }
+}
+
+#[test]
+fn test_messages_lines() {
+    let splitter = Splitter::new("dan", None, None);
+    assert_eq!(
+        lines(
+            &splitter
+                .messages("NOTICE", "alice", "one\ntwo\n\nthree", &[], "1")
+                .unwrap()
+        ),
+        vec!["NOTICE alice one", "NOTICE alice two", "NOTICE alice three"]
+    );
+}
+
+#[test]
+fn test_messages_ctcp() {
+    let splitter = Splitter::new("dan", Some("d"), Some("example.com"));
+    let text = format!("\x01ACTION {}\x01", "waves ".repeat(100));
+
+    let messages = splitter
+        .messages("PRIVMSG", "#rust", &text, &[], "1")
+        .unwrap();
+    assert_eq!(messages.len(), 2);
+    for line in lines(&messages) {
+        assert!(line.starts_with("PRIVMSG #rust :\x01ACTION waves"));
+        assert!(line.ends_with('\x01'));
+        assert!(format!(":dan!d@example.com {line}\r\n").len() <= MAX_LINE_LENGTH);
+    }
+}
+
+#[test]
+fn test_messages_multiline() {
+    let splitter =
+        Splitter::new("dan", Some("d"), Some("example.com")).multiline(Some(Multiline {
+            max_bytes: 4096,
+            max_lines: Some(3),
+        }));
+    let available = splitter.available("PRIVMSG", "#rust");
+    let long = "x".repeat(available + 5);
+
+    assert_eq!(
+        lines(
+            &splitter
+                .messages("PRIVMSG", "#rust", &format!("hi\n{long}"), &[], "ml1")
+                .unwrap()
+        ),
+        vec![
+            "BATCH +ml1 draft/multiline #rust".to_owned(),
+            "@batch=ml1 PRIVMSG #rust hi".to_owned(),
+            format!("@batch=ml1 PRIVMSG #rust {}", &long[..available]),
+            "@batch=ml1;draft/multiline-concat PRIVMSG #rust xxxxx".to_owned(),
+            "BATCH -ml1".to_owned(),
+        ]
+    );
+
+    // a single message needs no batch
+    assert_eq!(
+        lines(
+            &splitter
+                .messages("PRIVMSG", "#rust", "hi", &[], "ml2")
+                .unwrap()
+        ),
+        vec!["PRIVMSG #rust hi"]
+    );
+
+    // too many lines for a batch
+    assert_eq!(
+        splitter
+            .messages("PRIVMSG", "#rust", "1\n2\n3\n4", &[], "ml3")
+            .unwrap()
+            .len(),
+        4
+    );
+}
+
+#[test]
+fn test_messages_tags() {
+    let splitter = Splitter::new("dan", None, None);
+    assert_eq!(
+        lines(
+            &splitter
+                .messages("TAGMSG", "#rust", "hi", &[("+typing", Some("active"))], "1")
+                .unwrap()
+        ),
+        vec!["@+typing=active TAGMSG #rust hi"]
+    );
+
+    let value = "x".repeat(MAX_TAGS_LENGTH);
+    assert_eq!(
+        splitter.messages("PRIVMSG", "#rust", "hi", &[("+data", Some(&value))], "1"),
+        Err(Error::TagsTooLong(MAX_TAGS_LENGTH + 6))
+    );
+}
+
+#[test]
+fn test_multiline_parse() {
+    let capability = Capability::values("draft/multiline", vec!["max-bytes=4096", "max-lines=24"]);
+    assert_eq!(
+        Multiline::parse(&capability),
+        Some(Multiline {
+            max_bytes: 4096,
+            max_lines: Some(24),
+        })
+    );
+    assert_eq!(Multiline::parse(&Capability::new("draft/multiline")), None);
+}
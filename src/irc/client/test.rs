@@ -1,10 +1,20 @@
-use websocket::{stream::sync::NetworkStream, sync::Client, ClientBuilder, OwnedMessage};
+use std::time::Duration;
 
-use crate::irc::parser::capability::Capability;
+use websocket::{stream::sync::NetworkStream, sync, ClientBuilder, OwnedMessage};
 
-use super::cap_negotiator::CapNegotiator;
+use crate::irc::parser::{capability::Capability, message::Message};
 
-fn connect() -> Client<Box<dyn NetworkStream + Send>> {
+use super::{
+    authenticator::mechanism::Plain,
+    buffer::{Activity, Buffer, BufferName, LineKind, MAX_LINES},
+    cap_negotiator::CapNegotiator,
+    casemapping::{CaseMapping, Nickname},
+    command,
+    highlight::Highlight,
+    Client, ClientState, Config, Error, MAX_NICK_ATTEMPTS,
+};
+
+fn connect() -> sync::Client<Box<dyn NetworkStream + Send>> {
     let host = dotenv::var("WEBSOCKET_HOST").expect("");
     println!("Connectin websocket to {host}");
     match ClientBuilder::new(&host).unwrap().connect(None) {
@@ -21,7 +31,7 @@ fn integrate_client_with_websocket() {
     let mut client = connect();
     let capabilities = CapNegotiator::request(vec![Capability::new("sasl")]);
 
-    let msg = capabilities.ls(Some("302".into()));
+    let msg = capabilities.ls(Some("302"));
     client
         .send_message(&OwnedMessage::Text(msg.to_string()))
         .unwrap();
@@ -30,3 +40,1562 @@ fn integrate_client_with_websocket() {
         println!("{msg:?}");
     }
 }
+
+/// Feed scripted server lines to the client, returns all responses
+fn handle(client: &mut Client, lines: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .flat_map(|line| handle_at(client, line, Duration::ZERO))
+        .map(|msg| msg.to_string())
+        .collect()
+}
+
+fn handle_at(client: &mut Client, line: &str, now: Duration) -> Vec<Message> {
+    client.handle(&Message::parse(line).unwrap(), now).unwrap()
+}
+
+fn to_strings(messages: Vec<Message>) -> Vec<String> {
+    messages.iter().map(|msg| msg.to_string()).collect()
+}
+
+#[test]
+fn test_register_without_capabilities() {
+    let mut client = Client::new(Config::new("dan"));
+    assert_eq!(client.state(), &ClientState::Disconnected);
+
+    assert_eq!(
+        to_strings(client.register()),
+        vec!["CAP LS 302", "NICK dan", "USER dan 0 * dan"]
+    );
+    assert_eq!(client.state(), &ClientState::CapLs);
+
+    assert_eq!(
+        handle(&mut client, &[":server CAP * LS :sasl"]),
+        vec!["CAP END"]
+    );
+    assert_eq!(client.state(), &ClientState::Registering);
+
+    let none = Vec::<String>::new();
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":server 001 dan :Welcome to the network dan"]
+        ),
+        none
+    );
+    assert!(client.is_registered());
+    assert_eq!(client.nickname(), "dan");
+}
+
+#[test]
+fn test_register_with_password() {
+    let mut config = Config::new("dan");
+    config.username = "d".into();
+    config.realname = "Dan Smith".into();
+    config.password = Some("secret".into());
+
+    let mut client = Client::new(config);
+    assert_eq!(
+        to_strings(client.register()),
+        vec![
+            "CAP LS 302",
+            "PASS secret",
+            "NICK dan",
+            "USER d 0 * :Dan Smith"
+        ]
+    );
+}
+
+#[test]
+fn test_register_server_without_cap() {
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+
+    handle(
+        &mut client,
+        &[
+            ":server 421 dan CAP :Unknown command",
+            ":server 001 dan :Welcome",
+        ],
+    );
+    assert!(client.is_registered());
+}
+
+#[test]
+fn test_register_with_sasl() {
+    let mut config = Config::new("jilles");
+    config.sasl = vec![Box::new(Plain::new("jilles", "sesame"))];
+
+    let mut client = Client::new(config);
+    client.register();
+
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":server CAP * LS :multi-prefix sasl=PLAIN,EXTERNAL"]
+        ),
+        vec!["CAP REQ :multi-prefix sasl"]
+    );
+    assert_eq!(
+        handle(&mut client, &[":server CAP * ACK :multi-prefix sasl"]),
+        vec!["AUTHENTICATE PLAIN"]
+    );
+    assert_eq!(client.state(), &ClientState::Authenticating);
+
+    assert_eq!(
+        handle(&mut client, &["AUTHENTICATE +"]),
+        vec!["AUTHENTICATE AGppbGxlcwBzZXNhbWU="]
+    );
+    assert_eq!(
+        handle(
+            &mut client,
+            &[
+                ":server 900 jilles jilles!jilles@localhost jilles :You are now logged in as jilles",
+                ":server 903 jilles :SASL authentication successful",
+            ]
+        ),
+        vec!["CAP END"]
+    );
+    assert_eq!(client.account(), Some(&"jilles".into()));
+    assert_eq!(client.state(), &ClientState::Registering);
+
+    handle(&mut client, &[":server 001 jilles :Welcome"]);
+    assert!(client.is_registered());
+}
+
+#[test]
+fn test_register_sasl_failure() {
+    let mut config = Config::new("jilles");
+    config.sasl = vec![Box::new(Plain::new("jilles", "wrong"))];
+
+    let mut client = Client::new(config);
+    client.register();
+    handle(
+        &mut client,
+        &[
+            ":server CAP * LS :sasl",
+            ":server CAP * ACK sasl",
+            "AUTHENTICATE +",
+        ],
+    );
+
+    // registration continues without authentication
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":server 904 jilles :SASL authentication failed"]
+        ),
+        vec!["CAP END"]
+    );
+    assert_eq!(client.account(), None);
+}
+
+#[test]
+fn test_register_sasl_unsupported_mechanism() {
+    let mut config = Config::new("jilles");
+    config.sasl = vec![Box::new(Plain::new("jilles", "sesame"))];
+
+    let mut client = Client::new(config);
+    client.register();
+    handle(&mut client, &[":server CAP * LS :sasl=EXTERNAL"]);
+    assert_eq!(
+        handle(&mut client, &[":server CAP * ACK sasl"]),
+        vec!["CAP END"]
+    );
+}
+
+#[test]
+fn test_nick_fallback() {
+    let mut config = Config::new("dan");
+    config.alt_nicknames = vec!["dan2".into()];
+
+    let mut client = Client::new(config);
+    client.register();
+
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":server 433 * dan :Nickname is already in use"]
+        ),
+        vec!["NICK dan2"]
+    );
+    assert_eq!(
+        handle(&mut client, &[":server 436 * dan2 :Nickname collision"]),
+        vec!["NICK dan2_"]
+    );
+    assert_eq!(
+        handle(&mut client, &[":server 432 * dan2_ :Erroneus nickname"]),
+        vec!["NICK dan2__"]
+    );
+
+    handle(&mut client, &[":server 001 dan2__ :Welcome"]);
+    assert_eq!(client.nickname(), "dan2__");
+
+    // after registration, errors are left to the user
+    let none = Vec::<String>::new();
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":server 433 dan2__ dan :Nickname is already in use"]
+        ),
+        none
+    );
+}
+
+#[test]
+fn test_nick_fallback_gives_up() {
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+
+    let lines = vec![":server 433 * dan :Nickname is already in use"; 20];
+    assert_eq!(handle(&mut client, &lines).len(), MAX_NICK_ATTEMPTS);
+}
+
+#[test]
+fn test_server_assigned_nick() {
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+    handle(
+        &mut client,
+        &[":server CAP * LS :", ":server 001 Guest123 :Welcome"],
+    );
+    assert_eq!(client.nickname(), "Guest123");
+
+    handle(&mut client, &[":Guest123!d@host NICK dan"]);
+    assert_eq!(client.nickname(), "dan");
+
+    // nick changes of other users are ignored
+    handle(&mut client, &[":other!o@host NICK foo"]);
+    assert_eq!(client.nickname(), "dan");
+}
+
+fn registered(config: Config) -> Client {
+    let mut client = Client::new(config);
+    client.register();
+    handle(
+        &mut client,
+        &[":server CAP * LS :", ":server 001 dan :Welcome"],
+    );
+    client
+}
+
+#[test]
+fn test_pong() {
+    let mut client = registered(Config::new("dan"));
+    assert_eq!(
+        handle(&mut client, &["PING :irc.example.com"]),
+        vec!["PONG irc.example.com"]
+    );
+}
+
+#[test]
+fn test_pong_before_registration() {
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+    assert_eq!(handle(&mut client, &["PING 12345"]), vec!["PONG 12345"]);
+}
+
+#[test]
+fn test_keepalive() {
+    let secs = Duration::from_secs;
+
+    let mut config = Config::new("dan");
+    config.ping_interval = secs(60);
+    config.ping_timeout = secs(30);
+    let mut client = registered(config);
+    assert_eq!(client.lag(), None);
+
+    assert!(client.tick(secs(59)).unwrap().is_empty());
+    let ping = to_strings(client.tick(secs(60)).unwrap());
+    assert_eq!(ping, vec!["PING irc.rs-60000"]);
+
+    handle_at(
+        &mut client,
+        ":server PONG server :irc.rs-60000",
+        Duration::from_millis(60_120),
+    );
+    assert_eq!(client.lag(), Some(Duration::from_millis(120)));
+
+    // no PING while the server is active
+    handle_at(&mut client, ":dan!d@host PRIVMSG #rust :hello", secs(100));
+    assert!(client.tick(secs(150)).unwrap().is_empty());
+    assert_eq!(client.tick(secs(160)).unwrap().len(), 1);
+}
+
+#[test]
+fn test_keepalive_timeout() {
+    let secs = Duration::from_secs;
+
+    let mut client = registered(Config::new("dan"));
+    client.tick(secs(60)).unwrap();
+    assert!(client.tick(secs(89)).unwrap().is_empty());
+    assert!(matches!(client.tick(secs(90)), Err(Error::Timeout(_))));
+    assert_eq!(client.state(), &ClientState::Disconnected);
+
+    // no more pings for a dead connection
+    assert!(client.tick(secs(200)).unwrap().is_empty());
+}
+
+#[test]
+fn test_keepalive_before_welcome() {
+    let secs = Duration::from_secs;
+
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+    handle_at(&mut client, ":server CAP * LS :", secs(100));
+    // the server stalls before RPL_WELCOME
+    assert!(client.tick(secs(159)).unwrap().is_empty());
+    assert_eq!(
+        to_strings(client.tick(secs(160)).unwrap()),
+        vec!["PING irc.rs-160000"]
+    );
+    assert!(matches!(client.tick(secs(190)), Err(Error::Timeout(_))));
+    assert_eq!(client.state(), &ClientState::Disconnected);
+}
+
+#[test]
+fn test_join_remembers_key() {
+    let mut client = Client::new(Config::new("dan"));
+    assert_eq!(
+        to_strings(client.join("#rust", None).unwrap()),
+        vec!["JOIN #rust"]
+    );
+    assert_eq!(
+        to_strings(client.join("#secret", Some("key")).unwrap()),
+        vec!["JOIN #secret key"]
+    );
+    // joining again updates the key, joining without one keeps it
+    client.join("#rust", Some("rust")).unwrap();
+    assert_eq!(
+        to_strings(client.join("#rust", None).unwrap()),
+        vec!["JOIN #rust rust"]
+    );
+
+    let buffers = client
+        .buffers
+        .iter()
+        .map(|b| (b.id, b.name.as_str(), b.key.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        buffers,
+        vec![(0, "#rust", Some("rust")), (1, "#secret", Some("key"))]
+    );
+}
+
+#[test]
+fn test_join_limits() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[":server 005 dan CHANNELLEN=5 :are supported"],
+    );
+    assert_eq!(
+        client.join("#toolong", None),
+        Err(command::Error::TooLong {
+            argument: "channel",
+            max: 5
+        })
+    );
+    assert_eq!(names(&client), vec!["server"]);
+}
+
+#[test]
+fn test_rejoin_in_chunks() {
+    let mut client = Client::new(Config::new("dan"));
+    for i in 0..100 {
+        client.join(&format!("#channel{i:03}"), None).unwrap();
+    }
+
+    client.register();
+    let joins = handle(
+        &mut client,
+        &[":server CAP * LS :", ":server 001 dan :Welcome"],
+    );
+    let joins = &joins[1..];
+    assert_eq!(joins.len(), 4);
+    assert!(joins.iter().all(|join| join.len() <= 512));
+    assert!(joins[0].starts_with("JOIN #channel000,#channel001,"));
+    assert_eq!(joins[3], "JOIN #channel099");
+}
+
+#[test]
+fn test_isupport() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":server 005 dan CHANTYPES=# NICKLEN=9 NETWORK=Example :are supported by this server",
+            ":server 005 dan CASEMAPPING=ascii :are supported by this server",
+        ],
+    );
+
+    let info = client.server_info();
+    assert_eq!(info.chantypes, vec!['#']);
+    assert_eq!(info.nicklen, Some(9));
+    assert_eq!(info.network, Some("Example".into()));
+
+    // server parameters are reset for a new connection
+    client.register();
+    assert_eq!(client.server_info().nicklen, None);
+}
+
+#[test]
+fn test_nick_comparison_uses_casemapping() {
+    let mut client = registered(Config::new("dan[m]"));
+    handle(&mut client, &[":server 001 dan[m] :Welcome"]);
+
+    handle(&mut client, &[":Dan{M}!d@host NICK dan"]);
+    assert_eq!(client.nickname(), "dan");
+}
+
+#[test]
+fn test_join_ignores_case() {
+    let mut client = Client::new(Config::new("dan"));
+    client.join("#Rust", None).unwrap();
+    client.join("#rust", Some("key")).unwrap();
+    client.join("#rust[m]", None).unwrap();
+    client.join("#RUST{M}", None).unwrap();
+
+    let buffers = client
+        .buffers
+        .iter()
+        .map(|b| (b.name.as_str(), b.key.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(buffers, vec![("#Rust", Some("key")), ("#rust[m]", None)]);
+}
+
+#[test]
+fn test_casemapping_change_refolds_names() {
+    let mut client = registered(Config::new("dan[m]"));
+    handle(&mut client, &[":server 001 dan[m] :Welcome"]);
+    client.join("#rust[m]", None).unwrap();
+    assert_eq!(client.nickname(), "DAN{M}");
+
+    handle(
+        &mut client,
+        &[":server 005 dan[m] CASEMAPPING=ascii :are supported by this server"],
+    );
+    assert_eq!(client.nickname().casemapping(), CaseMapping::Ascii);
+    assert_ne!(client.nickname(), "dan{m}");
+    assert_eq!(client.nickname(), "DAN[M]");
+    assert_eq!(buffer(&client, "#rust[m]").name.as_str(), "#rust[m]");
+    assert_eq!(
+        buffer(&client, "#rust[m]")
+            .name
+            .channel()
+            .unwrap()
+            .casemapping(),
+        CaseMapping::Ascii
+    );
+}
+
+#[test]
+fn test_rejoin_channels_only() {
+    let mut client = Client::new(Config::new("dan"));
+    client.buffers.push(Buffer::new(
+        10,
+        BufferName::Query(Nickname::new("dan", CaseMapping::default())),
+    ));
+    client.join("#rust", None).unwrap();
+    client.join("&local", None).unwrap();
+
+    client.register();
+    let joins = handle(
+        &mut client,
+        &[":server CAP * LS :", ":server 001 dan :Welcome"],
+    );
+    assert_eq!(joins, vec!["CAP END", "JOIN #rust,&local"]);
+}
+
+#[test]
+fn test_join_respects_targmax() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[":server 005 dan TARGMAX=JOIN:2 :are supported"],
+    );
+    let id = client.buffers[0].id;
+    assert_eq!(
+        command(&mut client, id, "/join #a,#b,#c,#d k1,k2"),
+        vec!["JOIN #a,#b k1,k2", "JOIN #c,#d"]
+    );
+}
+
+#[test]
+fn test_rejoin_skips_left_channels() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #rust",
+            ":dan!d@host JOIN #parted",
+            ":dan!d@host JOIN #kicked",
+            ":dan!d@host PART #parted",
+            ":op!o@host KICK #kicked dan :bye",
+        ],
+    );
+    client.disconnected();
+
+    client.register();
+    let joins = handle(
+        &mut client,
+        &[":server CAP * LS :", ":server 001 dan :Welcome"],
+    );
+    assert_eq!(joins, vec!["CAP END", "JOIN #rust"]);
+
+    // joining again rejoins it after the next reconnect
+    handle(&mut client, &[":dan!d@host JOIN #parted"]);
+    client.disconnected();
+    client.register();
+    let joins = handle(
+        &mut client,
+        &[":server CAP * LS :", ":server 001 dan :Welcome"],
+    );
+    assert_eq!(joins, vec!["CAP END", "JOIN #rust,#parted"]);
+}
+
+#[test]
+fn test_nick_fallback_respects_nicklen() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[":server 005 dan NICKLEN=5 :are supported by this server"],
+    );
+
+    // pretend registration is still in progress, e.g. after a nick collision
+    client.state = ClientState::Registering;
+    client.nickname = Nickname::new("abcde", CaseMapping::default());
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":server 433 * abcde :Nickname is already in use"]
+        ),
+        vec!["NICK abcd_"]
+    );
+}
+
+fn buffer<'a>(client: &'a Client, name: &str) -> &'a Buffer {
+    client
+        .buffers
+        .iter()
+        .find(|b| b.name.as_str() == name)
+        .unwrap()
+}
+
+/// Nick list of a channel with prefixes, sorted by rank
+fn members(client: &Client, channel: &str) -> Vec<String> {
+    let Some(state) = &buffer(client, channel).channel else {
+        return vec![];
+    };
+    state
+        .sorted_members(client.server_info())
+        .iter()
+        .map(|member| {
+            let prefix = member.prefix(client.server_info()).map(String::from);
+            format!("{}{}", prefix.unwrap_or_default(), member.nick)
+        })
+        .collect()
+}
+
+#[test]
+fn test_channel_tracking() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #Rust",
+            ":server 332 dan #rust :Rust programming",
+            ":server 333 dan #rust alice 1700000000",
+            ":server 353 dan = #rust :@alice!a@host +bob dan",
+            ":server 366 dan #rust :End of /NAMES list",
+            ":server 324 dan #rust +ntk key",
+            ":server 329 dan #rust 1600000000",
+        ],
+    );
+    assert_eq!(members(&client, "#Rust"), vec!["@alice", "+bob", "dan"]);
+
+    let channel = buffer(&client, "#Rust").channel.as_ref().unwrap();
+    let topic = channel.topic.as_ref().unwrap();
+    assert_eq!(topic.text, "Rust programming");
+    assert_eq!(topic.setter, Some("alice".into()));
+    assert_eq!(topic.set_at, Some(1700000000));
+    assert_eq!(
+        channel.modes.keys().collect::<Vec<_>>(),
+        vec![&'k', &'n', &'t']
+    );
+    assert_eq!(channel.created, Some(1600000000));
+
+    handle(
+        &mut client,
+        &[
+            ":carol!c@host JOIN #rust",
+            ":Bob!b@host NICK robert",
+            ":alice!a@host MODE #rust +o-o+v carol alice robert",
+            ":carol!c@host TOPIC #rust :New topic",
+            ":alice!a@host PART #rust :bye",
+        ],
+    );
+    assert_eq!(members(&client, "#Rust"), vec!["@carol", "+robert", "dan"]);
+    let channel = buffer(&client, "#Rust").channel.as_ref().unwrap();
+    let topic = channel.topic.as_ref().unwrap();
+    assert_eq!(topic.text, "New topic");
+    assert_eq!(topic.setter, Some("carol".into()));
+
+    handle(
+        &mut client,
+        &[
+            ":robert!b@host QUIT :gone",
+            ":carol!c@host KICK #rust dan :out",
+        ],
+    );
+    assert!(buffer(&client, "#Rust").channel.is_none());
+    assert_eq!(members(&client, "#Rust"), Vec::<String>::new());
+
+    // the buffer is kept and the channel rejoined
+    handle(&mut client, &[":dan!d@host JOIN #rust"]);
+    assert_eq!(client.buffers.len(), 2);
+    assert_eq!(members(&client, "#Rust"), vec!["dan"]);
+}
+
+#[test]
+fn test_channel_state_reset_on_disconnect() {
+    let mut client = registered(Config::new("dan"));
+    client.join("#rust", None).unwrap();
+    handle(&mut client, &[":dan!d@host JOIN #rust"]);
+    assert!(buffer(&client, "#rust").channel.is_some());
+
+    client.disconnected();
+    assert!(buffer(&client, "#rust").channel.is_none());
+}
+
+/// Kinds and texts of the lines of a buffer
+fn lines(client: &Client, name: &str) -> Vec<(LineKind, String)> {
+    buffer(client, name)
+        .lines
+        .iter()
+        .map(|line| (line.kind.clone(), line.message.to_string()))
+        .collect()
+}
+
+fn names(client: &Client) -> Vec<&str> {
+    client.buffers.iter().map(|b| b.name.as_str()).collect()
+}
+
+#[test]
+fn test_route_server_messages() {
+    let mut client = Client::new(Config::new("dan"));
+    client.join("#rust", None).unwrap();
+    client.register();
+    handle(
+        &mut client,
+        &[
+            ":irc.example.com NOTICE * :*** Looking up your hostname",
+            ":server CAP * LS :",
+            ":irc.example.com 001 dan :Welcome dan",
+            ":irc.example.com 005 dan NICKLEN=9 :are supported by this server",
+            ":irc.example.com 372 dan :- Message of the day",
+            ":irc.example.com 421 dan FOO :Unknown command",
+            "ERROR :Closing link",
+        ],
+    );
+
+    // the server buffer is created first
+    assert_eq!(names(&client), vec!["irc.example.com", "#rust"]);
+    assert_eq!(
+        lines(&client, "irc.example.com"),
+        vec![
+            (LineKind::Notice, "*** Looking up your hostname".into()),
+            (LineKind::Message, "Welcome dan".into()),
+            (LineKind::Message, "- Message of the day".into()),
+            (LineKind::Error, "FOO Unknown command".into()),
+            (LineKind::Error, "Closing link".into()),
+        ]
+    );
+    let ids = buffer(&client, "irc.example.com")
+        .lines
+        .iter()
+        .map(|line| line.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_route_channel_messages() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":server 005 dan STATUSMSG=@+ :are supported by this server",
+            ":dan!d@host JOIN #rust",
+            ":server 332 dan #rust :Rust programming",
+            ":server 353 dan = #rust :dan alice bob",
+            ":server 366 dan #rust :End of /NAMES list",
+            ":alice!a@host PRIVMSG #Rust :hello",
+            ":alice!a@host PRIVMSG #rust :\x01ACTION waves\x01",
+            ":alice!a@host NOTICE @#rust :ops only",
+            ":alice!a@host MODE #rust +o bob",
+            ":alice!a@host TOPIC #rust :New topic",
+            ":alice!a@host KICK #rust bob :spam",
+            ":server 404 dan #rust :Cannot send to channel",
+            ":alice!a@host PART #rust :bye",
+        ],
+    );
+
+    assert_eq!(
+        lines(&client, "#rust"),
+        vec![
+            (LineKind::Join, "".into()),
+            (LineKind::Topic, "Rust programming".into()),
+            (LineKind::Message, "hello".into()),
+            (LineKind::Action, "waves".into()),
+            (LineKind::Notice, "ops only".into()),
+            (LineKind::Mode, "+o bob".into()),
+            (LineKind::Topic, "New topic".into()),
+            (LineKind::Kick { nick: "bob".into() }, "spam".into()),
+            (LineKind::Error, "#rust Cannot send to channel".into()),
+            (LineKind::Part, "bye".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_route_queries() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":NickServ!s@services NOTICE dan :Please identify",
+            ":alice!a@host PRIVMSG dan :hi dan",
+            // our own message, echoed back
+            ":dan!d@host PRIVMSG Alice :hi alice",
+            ":alice!a@host NOTICE dan :a notice",
+        ],
+    );
+
+    // notices only go to existing queries
+    assert_eq!(names(&client), vec!["server", "alice"]);
+    assert_eq!(
+        lines(&client, "server"),
+        vec![
+            (LineKind::Message, "Welcome".into()),
+            (LineKind::Notice, "Please identify".into()),
+        ]
+    );
+    assert_eq!(
+        lines(&client, "alice"),
+        vec![
+            (LineKind::Message, "hi dan".into()),
+            (LineKind::Message, "hi alice".into()),
+            (LineKind::Notice, "a notice".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_route_nick_and_quit() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #rust",
+            ":dan!d@host JOIN #yew",
+            ":server 353 dan = #rust :dan alice",
+            ":server 366 dan #rust :End of /NAMES list",
+            ":server 353 dan = #yew :dan bob",
+            ":server 366 dan #yew :End of /NAMES list",
+            ":alice!a@host PRIVMSG dan :hi",
+            ":alice!a@host NICK alice2",
+            ":alice2!a@host QUIT :gone",
+        ],
+    );
+
+    assert_eq!(names(&client), vec!["server", "#rust", "#yew", "alice2"]);
+    let nick = LineKind::Nick {
+        nickname: "alice2".into(),
+    };
+    assert_eq!(
+        lines(&client, "#rust")[1..],
+        [(nick.clone(), "".into()), (LineKind::Quit, "gone".into())]
+    );
+    assert_eq!(lines(&client, "#yew"), vec![(LineKind::Join, "".into())]);
+    assert_eq!(
+        lines(&client, "alice2"),
+        vec![
+            (LineKind::Message, "hi".into()),
+            (nick, "".into()),
+            (LineKind::Quit, "gone".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_nick_to_existing_query() {
+    let mut client = registered(Config::new("dan"));
+    handle(
+        &mut client,
+        &[
+            ":alice!a@host PRIVMSG dan :hi",
+            ":Alice2!a@host PRIVMSG dan :other",
+            ":alice!a@host NICK alice2",
+        ],
+    );
+
+    // no second buffer for the same nick
+    assert_eq!(names(&client), vec!["server", "alice", "Alice2"]);
+    let nick = LineKind::Nick {
+        nickname: "alice2".into(),
+    };
+    assert_eq!(
+        lines(&client, "alice"),
+        vec![(LineKind::Message, "hi".into()), (nick, "".into())]
+    );
+
+    // changing the case only renames the query
+    handle(&mut client, &[":alice!a@host NICK ALICE"]);
+    assert_eq!(names(&client), vec!["server", "ALICE", "Alice2"]);
+}
+
+/// Registered client with a joined channel, returns the id of its buffer
+fn with_channel() -> (Client, u64) {
+    let mut client = registered(Config::new("dan"));
+    handle(&mut client, &[":dan!d@host JOIN #rust"]);
+    let id = buffer(&client, "#rust").id;
+    (client, id)
+}
+
+fn command(client: &mut Client, buffer_id: u64, input: &str) -> Vec<String> {
+    to_strings(client.command(buffer_id, input, Duration::ZERO))
+}
+
+#[test]
+fn test_command_messages() {
+    let (mut client, id) = with_channel();
+    assert_eq!(
+        command(&mut client, id, "hello"),
+        vec!["PRIVMSG #rust hello"]
+    );
+    assert_eq!(
+        command(&mut client, id, "/me waves"),
+        vec!["PRIVMSG #rust :\x01ACTION waves\x01"]
+    );
+    assert_eq!(
+        command(&mut client, id, "/msg bob hi bob"),
+        vec!["PRIVMSG bob :hi bob"]
+    );
+
+    // sent messages are shown right away
+    assert_eq!(
+        lines(&client, "#rust")[1..],
+        [
+            (LineKind::Message, "hello".into()),
+            (LineKind::Action, "waves".into())
+        ]
+    );
+    assert_eq!(
+        lines(&client, "bob"),
+        vec![(LineKind::Message, "hi bob".into())]
+    );
+}
+
+#[test]
+fn test_command_buffers() {
+    let (mut client, id) = with_channel();
+    assert_eq!(
+        command(&mut client, id, "/join #yew,#secret ,key"),
+        vec!["JOIN #yew,#secret ,key"]
+    );
+    assert_eq!(buffer(&client, "#secret").key, Some("key".into()));
+    // without a key, the known one is kept and used
+    assert_eq!(
+        command(&mut client, id, "/join #secret"),
+        vec!["JOIN #secret key"]
+    );
+    assert_eq!(buffer(&client, "#secret").key, Some("key".into()));
+
+    assert!(command(&mut client, id, "/query alice").is_empty());
+    assert_eq!(
+        names(&client),
+        vec!["server", "#rust", "#yew", "#secret", "alice"]
+    );
+
+    // closing a joined channel leaves it
+    assert_eq!(command(&mut client, id, "/close"), vec!["PART #rust"]);
+    let alice = buffer(&client, "alice").id;
+    assert!(command(&mut client, alice, "/close").is_empty());
+    assert_eq!(names(&client), vec!["server", "#yew", "#secret"]);
+}
+
+#[test]
+fn test_command_channel_operations() {
+    let (mut client, id) = with_channel();
+    assert_eq!(
+        command(&mut client, id, "/kick bob bye"),
+        vec!["KICK #rust bob bye"]
+    );
+    assert_eq!(
+        command(&mut client, id, "/ban bob"),
+        vec!["MODE #rust +b bob!*@*"]
+    );
+    assert_eq!(
+        command(&mut client, id, "/topic New topic"),
+        vec!["TOPIC #rust :New topic"]
+    );
+    assert_eq!(
+        command(&mut client, id, "/invite alice"),
+        vec!["INVITE alice #rust"]
+    );
+    assert_eq!(command(&mut client, id, "/part"), vec!["PART #rust"]);
+}
+
+#[test]
+fn test_command_errors() {
+    let mut client = registered(Config::new("dan"));
+    let server = buffer(&client, "server").id;
+    assert!(command(&mut client, server, "hello").is_empty());
+    assert!(command(&mut client, server, "/kick bob").is_empty());
+    assert!(command(&mut client, server, "/foo").is_empty());
+
+    assert_eq!(
+        lines(&client, "server")[1..],
+        [
+            (
+                LineKind::Error,
+                "Messages can not be sent to the server, use /msg or /quote".into()
+            ),
+            (
+                LineKind::Error,
+                "/kick only works in a channel or with a channel argument".into()
+            ),
+            (LineKind::Error, "Unknown command: /foo".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_command_splits_long_messages() {
+    let (mut client, id) = with_channel();
+    // `:dan!d@host PRIVMSG #rust :` and CRLF leave 483 bytes
+    let text = format!("{} {}", "a".repeat(481), "b".repeat(10));
+    assert_eq!(
+        command(&mut client, id, &text),
+        vec![
+            format!("PRIVMSG #rust :{} ", "a".repeat(481)),
+            format!("PRIVMSG #rust {}", "b".repeat(10)),
+        ]
+    );
+    // the buffer shows the whole text
+    assert_eq!(
+        lines(&client, "#rust").last(),
+        Some(&(LineKind::Message, text))
+    );
+
+    // a changed host leaves less space
+    handle(&mut client, &[":dan!d@host CHGHOST d longer.host"]);
+    assert_eq!(
+        command(&mut client, id, &"a".repeat(482))[0],
+        format!("PRIVMSG #rust {}", "a".repeat(476))
+    );
+}
+
+#[test]
+fn test_command_multiline() {
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+    handle(
+        &mut client,
+        &[
+            ":server CAP * LS :batch draft/multiline=max-bytes=4096,max-lines=10",
+            ":server CAP * ACK :batch draft/multiline",
+            ":server 001 dan :Welcome to the network dan!d@host",
+            ":dan!d@host JOIN #rust",
+        ],
+    );
+    let id = buffer(&client, "#rust").id;
+    assert_eq!(
+        command(&mut client, id, "one\ntwo"),
+        vec![
+            "BATCH +ml1 draft/multiline #rust",
+            "@batch=ml1 PRIVMSG #rust one",
+            "@batch=ml1 PRIVMSG #rust two",
+            "BATCH -ml1",
+        ]
+    );
+}
+
+#[test]
+fn test_ctcp_replies() {
+    let mut client = registered(Config::new("dan"));
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":alice!a@host PRIVMSG dan :\x01PING 1234\x01"]
+        ),
+        vec!["NOTICE alice :\x01PING 1234\x01"]
+    );
+    // queries do not open a query buffer
+    assert_eq!(
+        lines(&client, "server").last(),
+        Some(&(
+            LineKind::Ctcp {
+                command: "PING".into()
+            },
+            "1234".into()
+        ))
+    );
+    assert_eq!(names(&client), vec!["server"]);
+
+    // channel queries are answered privately, but not our own
+    assert_eq!(
+        handle(
+            &mut client,
+            &[":alice!a@host PRIVMSG #rust :\x01VERSION\x01"]
+        )
+        .len(),
+        1
+    );
+    assert!(handle(&mut client, &[":dan!d@host PRIVMSG alice :\x01VERSION\x01"]).is_empty());
+    assert!(handle(&mut client, &[":alice!a@host PRIVMSG dan :\x01FINGER\x01"]).is_empty());
+}
+
+#[test]
+fn test_ctcp_rate_limit() {
+    let mut client = registered(Config::new("dan"));
+    let ping = Message::parse(":alice!a@host PRIVMSG dan :\x01PING\x01").unwrap();
+    let replies = (0..5)
+        .map(|_| client.handle(&ping, Duration::from_secs(1)).unwrap().len())
+        .sum::<usize>();
+    assert_eq!(replies, 3);
+
+    // answered again after the period
+    assert_eq!(
+        client.handle(&ping, Duration::from_secs(11)).unwrap().len(),
+        1
+    );
+}
+
+#[test]
+fn test_ctcp_command() {
+    let (mut client, id) = with_channel();
+    assert_eq!(
+        command(&mut client, id, "/ctcp alice version"),
+        vec!["PRIVMSG alice \x01VERSION\x01"]
+    );
+    handle(
+        &mut client,
+        &[":alice!a@host NOTICE dan :\x01VERSION irc.rs 0.1.0\x01"],
+    );
+    assert_eq!(
+        lines(&client, "server").last(),
+        Some(&(
+            LineKind::CtcpReply {
+                command: "VERSION".into()
+            },
+            "irc.rs 0.1.0".into()
+        ))
+    );
+}
+
+#[test]
+fn test_highlights() {
+    let mut config = Config::new("dan");
+    config.highlights = vec![Highlight::Word("rust".into())];
+    let mut client = registered(config);
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #rust",
+            ":alice!a@host PRIVMSG #rust :hi dan",
+            ":alice!a@host PRIVMSG #rust :hi all",
+            ":alice!a@host PRIVMSG #rust :\x01ACTION likes Rust\x01",
+            ":alice!a@host NOTICE #rust :DAN: ping",
+            // our own messages never highlight
+            ":dan!d@host PRIVMSG #rust :I am dan",
+        ],
+    );
+
+    let highlights = buffer(&client, "#rust")
+        .lines
+        .iter()
+        .map(|line| line.highlight)
+        .collect::<Vec<_>>();
+    assert_eq!(highlights, vec![false, true, false, true, true, false]);
+    assert_eq!(buffer(&client, "#rust").highlights, 3);
+
+    client
+        .buffers
+        .iter_mut()
+        .for_each(|buffer| buffer.mark_read());
+    assert_eq!(buffer(&client, "#rust").highlights, 0);
+}
+
+#[test]
+fn test_unread_and_activity() {
+    let (mut client, id) = with_channel();
+    let server = buffer(&client, "server").id;
+    client.select(server);
+
+    handle(&mut client, &[":alice!a@host JOIN #rust"]);
+    assert_eq!(buffer(&client, "#rust").activity, Activity::Events);
+    assert_eq!(buffer(&client, "#rust").unread, 0);
+
+    handle(
+        &mut client,
+        &[
+            ":alice!a@host PRIVMSG #rust :hi",
+            ":alice!a@host PRIVMSG #rust :there",
+        ],
+    );
+    assert_eq!(buffer(&client, "#rust").activity, Activity::Messages);
+    assert_eq!(buffer(&client, "#rust").unread, 2);
+
+    handle(&mut client, &[":alice!a@host PRIVMSG #rust :dan?"]);
+    assert_eq!(buffer(&client, "#rust").activity, Activity::Highlight);
+
+    // selecting marks the buffer as read, as do new lines while selected
+    client.select(id);
+    assert_eq!(client.selected().map(|buffer| buffer.id), Some(id));
+    handle(&mut client, &[":alice!a@host PRIVMSG #rust :still here"]);
+    assert_eq!(buffer(&client, "#rust").unread, 0);
+    assert_eq!(buffer(&client, "#rust").activity, Activity::None);
+
+    // our own messages are not unread
+    command(&mut client, id, "/msg bob hello");
+    assert_eq!(buffer(&client, "bob").unread, 0);
+}
+
+#[test]
+fn test_selected_defaults_to_first() {
+    let (mut client, _) = with_channel();
+    assert_eq!(client.selected().map(|b| b.name.as_str()), Some("server"));
+
+    // unknown buffers can not be selected
+    client.select(42);
+    assert_eq!(client.selected().map(|b| b.name.as_str()), Some("server"));
+
+    command(&mut client, 0, "/query alice");
+    assert_eq!(client.selected().map(|b| b.name.as_str()), Some("alice"));
+}
+
+#[test]
+fn test_server_time() {
+    let line = "@time=2011-10-19T16:40:51.620Z :alice!a@host PRIVMSG dan :hi";
+    let now = Duration::from_secs(1700000000);
+
+    // the local time is used, unless `server-time` is enabled
+    let mut client = registered(Config::new("dan"));
+    handle_at(&mut client, line, now);
+    assert_eq!(buffer(&client, "alice").lines[0].time, now);
+
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+    handle(
+        &mut client,
+        &[
+            ":server CAP * LS :server-time",
+            ":server CAP * ACK :server-time",
+            ":server 001 dan :Welcome",
+        ],
+    );
+    handle_at(&mut client, line, now);
+    handle_at(&mut client, ":alice!a@host PRIVMSG dan :untagged", now);
+    let times = buffer(&client, "alice")
+        .lines
+        .iter()
+        .map(|line| line.time)
+        .collect::<Vec<_>>();
+    assert_eq!(times, vec![Duration::from_millis(1319042451620), now]);
+}
+
+/// Registered client with `draft/chathistory`, which requests at most
+/// `limit` messages
+fn with_chathistory(limit: usize) -> Client {
+    let mut client = Client::new(Config::new("dan"));
+    client.register();
+    let caps = "batch draft/chathistory message-tags server-time";
+    let responses = handle(
+        &mut client,
+        &[
+            &format!(":server CAP * LS :{caps}"),
+            &format!(":server CAP * ACK :{caps}"),
+            &format!(":server 005 dan CHATHISTORY={limit} :are supported"),
+        ],
+    );
+    assert_eq!(
+        responses,
+        vec![
+            "CAP REQ :batch draft/chathistory message-tags server-time",
+            "CAP END"
+        ]
+    );
+    assert_eq!(
+        handle(&mut client, &[":server 001 dan :Welcome"]),
+        vec![format!(
+            "CHATHISTORY TARGETS timestamp=1970-01-01T00:00:00.000Z timestamp=1970-01-01T00:00:00.000Z {limit}"
+        )]
+    );
+    client
+}
+
+fn messages(client: &Client, name: &str) -> Vec<String> {
+    lines(client, name)
+        .into_iter()
+        .filter(|(kind, _)| *kind == LineKind::Message)
+        .map(|(_, message)| message)
+        .collect()
+}
+
+#[test]
+fn test_chathistory_latest_on_join() {
+    let mut client = with_chathistory(50);
+    assert_eq!(
+        handle(
+            &mut client,
+            &["@time=2024-12-24T18:00:00.000Z :dan!d@host JOIN #rust"]
+        ),
+        vec!["CHATHISTORY LATEST #rust * 50"]
+    );
+
+    // messages which arrive meanwhile are not duplicated
+    handle(
+        &mut client,
+        &[
+            "@time=2024-12-24T18:00:01.000Z;msgid=b :alice!a@host PRIVMSG #rust :new",
+            ":server BATCH +h1 chathistory #rust",
+            "@batch=h1;time=2024-12-24T17:00:00.000Z;msgid=a :alice!a@host PRIVMSG #rust :old",
+            "@batch=h1;time=2024-12-24T18:00:01.000Z;msgid=b :alice!a@host PRIVMSG #rust :new",
+            ":server BATCH -h1",
+        ],
+    );
+    assert_eq!(messages(&client, "#rust"), vec!["old", "new"]);
+    assert_eq!(lines(&client, "#rust")[1].0, LineKind::Join);
+
+    // history is not unread, and there is no more of it
+    let rust = buffer(&client, "#rust");
+    assert_eq!(rust.unread, 1);
+    assert_eq!(rust.lines[0].msgid.as_deref(), Some("a"));
+    assert!(rust.history.complete);
+    let id = rust.id;
+    assert!(client.load_history(id).is_empty());
+}
+
+#[test]
+fn test_chathistory_history_is_not_handled() {
+    let mut client = with_chathistory(50);
+    handle(&mut client, &[":dan!d@host JOIN #rust"]);
+    let responses = handle(
+        &mut client,
+        &[
+            ":server BATCH +h1 chathistory dan",
+            "@batch=h1 :alice!a@host PRIVMSG dan :\x01VERSION\x01",
+            "@batch=h1 :alice!a@host PRIVMSG dan :hi dan",
+            ":server BATCH -h1",
+        ],
+    );
+    // old queries are not answered and old highlights do not count
+    assert!(responses.is_empty());
+    let alice = buffer(&client, "alice");
+    assert_eq!(alice.highlights, 0);
+    assert!(alice.lines.last().unwrap().highlight);
+}
+
+#[test]
+fn test_chathistory_before_on_scroll() {
+    let mut client = with_chathistory(2);
+    handle(
+        &mut client,
+        &["@time=2024-12-24T18:00:00.000Z :dan!d@host JOIN #rust"],
+    );
+    handle(
+        &mut client,
+        &[
+            ":server BATCH +h1 chathistory #rust",
+            "@batch=h1;time=2024-12-24T17:00:00.000Z;msgid=a :alice!a@host PRIVMSG #rust :one",
+            "@batch=h1;time=2024-12-24T17:00:01.000Z;msgid=b :alice!a@host PRIVMSG #rust :two",
+            ":server BATCH -h1",
+        ],
+    );
+    let id = buffer(&client, "#rust").id;
+    assert!(!buffer(&client, "#rust").history.complete);
+
+    assert_eq!(
+        to_strings(client.load_history(id)),
+        vec!["CHATHISTORY BEFORE #rust msgid=a 2"]
+    );
+    // only one request at a time
+    assert!(client.load_history(id).is_empty());
+
+    handle(
+        &mut client,
+        &[
+            ":server BATCH +h2 chathistory #rust",
+            "@batch=h2;time=2024-12-24T16:00:00.000Z;msgid=z :alice!a@host PRIVMSG #rust :zero",
+            ":server BATCH -h2",
+        ],
+    );
+    assert_eq!(messages(&client, "#rust"), vec!["zero", "one", "two"]);
+    assert!(buffer(&client, "#rust").history.complete);
+}
+
+#[test]
+fn test_chathistory_fail_allows_new_requests() {
+    let mut client = with_chathistory(50);
+    handle(&mut client, &[":dan!d@host JOIN #rust"]);
+    let id = buffer(&client, "#rust").id;
+    assert!(client.load_history(id).is_empty());
+
+    handle(
+        &mut client,
+        &[":server FAIL CHATHISTORY MESSAGE_ERROR LATEST #rust :Messages could not be retrieved"],
+    );
+    assert_eq!(client.load_history(id).len(), 1);
+}
+
+#[test]
+fn test_chathistory_between_after_reconnect() {
+    let mut client = with_chathistory(50);
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #rust",
+            ":server BATCH +h1 chathistory #rust",
+            ":server BATCH -h1",
+            "@time=2024-12-24T18:00:00.000Z;msgid=a :alice!a@host PRIVMSG #rust :before",
+        ],
+    );
+
+    client.disconnected();
+    client.register();
+    handle(
+        &mut client,
+        &[
+            ":server CAP * LS :batch draft/chathistory server-time",
+            ":server CAP * ACK :batch draft/chathistory server-time",
+            ":server 001 dan :Welcome",
+        ],
+    );
+    assert_eq!(
+        handle(
+            &mut client,
+            &["@time=2024-12-24T19:00:00.000Z :dan!d@host JOIN #rust"]
+        ),
+        vec!["CHATHISTORY BETWEEN #rust msgid=a timestamp=2024-12-24T19:00:00.000Z 50"]
+    );
+}
+
+#[test]
+fn test_chathistory_between_after_sending() {
+    let mut client = with_chathistory(50);
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #rust",
+            ":server BATCH +h1 chathistory #rust",
+            ":server BATCH -h1",
+            "@time=2024-12-24T18:00:00.000Z;msgid=a :alice!a@host PRIVMSG #rust :before",
+        ],
+    );
+    let id = buffer(&client, "#rust").id;
+    let sent = Duration::from_secs(1735065000);
+    assert_eq!(
+        to_strings(client.command(id, "mine", sent)),
+        vec!["PRIVMSG #rust mine"]
+    );
+
+    client.disconnected();
+    client.register();
+    handle(
+        &mut client,
+        &[
+            ":server CAP * LS :batch draft/chathistory message-tags server-time",
+            ":server CAP * ACK :batch draft/chathistory message-tags server-time",
+            ":server 001 dan :Welcome",
+            "@time=2024-12-24T19:00:00.000Z :dan!d@host JOIN #rust",
+            ":server BATCH +h2 chathistory #rust",
+            "@batch=h2;time=2024-12-24T18:30:00.250Z;msgid=b :dan!d@host PRIVMSG #rust :mine",
+            "@batch=h2;time=2024-12-24T18:40:00.000Z;msgid=c :alice!a@host PRIVMSG #rust :after",
+            ":server BATCH -h2",
+        ],
+    );
+    // our own message is known from its echo
+    assert_eq!(messages(&client, "#rust"), vec!["before", "mine", "after"]);
+    let rust = buffer(&client, "#rust");
+    let mine = rust.lines.iter().find(|line| line.message == "mine");
+    assert_eq!(mine.unwrap().msgid.as_deref(), Some("b"));
+}
+
+#[test]
+fn test_tagged_message_at_trim() {
+    let mut client = with_chathistory(50);
+    handle(&mut client, &[":dan!d@host JOIN #rust"]);
+    let rust = client
+        .buffers
+        .iter_mut()
+        .find(|buffer| buffer.name.as_str() == "#rust")
+        .unwrap();
+    while rust.lines.len() < MAX_LINES + MAX_LINES / 10 {
+        rust.push(None, LineKind::Message, "old".into(), Duration::ZERO);
+    }
+
+    handle(
+        &mut client,
+        &["@time=2024-12-24T18:00:00.000Z;msgid=a :alice!a@host PRIVMSG #rust :new"],
+    );
+    let rust = buffer(&client, "#rust");
+    assert_eq!(rust.lines.len(), MAX_LINES);
+    let last = rust.lines.last().unwrap();
+    assert_eq!(last.message, "new");
+    assert_eq!(last.msgid.as_deref(), Some("a"));
+}
+
+#[test]
+fn test_chathistory_targets() {
+    let mut client = with_chathistory(50);
+    assert_eq!(
+        handle(
+            &mut client,
+            &[
+                ":server BATCH +t1 draft/chathistory-targets",
+                "@batch=t1 :server CHATHISTORY TARGETS #rust 2024-12-24T18:00:00.000Z",
+                "@batch=t1 :server CHATHISTORY TARGETS alice 2024-12-24T17:00:00.000Z",
+                ":server BATCH -t1",
+            ]
+        ),
+        vec!["CHATHISTORY LATEST alice * 50"]
+    );
+    // channels are only shown once joined
+    assert_eq!(names(&client), vec!["server", "alice"]);
+}
+
+#[test]
+fn test_netsplit_batch() {
+    let (mut client, _) = with_channel();
+    handle(
+        &mut client,
+        &[
+            ":alice!a@host JOIN #rust",
+            ":bob!b@host JOIN #rust",
+            ":server BATCH +s netsplit irc.a irc.b",
+            "@batch=s :alice!a@host QUIT :irc.a irc.b",
+            "@batch=s :bob!b@host QUIT :irc.a irc.b",
+        ],
+    );
+    // nothing happens until the batch ends
+    assert_eq!(members(&client, "#rust"), vec!["alice", "bob", "dan"]);
+
+    handle(&mut client, &[":server BATCH -s"]);
+    assert_eq!(members(&client, "#rust"), vec!["dan"]);
+    assert_eq!(
+        lines(&client, "#rust").last(),
+        Some(&(
+            LineKind::Netsplit {
+                nicks: vec!["alice".into(), "bob".into()]
+            },
+            "irc.a irc.b".into()
+        ))
+    );
+
+    handle(
+        &mut client,
+        &[
+            ":server BATCH +j netjoin irc.a irc.b",
+            "@batch=j :alice!a@host JOIN #rust",
+            ":server BATCH -j",
+        ],
+    );
+    assert_eq!(members(&client, "#rust"), vec!["alice", "dan"]);
+    assert_eq!(
+        lines(&client, "#rust").last(),
+        Some(&(
+            LineKind::Netjoin {
+                nicks: vec!["alice".into()]
+            },
+            "irc.a irc.b".into()
+        ))
+    );
+}
+
+#[test]
+fn test_multiline_batch() {
+    let (mut client, _) = with_channel();
+    handle(
+        &mut client,
+        &[
+            "@msgid=m :alice!a@host BATCH +ml draft/multiline #rust",
+            "@batch=ml :alice!a@host PRIVMSG #rust :first line",
+            "@batch=ml :alice!a@host PRIVMSG #rust :second ",
+            "@batch=ml;draft/multiline-concat :alice!a@host PRIVMSG #rust line",
+            ":alice!a@host BATCH -ml",
+        ],
+    );
+    assert_eq!(messages(&client, "#rust"), vec!["first line\nsecond line"]);
+    assert_eq!(
+        buffer(&client, "#rust")
+            .lines
+            .last()
+            .unwrap()
+            .msgid
+            .as_deref(),
+        Some("m")
+    );
+}
+
+#[test]
+fn test_unknown_batch_is_replayed() {
+    let (mut client, _) = with_channel();
+    let responses = handle(
+        &mut client,
+        &[
+            ":server BATCH +l labeled-response",
+            "@batch=l :server BATCH +x example.com/other",
+            "@batch=x PING :token",
+            "@batch=l :alice!a@host PRIVMSG #rust :hi",
+            ":server BATCH -x",
+        ],
+    );
+    assert!(responses.is_empty());
+    assert_eq!(
+        handle(&mut client, &[":server BATCH -l"]),
+        vec!["PONG token"]
+    );
+    assert_eq!(messages(&client, "#rust"), vec!["hi"]);
+}
+
+#[test]
+fn test_batch_timeout() {
+    let (mut client, _) = with_channel();
+    handle_at(
+        &mut client,
+        ":server BATCH +x example.com/other",
+        Duration::ZERO,
+    );
+    handle_at(
+        &mut client,
+        "@batch=x :alice!a@host PRIVMSG #rust :hi",
+        Duration::ZERO,
+    );
+
+    client.tick(Duration::from_secs(59)).unwrap();
+    assert!(messages(&client, "#rust").is_empty());
+    client.tick(Duration::from_secs(60)).unwrap();
+    assert_eq!(messages(&client, "#rust"), vec!["hi"]);
+}
+
+#[test]
+fn test_batch_flushed_on_disconnect() {
+    let mut client = with_chathistory(50);
+    handle(
+        &mut client,
+        &[
+            ":dan!d@host JOIN #rust",
+            ":server BATCH +h1 chathistory #rust",
+            "@batch=h1;msgid=a :alice!a@host PRIVMSG #rust :old",
+        ],
+    );
+    client.disconnected();
+
+    // the incomplete history is kept, but may be requested again
+    let rust = buffer(&client, "#rust");
+    assert_eq!(messages(&client, "#rust"), vec!["old"]);
+    assert_eq!(rust.history.pending, None);
+    assert!(!rust.history.complete);
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+#[cfg(test)]
+mod test;
+
+const SECS_PER_DAY: u64 = 86400;
+
+/// Parse an ISO-8601 time in UTC, with optional milliseconds, e.g. from the
+/// `time` tag of `server-time`: `2011-10-19T16:40:51.620Z`. Times are
+/// durations since the UNIX epoch.
+pub fn parse(value: &str) -> Option<Duration> {
+    let value = value.strip_suffix('Z')?;
+    let (date, time) = value.split_once('T')?;
+
+    let mut date = date.split('-').map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    // four digit years keep the day and second counts far from overflowing
+    if date.next().is_some()
+        || year > 9999
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+    {
+        return None;
+    }
+
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if time.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // fractions are given with any number of digits, keep milliseconds
+    let millis = match fraction {
+        "" => 0,
+        digits if digits.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{digits:0<3}")[..3].parse::<u64>().ok()?
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year, month, day)?;
+    let secs = days * SECS_PER_DAY + hour * 3600 + minute * 60 + second;
+    Some(Duration::from_secs(secs) + Duration::from_millis(millis))
+}
+
+/// Time in UTC, e.g. `2024-12-24T18:30:00Z`
+pub fn to_iso(time: Duration) -> String {
+    format(time, 0, "%Y-%m-%dT%H:%M:%SZ")
+}
+
+/// Time in UTC with milliseconds, e.g. `2024-12-24T18:30:00.250Z`
+pub fn to_iso_millis(time: Duration) -> String {
+    let secs = format(time, 0, "%Y-%m-%dT%H:%M:%S");
+    format!("{secs}.{:03}Z", time.subsec_millis())
+}
+
+/// Format a time with the local offset to UTC in minutes, e.g. `120` for
+/// UTC+2. The format supports `%Y`, `%m`, `%d`, `%H`, `%M`, `%S` and `%%`.
+pub fn format(time: Duration, utc_offset: i32, format: &str) -> String {
+    let secs = local_secs(time, utc_offset);
+    let (year, month, day) = civil_from_days(secs / SECS_PER_DAY);
+    let secs = secs % SECS_PER_DAY;
+
+    let mut result = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => result.push_str(&format!("{year:04}")),
+            Some('m') => result.push_str(&format!("{month:02}")),
+            Some('d') => result.push_str(&format!("{day:02}")),
+            Some('H') => result.push_str(&format!("{:02}", secs / 3600)),
+            Some('M') => result.push_str(&format!("{:02}", secs / 60 % 60)),
+            Some('S') => result.push_str(&format!("{:02}", secs % 60)),
+            Some('%') => result.push('%'),
+            Some(other) => {
+                result.push('%');
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+/// Local day of a time, to detect day changes between lines
+pub fn day(time: Duration, utc_offset: i32) -> u64 {
+    local_secs(time, utc_offset) / SECS_PER_DAY
+}
+
+fn local_secs(time: Duration, utc_offset: i32) -> u64 {
+    time.as_secs()
+        .saturating_add_signed(i64::from(utc_offset) * 60)
+}
+
+// civil dates from days and back, see
+// http://howardhinnant.github.io/date_algorithms.html
+
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `None` for dates before the UNIX epoch
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    let year = if month <= 2 {
+        year.checked_sub(1)?
+    } else {
+        year
+    };
+    let era = year / 400;
+    let yoe = year % 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    (era * 146097 + doe).checked_sub(719468)
+}
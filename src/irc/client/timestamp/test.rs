@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use super::{day, format, parse, to_iso, to_iso_millis};
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse("2011-10-19T16:40:51.620Z"),
+        Some(Duration::from_millis(1319042451620))
+    );
+    assert_eq!(parse("1970-01-01T00:00:00Z"), Some(Duration::ZERO));
+    // any number of fraction digits
+    assert_eq!(
+        parse("2000-02-29T00:00:00.5Z"),
+        Some(Duration::from_millis(951782400500))
+    );
+    assert_eq!(
+        parse("2000-02-29T00:00:00.123456Z"),
+        Some(Duration::from_millis(951782400123))
+    );
+}
+
+#[test]
+fn test_parse_invalid() {
+    assert_eq!(parse("2011-10-19T16:40:51"), None);
+    assert_eq!(parse("2011-10-19 16:40:51Z"), None);
+    assert_eq!(parse("2011-13-19T16:40:51Z"), None);
+    assert_eq!(parse("2011-10-19T24:00:00Z"), None);
+    assert_eq!(parse("2011-10-19T16:40:51.x2Z"), None);
+    assert_eq!(parse("1969-12-31T23:59:59Z"), None);
+    assert_eq!(parse("99999999999999999-01-01T00:00:00Z"), None);
+    assert_eq!(parse("10000-01-01T00:00:00Z"), None);
+    assert!(parse("9999-12-31T23:59:59Z").is_some());
+}
+
+#[test]
+fn test_format() {
+    let time = Duration::from_millis(1319042451620);
+    assert_eq!(to_iso(time), "2011-10-19T16:40:51Z");
+    assert_eq!(format(time, 0, "[%H:%M:%S]"), "[16:40:51]");
+    // local time of UTC+2 and UTC-8
+    assert_eq!(format(time, 120, "%d.%m.%Y %H:%M"), "19.10.2011 18:40");
+    assert_eq!(format(time, -480, "%H:%M 100%%"), "08:40 100%");
+    assert_eq!(format(Duration::ZERO, 0, "%Y-%m-%d"), "1970-01-01");
+}
+
+#[test]
+fn test_day() {
+    let time = parse("2011-10-19T23:30:00Z").unwrap();
+    assert_eq!(day(time, 0) + 1, day(time, 60));
+    assert_eq!(day(time, 0), day(time, -60));
+}
+
+#[test]
+fn test_to_iso_millis() {
+    assert_eq!(
+        to_iso_millis(Duration::from_millis(1319042451620)),
+        "2011-10-19T16:40:51.620Z"
+    );
+    assert_eq!(to_iso_millis(Duration::ZERO), "1970-01-01T00:00:00.000Z");
+}
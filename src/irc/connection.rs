@@ -0,0 +1,205 @@
+use std::{fmt::Debug, rc::Rc, time::Duration};
+
+use yew::AttrValue;
+
+use super::{
+    client::{backoff::Backoff, Client},
+    parser::{message::Message, typed_message::TypedMessage},
+    transport::{self, Event, Transport},
+};
+
+#[cfg(test)]
+mod test;
+
+/// Opens a new transport. Its events have to be passed to
+/// `Connection::event`.
+pub type Connect = Rc<dyn Fn() -> Result<Box<dyn Transport>, transport::Error>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    /// Not connected, and no reconnect is scheduled
+    Disconnected,
+    /// Waiting for the transport to open
+    Connecting,
+    /// Transport is open, waiting for the server to accept the registration
+    Registering,
+    Connected,
+    /// Connection was lost, the next attempt is made at `at`
+    Reconnecting {
+        attempt: u32,
+        at: Duration,
+    },
+}
+
+/// Drives a `Client` over a `Transport`, and reconnects whenever the
+/// connection is lost
+pub struct Connection {
+    pub client: Client,
+    pub backoff: Backoff,
+    connect: Connect,
+    transport: Option<Box<dyn Transport>>,
+    status: Status,
+    last_error: Option<AttrValue>,
+}
+
+impl Debug for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Connection")
+            .field("client", &self.client)
+            .field("status", &self.status)
+            .field("last_error", &self.last_error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Connection {
+    pub fn new(client: Client, connect: Connect) -> Self {
+        Connection {
+            client,
+            backoff: Backoff::new(Duration::from_secs(1), Duration::from_secs(300)),
+            connect,
+            transport: None,
+            status: Status::Disconnected,
+            last_error: None,
+        }
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Last error of the transport or the client, e.g. for display
+    pub fn last_error(&self) -> Option<&AttrValue> {
+        self.last_error.as_ref()
+    }
+
+    /// Open a new connection, replacing the current one
+    pub fn connect(&mut self, now: Duration) {
+        self.close_transport();
+
+        match (self.connect)() {
+            Ok(transport) => {
+                self.transport = Some(transport);
+                self.status = Status::Connecting;
+            }
+            Err(err) => {
+                self.last_error = Some(format!("{err:?}").into());
+                self.schedule_reconnect(now);
+            }
+        }
+    }
+
+    /// Close the connection on purpose, no reconnect is attempted
+    pub fn disconnect(&mut self, reason: Option<&str>) {
+        self.send(
+            &Message::typed(TypedMessage::Quit {
+                reason: reason.map(|reason| reason.to_owned().into()),
+            })
+            .build(),
+        );
+        self.close_transport();
+        self.status = Status::Disconnected;
+    }
+
+    pub fn send(&mut self, message: &Message) {
+        let Some(transport) = &self.transport else {
+            return;
+        };
+        if let Err(err) = transport.send_message(message) {
+            self.last_error = Some(format!("{err:?}").into());
+        }
+    }
+
+    /// Handle the input of a buffer, see `Client::command`. `/quit` closes
+    /// the connection like `disconnect`, so it is not reconnected.
+    pub fn command(&mut self, buffer_id: u64, input: &str, now: Duration) {
+        for message in self.client.command(buffer_id, input, now) {
+            if let Ok(TypedMessage::Quit { reason }) = message.to_typed() {
+                self.disconnect(reason.as_deref());
+                return;
+            }
+            self.send(&message);
+        }
+    }
+
+    /// Request older messages of a buffer, see `Client::load_history`
+    pub fn load_history(&mut self, buffer_id: u64) {
+        for message in self.client.load_history(buffer_id) {
+            self.send(&message);
+        }
+    }
+
+    /// Handle an event of the current transport
+    pub fn event(&mut self, event: Event, now: Duration) {
+        // events of transports which have been closed already are ignored
+        if self.transport.is_none() {
+            return;
+        }
+
+        match event {
+            Event::Open => {
+                self.status = Status::Registering;
+                for message in self.client.register() {
+                    self.send(&message);
+                }
+            }
+            Event::Line(line) => self.line(&line, now),
+            Event::Error(err) => self.last_error = Some(err),
+            Event::Closed { .. } => {
+                self.close_transport();
+                self.schedule_reconnect(now);
+            }
+        }
+    }
+
+    /// Timers of the connection, should be called regularly, e.g. every second
+    pub fn tick(&mut self, now: Duration) {
+        match self.status {
+            Status::Reconnecting { at, .. } if at <= now => self.connect(now),
+            Status::Registering | Status::Connected => match self.client.tick(now) {
+                Ok(messages) => messages.iter().for_each(|message| self.send(message)),
+                Err(err) => {
+                    self.last_error = Some(format!("{err:?}").into());
+                    self.close_transport();
+                    self.schedule_reconnect(now);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn line(&mut self, line: &str, now: Duration) {
+        let message = match Message::parse(line) {
+            Ok(message) => message,
+            Err(err) => {
+                self.last_error = Some(err.to_string().into());
+                return;
+            }
+        };
+
+        match self.client.handle(&message, now) {
+            Ok(messages) => messages.iter().for_each(|message| self.send(message)),
+            Err(err) => self.last_error = Some(format!("{err:?}").into()),
+        }
+
+        if self.status == Status::Registering && self.client.is_registered() {
+            self.status = Status::Connected;
+            self.backoff.reset();
+        }
+    }
+
+    fn close_transport(&mut self) {
+        if let Some(transport) = self.transport.take() {
+            transport.close();
+        }
+        self.client.disconnected();
+    }
+
+    fn schedule_reconnect(&mut self, now: Duration) {
+        let delay = self.backoff.next_delay();
+        self.status = Status::Reconnecting {
+            attempt: self.backoff.attempt(),
+            at: now + delay,
+        };
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use yew::{AttrValue, Callback};
+
+use crate::irc::{
+    client::{Client, Config},
+    transport::{self, memory::MemoryTransport, Event, Transport},
+};
+
+use super::{Connection, Status};
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+/// Connection with in-memory transports, which simulates the server side
+struct Harness {
+    connection: Connection,
+    events: Rc<RefCell<Vec<Event>>>,
+    transports: Rc<RefCell<Vec<MemoryTransport>>>,
+    /// Simulate failures to open a transport
+    unreachable: Rc<Cell<bool>>,
+}
+
+impl Harness {
+    fn new(client: Client) -> Self {
+        let events = Rc::new(RefCell::new(vec![]));
+        let transports = Rc::new(RefCell::new(vec![]));
+        let unreachable = Rc::new(Cell::new(false));
+
+        let connect = Rc::new({
+            let events = events.clone();
+            let transports = transports.clone();
+            let unreachable = unreachable.clone();
+            move || {
+                if unreachable.get() {
+                    return Err(transport::Error::Connection("unreachable".into()));
+                }
+                let events = events.clone();
+                let transport =
+                    MemoryTransport::new(Callback::from(move |e| events.borrow_mut().push(e)));
+                transports.borrow_mut().push(transport.clone());
+                Ok(Box::new(transport) as Box<dyn Transport>)
+            }
+        });
+
+        let mut connection = Connection::new(client, connect);
+        connection.backoff.initial = secs(1);
+        connection.backoff.max = secs(8);
+
+        Harness {
+            connection,
+            events,
+            transports,
+            unreachable,
+        }
+    }
+
+    /// Deliver all pending transport events
+    fn pump(&mut self, now: Duration) {
+        loop {
+            let events = std::mem::take(&mut *self.events.borrow_mut());
+            if events.is_empty() {
+                break;
+            }
+            for event in events {
+                self.connection.event(event, now);
+            }
+        }
+    }
+
+    fn server(&self) -> MemoryTransport {
+        self.transports.borrow().last().unwrap().clone()
+    }
+
+    fn open(&mut self, now: Duration) -> Vec<AttrValue> {
+        self.server().open();
+        self.pump(now);
+        self.server().take_sent()
+    }
+
+    fn receive(&mut self, lines: &[&str], now: Duration) -> Vec<AttrValue> {
+        for line in lines {
+            self.server().receive(line);
+        }
+        self.pump(now);
+        self.server().take_sent()
+    }
+
+    fn tick(&mut self, now: Duration) {
+        self.connection.tick(now);
+        self.pump(now);
+    }
+
+    fn register(&mut self, now: Duration) -> Vec<AttrValue> {
+        self.open(now);
+        self.receive(&[":server CAP * LS :", ":server 001 dan :Welcome"], now)
+    }
+}
+
+fn client() -> Client {
+    let mut client = Client::new(Config::new("dan"));
+    client.join("#rust", None).unwrap();
+    client.join("#secret", Some("key")).unwrap();
+    client.join("#yew", None).unwrap();
+    client
+}
+
+#[test]
+fn test_connect_and_register() {
+    let mut h = Harness::new(Client::new(Config::new("dan")));
+    assert_eq!(h.connection.status(), &Status::Disconnected);
+
+    h.connection.connect(secs(0));
+    assert_eq!(h.connection.status(), &Status::Connecting);
+
+    assert_eq!(
+        h.open(secs(0)),
+        vec![
+            AttrValue::from("CAP LS 302"),
+            AttrValue::from("NICK dan"),
+            AttrValue::from("USER dan 0 * dan")
+        ]
+    );
+    assert_eq!(h.connection.status(), &Status::Registering);
+
+    h.receive(&[":server CAP * LS :"], secs(0));
+    h.receive(&[":server 001 dan :Welcome"], secs(0));
+    assert_eq!(h.connection.status(), &Status::Connected);
+}
+
+#[test]
+fn test_rejoin_after_reconnect() {
+    let mut h = Harness::new(client());
+    h.connection.connect(secs(0));
+    assert_eq!(
+        h.register(secs(0)),
+        vec![
+            AttrValue::from("CAP END"),
+            "JOIN #secret,#rust,#yew key".into()
+        ]
+    );
+
+    h.server().fail("connection reset");
+    h.pump(secs(10));
+    assert_eq!(h.connection.last_error(), Some(&"connection reset".into()));
+    assert!(!h.connection.client.is_registered());
+    let Status::Reconnecting { attempt: 1, at } = *h.connection.status() else {
+        panic!("{:?}", h.connection.status());
+    };
+    assert!(secs(10) < at && at <= secs(11), "{at:?}");
+
+    // no new transport before the delay passed
+    h.tick(secs(10));
+    assert_eq!(h.transports.borrow().len(), 1);
+
+    h.tick(secs(11));
+    assert_eq!(h.transports.borrow().len(), 2);
+    assert_eq!(h.connection.status(), &Status::Connecting);
+
+    assert_eq!(
+        h.register(secs(11)),
+        vec![
+            AttrValue::from("CAP END"),
+            "JOIN #secret,#rust,#yew key".into()
+        ]
+    );
+    assert_eq!(h.connection.status(), &Status::Connected);
+}
+
+#[test]
+fn test_backoff() {
+    let mut h = Harness::new(client());
+    h.unreachable.set(true);
+    h.connection.connect(secs(0));
+
+    let mut now = secs(0);
+    let mut delays = vec![];
+    for _ in 0..5 {
+        let Status::Reconnecting { at, .. } = *h.connection.status() else {
+            panic!("{:?}", h.connection.status());
+        };
+        delays.push(at - now);
+        now = at;
+        h.tick(now);
+    }
+
+    // delays are between half and the full exponential delay
+    for (delay, max) in delays.iter().zip([1, 2, 4, 8, 8]) {
+        assert!(secs(max) / 2 <= *delay && *delay <= secs(max), "{delays:?}");
+    }
+
+    // successful registration resets the delay
+    h.unreachable.set(false);
+    let Status::Reconnecting { at, .. } = *h.connection.status() else {
+        panic!("{:?}", h.connection.status());
+    };
+    h.tick(at);
+    h.register(at);
+    assert_eq!(h.connection.backoff.attempt(), 0);
+}
+
+#[test]
+fn test_disconnect() {
+    let mut h = Harness::new(client());
+    h.connection.connect(secs(0));
+    h.register(secs(0));
+
+    let server = h.server();
+    h.connection.disconnect(Some("bye"));
+    assert_eq!(server.take_sent(), vec![AttrValue::from("QUIT bye")]);
+    assert_eq!(server.state(), transport::State::Closed);
+
+    // the close event does not trigger a reconnect
+    h.pump(secs(1));
+    h.tick(secs(1000));
+    assert_eq!(h.connection.status(), &Status::Disconnected);
+    assert_eq!(h.transports.borrow().len(), 1);
+}
+
+#[test]
+fn test_ping_timeout() {
+    let mut h = Harness::new(client());
+    h.connection.connect(secs(0));
+    h.register(secs(0));
+
+    h.tick(secs(60));
+    assert_eq!(
+        h.server().take_sent(),
+        vec![AttrValue::from("PING irc.rs-60000")]
+    );
+
+    h.tick(secs(90));
+    assert!(matches!(
+        h.connection.status(),
+        Status::Reconnecting { attempt: 1, .. }
+    ));
+    assert_eq!(h.server().state(), transport::State::Closed);
+}
+
+#[test]
+fn test_command() {
+    let mut h = Harness::new(client());
+    h.connection.connect(secs(0));
+    h.register(secs(0));
+
+    let id = h
+        .connection
+        .client
+        .buffers
+        .iter()
+        .find(|buffer| buffer.name.as_str() == "#rust")
+        .unwrap()
+        .id;
+    h.connection.command(id, "hello", Duration::ZERO);
+    assert_eq!(
+        h.server().take_sent(),
+        vec![AttrValue::from("PRIVMSG #rust hello")]
+    );
+}
+
+#[test]
+fn test_quit_command() {
+    let mut h = Harness::new(client());
+    h.connection.connect(secs(0));
+    h.register(secs(0));
+
+    let server = h.server();
+    let id = h.connection.client.buffers[0].id;
+    h.connection.command(id, "/quit bye", secs(1));
+    assert_eq!(server.take_sent(), vec![AttrValue::from("QUIT bye")]);
+
+    // the server closing the connection does not trigger a reconnect
+    server.fail("closed");
+    h.pump(secs(1));
+    h.tick(secs(1000));
+    assert_eq!(h.connection.status(), &Status::Disconnected);
+    assert_eq!(h.transports.borrow().len(), 1);
+}
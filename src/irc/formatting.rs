@@ -0,0 +1,160 @@
+use yew::AttrValue;
+
+#[cfg(test)]
+mod test;
+
+pub const BOLD: char = '\x02';
+pub const COLOR: char = '\x03';
+pub const HEX_COLOR: char = '\x04';
+pub const RESET: char = '\x0f';
+pub const MONOSPACE: char = '\x11';
+pub const REVERSE: char = '\x16';
+pub const ITALIC: char = '\x1d';
+pub const STRIKETHROUGH: char = '\x1e';
+pub const UNDERLINE: char = '\x1f';
+
+/// Colour code `99`, which is the default colour
+const DEFAULT_COLOR: u8 = 99;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    /// One of the 99 colours, `0` to `98`
+    Palette(u8),
+    /// Hex colour of `\x04`
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub monospace: bool,
+    /// Swap foreground and background colour
+    pub reverse: bool,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+/// Text with the same style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub style: Style,
+    pub text: AttrValue,
+}
+
+/// Split a text into styled spans, without the mIRC formatting codes, e.g.
+/// `\x02bold\x02` or `\x0304,01red on black`, following:
+///
+/// https://modern.ircdocs.horse/formatting
+pub fn parse(text: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut style = Style::default();
+    let mut current = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        let previous = style;
+        match c {
+            BOLD => style.bold = !style.bold,
+            ITALIC => style.italic = !style.italic,
+            UNDERLINE => style.underline = !style.underline,
+            STRIKETHROUGH => style.strikethrough = !style.strikethrough,
+            MONOSPACE => style.monospace = !style.monospace,
+            REVERSE => style.reverse = !style.reverse,
+            RESET => style = Style::default(),
+            COLOR => set_colors(&mut style, &mut rest, palette_color),
+            HEX_COLOR => set_colors(&mut style, &mut rest, hex_color),
+            c => {
+                current.push(c);
+                continue;
+            }
+        }
+
+        if style != previous && !current.is_empty() {
+            spans.push(Span {
+                style: previous,
+                text: std::mem::take(&mut current).into(),
+            });
+        }
+    }
+
+    if !current.is_empty() {
+        spans.push(Span {
+            style,
+            text: current.into(),
+        });
+    }
+    spans
+}
+
+/// Text without any formatting codes
+pub fn strip_formatting(text: &str) -> String {
+    parse(text).iter().map(|span| span.text.as_str()).collect()
+}
+
+/// Formatting code inserted with `Ctrl` and a key in the input, e.g. `Ctrl+B`
+pub fn shortcut(key: &str) -> Option<char> {
+    match key.to_ascii_lowercase().as_str() {
+        "b" => Some(BOLD),
+        "i" => Some(ITALIC),
+        "u" => Some(UNDERLINE),
+        "s" => Some(STRIKETHROUGH),
+        "m" => Some(MONOSPACE),
+        "k" => Some(COLOR),
+        "o" => Some(RESET),
+        _ => None,
+    }
+}
+
+/// Colour of a code, `Some(None)` for the default colour
+type ParseColor = fn(&mut &str) -> Option<Option<Color>>;
+
+/// Apply the foreground and optional background colour after a colour code.
+/// Without any colour, the colours are reset.
+fn set_colors(style: &mut Style, rest: &mut &str, color: ParseColor) {
+    let Some(foreground) = color(rest) else {
+        style.foreground = None;
+        style.background = None;
+        return;
+    };
+    style.foreground = foreground;
+
+    // the comma is part of the text, if no colour follows
+    if let Some(mut after) = rest.strip_prefix(',') {
+        if let Some(background) = color(&mut after) {
+            style.background = background;
+            *rest = after;
+        }
+    }
+}
+
+/// Colour of one or two digits, e.g. `4` or `04`
+fn palette_color(rest: &mut &str) -> Option<Option<Color>> {
+    let digits = rest.bytes().take(2).take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let (code, after) = rest.split_at(digits);
+    *rest = after;
+    match code.parse().ok()? {
+        DEFAULT_COLOR => Some(None),
+        code => Some(Some(Color::Palette(code))),
+    }
+}
+
+/// Colour of six hex digits, e.g. `FF0000`
+fn hex_color(rest: &mut &str) -> Option<Option<Color>> {
+    let hex = rest
+        .get(..6)
+        .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    *rest = &rest[6..];
+    Some(Some(Color::Rgb(
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    )))
+}
@@ -0,0 +1,122 @@
+use super::{parse, shortcut, strip_formatting, Color, Span, Style, BOLD};
+
+fn span(text: &str, style: Style) -> Span {
+    Span {
+        style,
+        text: text.to_owned().into(),
+    }
+}
+
+#[test]
+fn test_parse_plain() {
+    assert_eq!(parse("hello"), vec![span("hello", Style::default())]);
+    assert_eq!(parse(""), vec![]);
+}
+
+#[test]
+fn test_parse_toggles() {
+    let bold = Style {
+        bold: true,
+        ..Style::default()
+    };
+    let bold_italic = Style {
+        italic: true,
+        ..bold
+    };
+    assert_eq!(
+        parse("a\x02b\x1dc\x0fd"),
+        vec![
+            span("a", Style::default()),
+            span("b", bold),
+            span("c", bold_italic),
+            span("d", Style::default()),
+        ]
+    );
+    // toggled twice without text in between
+    assert_eq!(parse("\x02\x02a"), vec![span("a", Style::default())]);
+    assert_eq!(
+        parse("\x1e\x1f\x11\x16a"),
+        vec![span(
+            "a",
+            Style {
+                strikethrough: true,
+                underline: true,
+                monospace: true,
+                reverse: true,
+                ..Style::default()
+            }
+        )]
+    );
+}
+
+#[test]
+fn test_parse_colors() {
+    let red = Style {
+        foreground: Some(Color::Palette(4)),
+        ..Style::default()
+    };
+    let red_on_black = Style {
+        background: Some(Color::Palette(1)),
+        ..red
+    };
+    assert_eq!(
+        parse("\x034red\x0304,01black\x03 none"),
+        vec![
+            span("red", red),
+            span("black", red_on_black),
+            span(" none", Style::default()),
+        ]
+    );
+    // at most two digits, the comma is text without a colour after it
+    assert_eq!(parse("\x03041"), vec![span("1", red)]);
+    assert_eq!(parse("\x034,x"), vec![span(",x", red)]);
+    // 99 is the default colour
+    assert_eq!(
+        parse("\x0399,4a"),
+        vec![span(
+            "a",
+            Style {
+                background: Some(Color::Palette(4)),
+                ..Style::default()
+            }
+        )]
+    );
+    // the background is kept when only the foreground changes
+    assert_eq!(
+        parse("\x031,4\x032a")[0].style.background,
+        Some(Color::Palette(4))
+    );
+}
+
+#[test]
+fn test_parse_hex_colors() {
+    assert_eq!(
+        parse("\x04FF8000,000000a\x04b"),
+        vec![
+            span(
+                "a",
+                Style {
+                    foreground: Some(Color::Rgb(255, 128, 0)),
+                    background: Some(Color::Rgb(0, 0, 0)),
+                    ..Style::default()
+                }
+            ),
+            span("b", Style::default()),
+        ]
+    );
+}
+
+#[test]
+fn test_strip_formatting() {
+    assert_eq!(
+        strip_formatting("\x02bold\x02 \x0304,01red\x03 \x04FF0000hex ümlaut"),
+        "bold red hex ümlaut"
+    );
+}
+
+#[test]
+fn test_shortcut() {
+    assert_eq!(shortcut("b"), Some(BOLD));
+    assert_eq!(shortcut("B"), Some(BOLD));
+    assert_eq!(shortcut("x"), None);
+}
@@ -1,30 +1,46 @@
-use std::{collections::BTreeMap, fmt::Display};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+    ops::Range,
+};
 
 use generic_message::GenericMessage;
 use message::{Message, MessageType};
 use msg_cap::MsgCap;
+use numeric::Numeric;
 use pest::{
-    error::{Error, ErrorVariant},
+    error::{Error, ErrorVariant, InputLocation},
     iterators::{Pair, Pairs},
-    Parser, Position,
+    Parser,
 };
 
-mod grammar;
+pub mod grammar;
 use grammar::{Grammar, Rule};
 use yew::AttrValue;
 
 pub mod generic_message;
 pub mod message;
 pub mod msg_cap;
+pub mod numeric;
+pub mod numeric_reply;
+pub mod typed_message;
+
+mod params;
 
 pub mod capability;
+pub mod tag;
 
 #[cfg(test)]
 mod test;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum MessageBuilderError {
     MultipleTrailingParameters,
+    /// Nick of a `CAP` message has not been set
+    MissingNick,
+    /// Sub command of a `CAP` message has not been set, e.g. before adding
+    /// capabilities
+    MissingSubCommand,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -40,7 +56,7 @@ pub struct User {
     pub host: Option<AttrValue>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(PartialEq, Clone)]
 pub enum Command {
     Digit3(u32),
     Cmd(AttrValue),
@@ -55,67 +71,165 @@ impl Command {
     }
 }
 
+impl Command {
+    /// Numeric reply definition, if this is a known numeric
+    pub fn numeric(&self) -> Option<&'static Numeric> {
+        match self {
+            Self::Digit3(val) => numeric::lookup(*val),
+            Self::Cmd(_) => None,
+        }
+    }
+}
+
+/// Human readable command, known numerics are shown with their symbolic name,
+/// e.g. `001 RPL_WELCOME`
 impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self, self.numeric()) {
+            (Self::Digit3(val), Some(numeric)) => write!(f, "{val:0>3} {}", numeric.name),
+            (Self::Digit3(val), None) => write!(f, "{val:0>3}"),
+            (Self::Cmd(cmd), _) => write!(f, "{cmd}"),
+        }
+    }
+}
+
+impl Debug for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Digit3(val) => write!(f, "{val:0>3}"),
-            Self::Cmd(cmd) => write!(f, "{cmd}"),
+            Self::Digit3(_) => write!(f, "Digit3({self})"),
+            Self::Cmd(cmd) => write!(f, "Cmd({:?})", cmd.as_str()),
         }
     }
 }
 
-fn unexpected_rule(pair: Pair<Rule>) -> Error<Rule> {
-    Error::new_from_span(
-        ErrorVariant::CustomError {
-            message: format!("Unexpected rule: {:?}", pair.as_rule()),
-        },
-        pair.as_span(),
-    )
+/// Byte range of the input a `ParseError` refers to
+pub type Span = Range<usize>;
+
+/// Error returned from all parsers of IRC messages. Parsing never panics, any
+/// malformed input results in one of these errors.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    /// Input does not match the IRC grammar
+    Syntax {
+        span: Span,
+        expected: Vec<Rule>,
+        message: String,
+    },
+    /// Grammar produced a rule where it was not expected
+    UnexpectedRule { span: Span, rule: Rule },
+    /// Required rule is missing, e.g. the command of a message
+    MissingRule { span: Span, rule: Rule },
+    /// Rule matched, but its value could not be interpreted
+    InvalidValue {
+        span: Span,
+        rule: Rule,
+        message: String,
+    },
 }
 
-fn empty_pairs(pairs: &Pairs<Rule>) -> Error<Rule> {
-    Error::new_from_pos(
-        ErrorVariant::CustomError {
-            message: "Empty pairs.".into(),
-        },
-        Position::new(pairs.get_input(), 0).unwrap(),
-    )
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Syntax { span, .. }
+            | Self::UnexpectedRule { span, .. }
+            | Self::MissingRule { span, .. }
+            | Self::InvalidValue { span, .. } => span.clone(),
+        }
+    }
+
+    /// Rule which failed or was expected, if known
+    pub fn rule(&self) -> Option<Rule> {
+        match self {
+            Self::Syntax { expected, .. } => expected.first().copied(),
+            Self::UnexpectedRule { rule, .. }
+            | Self::MissingRule { rule, .. }
+            | Self::InvalidValue { rule, .. } => Some(*rule),
+        }
+    }
+
+    /// Human readable error message
+    pub fn message(&self) -> String {
+        match self {
+            Self::Syntax { message, .. } => message.clone(),
+            Self::UnexpectedRule { rule, .. } => format!("Unexpected rule: {rule:?}"),
+            Self::MissingRule { rule, .. } => format!("Missing rule: {rule:?}"),
+            Self::InvalidValue { rule, message, .. } => format!("Invalid {rule:?}: {message}"),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span = self.span();
+        write!(f, "{} (at {}..{})", self.message(), span.start, span.end)
+    }
 }
 
-fn too_many_pairs(pairs: &Pairs<Rule>, expected: u32) -> Error<Rule> {
-    Error::new_from_pos(
-        ErrorVariant::CustomError {
-            message: format!("Expected {} pairs, have: {}", expected, pairs.len()),
-        },
-        Position::new(pairs.get_input(), 0).unwrap(),
-    )
+impl std::error::Error for ParseError {}
+
+impl From<Error<Rule>> for ParseError {
+    fn from(err: Error<Rule>) -> Self {
+        let span = match err.location {
+            InputLocation::Pos(pos) => pos..pos,
+            InputLocation::Span((start, end)) => start..end,
+        };
+        let expected = match &err.variant {
+            ErrorVariant::ParsingError { positives, .. } => positives.clone(),
+            ErrorVariant::CustomError { .. } => vec![],
+        };
+        ParseError::Syntax {
+            span,
+            expected,
+            message: err.variant.message().into_owned(),
+        }
+    }
+}
+
+fn span_of(pair: &Pair<Rule>) -> Span {
+    let span = pair.as_span();
+    span.start()..span.end()
+}
+
+fn unexpected_rule(pair: Pair<Rule>) -> ParseError {
+    ParseError::UnexpectedRule {
+        span: span_of(&pair),
+        rule: pair.as_rule(),
+    }
+}
+
+/// Required `rule` is missing inside of `parent`
+fn missing_rule(parent: &Pair<Rule>, rule: Rule) -> ParseError {
+    ParseError::MissingRule {
+        span: span_of(parent),
+        rule,
+    }
 }
 
 impl Message {
-    pub fn parse(str: &str) -> Result<Self, Error<Rule>> {
+    pub fn parse(str: &str) -> Result<Self, ParseError> {
         let mut pairs = Grammar::parse(Rule::message, str)?;
         match pairs.next() {
-            Some(pair) if pair.as_rule() == Rule::message => Self::parse_inner(pair.into_inner()),
-            _ => Err(Error::new_from_pos(
-                pest::error::ErrorVariant::CustomError {
-                    message: "Failed.".into(),
-                },
-                Position::new(str, 0).unwrap(),
-            )),
+            Some(pair) if pair.as_rule() == Rule::message => Self::parse_inner(pair),
+            Some(pair) => Err(unexpected_rule(pair)),
+            None => Err(ParseError::MissingRule {
+                span: 0..str.len(),
+                rule: Rule::message,
+            }),
         }
     }
 
-    fn parse_inner(pairs: Pairs<Rule>) -> Result<Self, Error<Rule>> {
+    fn parse_inner(message: Pair<Rule>) -> Result<Self, ParseError> {
         let mut tags = BTreeMap::new();
         let mut source = None;
         let mut msg_type = None::<MessageType>;
 
         // parse inner pairs
-        for pair in pairs {
+        for pair in message.clone().into_inner() {
             match pair.as_rule() {
                 Rule::tags => tags = Self::parse_tags(pair.into_inner())?,
-                Rule::source => source = Some(Self::parse_source(pair.into_inner())?),
-                Rule::msg_type => msg_type = Some(Self::parse_msg_type(pair.into_inner())?),
+                Rule::source => source = Some(Self::parse_source(pair)?),
+                Rule::msg_type => msg_type = Some(Self::parse_msg_type(pair)?),
+                Rule::EOI => (),
                 _ => return Err(unexpected_rule(pair)),
             }
         }
@@ -123,52 +237,53 @@ impl Message {
         Ok(Message {
             tags,
             source,
-            msg_type: msg_type.expect("Missing message type"),
+            msg_type: msg_type.ok_or_else(|| missing_rule(&message, Rule::msg_type))?,
         })
     }
 
-    fn parse_msg_type(mut pairs: Pairs<Rule>) -> Result<MessageType, Error<Rule>> {
-        if pairs.len() != 1 {
-            return Err(too_many_pairs(&pairs, 1));
-        }
-
-        let pair = pairs.next().unwrap();
-        match pair.as_rule() {
-            Rule::generic_message => Ok(MessageType::Generic(GenericMessage::parse(
-                pair.into_inner(),
-            )?)),
-            Rule::msg_cap => Ok(MessageType::Capability(MsgCap::parse(pair.into_inner())?)),
-            _ => return Err(unexpected_rule(pair)),
+    fn parse_msg_type(msg_type: Pair<Rule>) -> Result<MessageType, ParseError> {
+        let mut pairs = msg_type.clone().into_inner();
+        match (pairs.next(), pairs.next()) {
+            (Some(pair), None) => match pair.as_rule() {
+                Rule::generic_message => Ok(MessageType::Generic(GenericMessage::parse(pair)?)),
+                Rule::msg_cap => Ok(MessageType::Capability(MsgCap::parse(pair)?)),
+                _ => Err(unexpected_rule(pair)),
+            },
+            (_, Some(pair)) => Err(unexpected_rule(pair)),
+            (None, None) => Err(missing_rule(&msg_type, Rule::generic_message)),
         }
     }
 
-    fn parse_tags(pairs: Pairs<Rule>) -> Result<BTreeMap<String, Option<String>>, Error<Rule>> {
+    fn parse_tags(pairs: Pairs<Rule>) -> Result<BTreeMap<String, Option<String>>, ParseError> {
         let mut tags = BTreeMap::new();
         for pair in pairs {
             match pair.as_rule() {
                 Rule::tag => {
-                    let mut key = "";
+                    let mut key = None::<&str>;
                     let mut value = None::<String>;
 
-                    for pair in pair.into_inner() {
-                        match pair.as_rule() {
-                            Rule::key => key = pair.as_str(),
+                    for inner in pair.clone().into_inner() {
+                        match inner.as_rule() {
+                            Rule::key => key = Some(inner.as_str()),
                             Rule::assignment => value = Some(String::new()),
-                            Rule::escaped_value => value = Some(pair.as_str().into()),
-                            _ => return Err(unexpected_rule(pair.clone())),
+                            Rule::escaped_value => value = Some(tag::unescape(inner.as_str())),
+                            _ => return Err(unexpected_rule(inner)),
                         }
                     }
+                    let key = key.ok_or_else(|| missing_rule(&pair, Rule::key))?;
                     tags.insert(key.to_owned(), value);
                 }
-                _ => return Err(unexpected_rule(pair.clone())),
+                _ => return Err(unexpected_rule(pair)),
             }
         }
         Ok(tags)
     }
 
-    fn parse_source(mut pairs: Pairs<Rule>) -> Result<Source, Error<Rule>> {
-        let pair = pairs.next().ok_or(empty_pairs(&pairs))?;
-        print!("{pair:?}");
+    fn parse_source(source: Pair<Rule>) -> Result<Source, ParseError> {
+        let mut pairs = source.clone().into_inner();
+        let pair = pairs
+            .next()
+            .ok_or_else(|| missing_rule(&source, Rule::name))?;
 
         if pair.as_rule() != Rule::name {
             return Err(unexpected_rule(pair));
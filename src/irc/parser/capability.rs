@@ -24,6 +24,21 @@ impl Capability {
     pub fn disabled(value: &str) -> Self {
         Capability::Disabled(value.to_owned().into())
     }
+
+    /// Name of the capability, without values or `-` prefix
+    pub fn key(&self) -> &AttrValue {
+        match self {
+            Capability::Single(key) | Capability::Values(key, _) | Capability::Disabled(key) => key,
+        }
+    }
+
+    /// List of values, e.g. `PLAIN` and `EXTERNAL` for `sasl=PLAIN,EXTERNAL`
+    pub fn value_list(&self) -> &[AttrValue] {
+        match self {
+            Capability::Values(_, values) => values,
+            _ => &[],
+        }
+    }
 }
 
 impl Display for Capability {
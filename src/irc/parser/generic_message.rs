@@ -1,12 +1,9 @@
 use std::fmt::Display;
 
-use pest::{
-    error::Error,
-    iterators::{Pair, Pairs},
-};
+use pest::iterators::{Pair, Pairs};
 use yew::AttrValue;
 
-use super::{grammar::Rule, unexpected_rule, Command};
+use super::{grammar::Rule, missing_rule, span_of, unexpected_rule, Command, ParseError};
 
 #[cfg(test)]
 mod test;
@@ -41,15 +38,15 @@ impl GenericMessage {
 }
 
 impl GenericMessage {
-    pub fn parse(pairs: Pairs<Rule>) -> Result<Self, Error<Rule>> {
+    pub fn parse(message: Pair<Rule>) -> Result<Self, ParseError> {
         let mut command = None::<Command>;
         let mut parameters = vec![];
 
-        for pair in pairs {
+        for pair in message.clone().into_inner() {
             match pair.as_rule() {
                 Rule::command => command = Some(Self::parse_command(pair)?),
                 Rule::parameters => parameters = Self::parse_parameters(pair.into_inner())?,
-                r => todo!("Unexpected rule {r:?}"),
+                _ => return Err(unexpected_rule(pair)),
             }
         }
 
@@ -58,26 +55,35 @@ impl GenericMessage {
                 command,
                 parameters,
             }),
-            _ => todo!("Missing command"),
+            None => Err(missing_rule(&message, Rule::command)),
         }
     }
 
-    fn parse_command(pair: Pair<Rule>) -> Result<Command, Error<Rule>> {
+    fn parse_command(pair: Pair<Rule>) -> Result<Command, ParseError> {
         let cmd = pair.as_str().to_owned();
-        match pair.into_inner().next() {
-            Some(val) if val.as_rule() == Rule::digit3 => Ok(Command::Digit3(cmd.parse().unwrap())),
+        match pair.clone().into_inner().next() {
+            // `NUMBER` also matches non-ASCII digits, which do not parse as u32
+            Some(val) if val.as_rule() == Rule::digit3 => match cmd.parse() {
+                Ok(digit3) => Ok(Command::Digit3(digit3)),
+                Err(err) => Err(ParseError::InvalidValue {
+                    span: span_of(&val),
+                    rule: Rule::digit3,
+                    message: err.to_string(),
+                }),
+            },
             _ => Ok(Command::Cmd(cmd.into())),
         }
     }
 
-    fn parse_parameters(pairs: Pairs<Rule>) -> Result<Vec<AttrValue>, Error<Rule>> {
+    fn parse_parameters(pairs: Pairs<Rule>) -> Result<Vec<AttrValue>, ParseError> {
         let mut params = Vec::<AttrValue>::new();
         for pair in pairs {
             match pair.as_rule() {
                 Rule::middle => params.push(pair.as_str().to_owned().into()),
-                Rule::trailing => {
-                    params.push(pair.into_inner().next().unwrap().as_str().to_owned().into())
-                }
+                Rule::trailing => match pair.clone().into_inner().next() {
+                    Some(inner) => params.push(inner.as_str().to_owned().into()),
+                    None => return Err(missing_rule(&pair, Rule::trailing_inner)),
+                },
                 _ => return Err(unexpected_rule(pair)),
             }
         }
@@ -94,17 +100,21 @@ impl Display for GenericMessage {
         }
 
         // format parameters
-        match &self.parameters[..] {
-            [middle @ .., trailing] => {
-                if !middle.is_empty() {
-                    write!(f, " {}", middle.join(" "))?;
-                }
-                if !trailing.is_empty() {
-                    let prefix = if trailing.contains(" ") { ":" } else { "" };
-                    write!(f, " {prefix}{trailing}")?;
-                }
+        if let [middle @ .., trailing] = &self.parameters[..] {
+            if !middle.is_empty() {
+                write!(f, " {}", middle.join(" "))?;
+            }
+            // empty trailing parameters only need to be preserved if they
+            // follow other parameters, e.g. `TOPIC #channel :`
+            if !trailing.is_empty() || !middle.is_empty() {
+                let prefix =
+                    if trailing.is_empty() || trailing.contains(' ') || trailing.starts_with(':') {
+                        ":"
+                    } else {
+                        ""
+                    };
+                write!(f, " {prefix}{trailing}")?;
             }
-            _ => {}
         }
 
         Ok(())
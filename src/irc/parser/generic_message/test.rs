@@ -13,12 +13,12 @@ fn test_generic_message() {
 
     let mut pairs = Grammar::parse(Rule::generic_message, input).unwrap();
     let pair = pairs.next().unwrap();
-    let msg = GenericMessage::parse(pair.into_inner()).unwrap();
+    let msg = GenericMessage::parse(pair).unwrap();
     assert_eq!(
         msg,
         GenericMessage {
             command: Command::Cmd("TEST".to_string().into()),
-            parameters: vec!["a", "b", "c", "x y z"]
+            parameters: ["a", "b", "c", "x y z"]
                 .iter()
                 .map(|s| s.to_owned().into())
                 .collect(),
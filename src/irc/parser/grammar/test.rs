@@ -276,3 +276,25 @@ fn test_msg_cap_multiline() {
         }
     }
 }
+
+#[test]
+fn test_tag_key_client_prefix_vendor() {
+    let mut res = Grammar::parse(Rule::key, "+example.com/foo").unwrap();
+    let pair = res.next().unwrap();
+    assert_eq!(pair.as_rule(), Rule::key);
+    assert_eq!(pair.as_str(), "+example.com/foo");
+
+    let mut inner = pair.into_inner();
+    assert_eq!(inner.len(), 3);
+
+    let pair = inner.next().unwrap();
+    assert_eq!(pair.as_rule(), Rule::client_prefix);
+
+    let pair = inner.next().unwrap();
+    assert_eq!(pair.as_rule(), Rule::vendor);
+    assert_eq!(pair.as_str(), "example.com");
+
+    let pair = inner.next().unwrap();
+    assert_eq!(pair.as_rule(), Rule::key_chars);
+    assert_eq!(pair.as_str(), "foo");
+}
@@ -2,9 +2,16 @@ use std::{collections::BTreeMap, fmt::Display};
 
 use yew::AttrValue;
 
-use crate::irc::parser::User;
+use crate::irc::parser::{Command, User};
 
-use super::{generic_message::GenericMessage, msg_cap::MsgCap, Source};
+use super::{
+    generic_message::GenericMessage,
+    msg_cap::MsgCap,
+    numeric_reply::NumericReply,
+    tag,
+    typed_message::{TypedMessage, TypedMessageError},
+    Source,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Message {
@@ -35,6 +42,32 @@ impl Message {
         let msg_type = MessageType::Capability(msg_cap);
         MessageBuilder::new().msg_type(msg_type)
     }
+
+    pub fn typed(msg: TypedMessage) -> MessageBuilder {
+        Self::generic(msg.into())
+    }
+}
+
+impl Message {
+    /// Interpret a generic message as strongly typed command
+    pub fn to_typed(&self) -> Result<TypedMessage, TypedMessageError> {
+        match &self.msg_type {
+            MessageType::Generic(msg) => TypedMessage::try_from(msg),
+            MessageType::Capability(_) => Err(TypedMessageError::UnknownCommand(Command::Cmd(
+                "CAP".into(),
+            ))),
+        }
+    }
+
+    /// Interpret a generic message as numeric reply
+    pub fn to_numeric(&self) -> Result<NumericReply, TypedMessageError> {
+        match &self.msg_type {
+            MessageType::Generic(msg) => NumericReply::try_from(msg),
+            MessageType::Capability(_) => Err(TypedMessageError::UnknownCommand(Command::Cmd(
+                "CAP".into(),
+            ))),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -63,7 +96,8 @@ impl Display for Message {
                     .iter()
                     .map(|(key, mval)| format!(
                         "{key}{}",
-                        mval.clone().map_or(String::new(), |val| format!("={val}"))
+                        mval.as_ref()
+                            .map_or(String::new(), |val| format!("={}", tag::escape(val)))
                     ))
                     .collect::<Vec<String>>()
                     .join(";")
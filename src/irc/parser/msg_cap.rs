@@ -1,9 +1,12 @@
 use std::fmt::{Display, Formatter};
 
-use pest::{error::Error, iterators::Pairs};
+use pest::iterators::{Pair, Pairs};
 use yew::AttrValue;
 
-use super::{capability::Capability, grammar::Rule, unexpected_rule};
+use super::{
+    capability::Capability, grammar::Rule, missing_rule, unexpected_rule, MessageBuilderError,
+    ParseError,
+};
 
 #[cfg(test)]
 mod test;
@@ -20,6 +23,7 @@ impl MsgCap {
         Builder {
             nick: None,
             sub_command: None,
+            error: None,
         }
     }
 
@@ -35,6 +39,8 @@ impl MsgCap {
 pub struct Builder {
     nick: Option<CapNick>,
     sub_command: Option<SubCommand>,
+    /// First error while building, returned by `build`
+    error: Option<MessageBuilderError>,
 }
 
 impl Builder {
@@ -67,36 +73,90 @@ impl Builder {
         self
     }
 
+    pub fn list(mut self, multiline: bool) -> Self {
+        self.sub_command = Some(SubCommand::LIST(multiline, vec![]));
+        self
+    }
+
+    /// Sub command `NEW`
+    pub fn cap_new(mut self) -> Self {
+        self.sub_command = Some(SubCommand::NEW(vec![]));
+        self
+    }
+
+    pub fn del(mut self) -> Self {
+        self.sub_command = Some(SubCommand::DEL(vec![]));
+        self
+    }
+
     /// Add a single capability
     pub fn single(mut self, single_capability: &str) -> Self {
         let cap = Capability::Single(single_capability.to_string().into());
-        self.push_capability(cap);
+        self.add_capability(cap);
+        self
+    }
+
+    /// Add a capability with values, e.g. `sasl=PLAIN,EXTERNAL`
+    pub fn values(mut self, key: &str, values: Vec<&str>) -> Self {
+        let cap = Capability::Values(
+            key.to_owned().into(),
+            values.iter().map(|s| s.to_string().into()).collect(),
+        );
+        self.add_capability(cap);
+        self
+    }
+
+    /// Add a disabled capability, e.g. `-multi-prefix`
+    pub fn disabled(mut self, key: &str) -> Self {
+        self.add_capability(Capability::disabled(key));
         self
     }
 
     pub fn capabilities(mut self, capabilities: Vec<AttrValue>) -> Self {
         for cap in capabilities {
-            self.push_capability(Capability::Single(cap));
+            self.add_capability(Capability::Single(cap));
         }
         self
     }
 
-    fn push_capability(&mut self, capability: Capability) {
+    /// Fails if the sub command has not been set before
+    fn push_capability(&mut self, capability: Capability) -> Result<(), MessageBuilderError> {
         use SubCommand::*;
 
         match &mut self.sub_command {
-            Some(LS(_, capabilities)) => capabilities.push(capability),
-            _ => todo!(),
-        };
+            Some(LS(_, capabilities))
+            | Some(LIST(_, capabilities))
+            | Some(REQ(capabilities))
+            | Some(ACK(capabilities))
+            | Some(NAK(capabilities))
+            | Some(NEW(capabilities))
+            | Some(DEL(capabilities)) => {
+                capabilities.push(capability);
+                Ok(())
+            }
+            None => Err(MessageBuilderError::MissingSubCommand),
+        }
     }
 
-    /// Build message from Builder. Panics if nick or sub_command is not set.
-    pub fn build(self) -> MsgCap {
-        MsgCap {
-            nick: self.nick.unwrap(),
-            sub_command: self.sub_command.unwrap(),
+    /// Keep the first error for `build`
+    fn add_capability(&mut self, capability: Capability) {
+        if let Err(err) = self.push_capability(capability) {
+            self.error.get_or_insert(err);
         }
     }
+
+    /// Build message from Builder. Fails if nick or sub_command is not set.
+    pub fn build(self) -> Result<MsgCap, MessageBuilderError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        Ok(MsgCap {
+            nick: self.nick.ok_or(MessageBuilderError::MissingNick)?,
+            sub_command: self
+                .sub_command
+                .ok_or(MessageBuilderError::MissingSubCommand)?,
+        })
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -169,27 +229,30 @@ impl Display for SubCommand {
 
 /// Parsing `MsgCap` implementation
 impl MsgCap {
-    pub fn parse(pairs: Pairs<Rule>) -> Result<Self, Error<Rule>> {
+    pub fn parse(msg_cap: Pair<Rule>) -> Result<Self, ParseError> {
         let mut nick = None::<CapNick>;
         let mut sub_command = None::<SubCommand>;
 
-        for pair in pairs {
+        for pair in msg_cap.clone().into_inner() {
             match pair.as_rule() {
-                Rule::cap_nick => nick = Some(Self::parse_nick(pair.into_inner())?),
-                Rule::cap_cmd => sub_command = Some(Self::parse_sub_command(pair.into_inner())?),
-                _ => todo!("{pair:?}"),
+                Rule::cap_nick => nick = Some(Self::parse_nick(pair)?),
+                Rule::cap_cmd => sub_command = Some(Self::parse_sub_command(pair)?),
+                _ => return Err(unexpected_rule(pair)),
             }
         }
 
         Ok(MsgCap {
-            nick: nick.unwrap(), // TODO: proper error handling
-            sub_command: sub_command.unwrap(),
+            nick: nick.ok_or_else(|| missing_rule(&msg_cap, Rule::cap_nick))?,
+            sub_command: sub_command.ok_or_else(|| missing_rule(&msg_cap, Rule::cap_cmd))?,
         })
     }
 
-    fn parse_nick(pairs: Pairs<Rule>) -> Result<CapNick, Error<Rule>> {
+    fn parse_nick(cap_nick: Pair<Rule>) -> Result<CapNick, ParseError> {
         // use peek() instead of next() to not actually consume the first character
-        let pair = pairs.peek().expect("At least one pair");
+        let pairs = cap_nick.clone().into_inner();
+        let pair = pairs
+            .peek()
+            .ok_or_else(|| missing_rule(&cap_nick, Rule::nospcrlf))?;
         match pair.as_rule() {
             Rule::star => Ok(CapNick::Star),
             Rule::nospcrlf => Ok(CapNick::nick(pairs.as_str())),
@@ -197,15 +260,27 @@ impl MsgCap {
         }
     }
 
-    fn parse_sub_command(mut pairs: Pairs<Rule>) -> Result<SubCommand, Error<Rule>> {
-        let pair = pairs.next().unwrap();
-        match pair.as_rule() {
-            Rule::cap_ls => Ok(Self::parse_ls(pair.into_inner())?),
-            _ => todo!("{pair:?}"),
-        }
+    fn parse_sub_command(cap_cmd: Pair<Rule>) -> Result<SubCommand, ParseError> {
+        let pair = cap_cmd
+            .clone()
+            .into_inner()
+            .next()
+            .ok_or_else(|| missing_rule(&cap_cmd, Rule::cap_ls))?;
+        let (multiline, capabilities) = Self::parse_capabilities(pair.clone().into_inner())?;
+
+        Ok(match pair.as_rule() {
+            Rule::cap_ls => SubCommand::LS(multiline, capabilities),
+            Rule::cap_list => SubCommand::LIST(multiline, capabilities),
+            Rule::cap_req => SubCommand::REQ(capabilities),
+            Rule::cap_ack => SubCommand::ACK(capabilities),
+            Rule::cap_nak => SubCommand::NAK(capabilities),
+            Rule::cap_new => SubCommand::NEW(capabilities),
+            Rule::cap_del => SubCommand::DEL(capabilities),
+            _ => return Err(unexpected_rule(pair)),
+        })
     }
 
-    fn parse_ls(pairs: Pairs<Rule>) -> Result<SubCommand, Error<Rule>> {
+    fn parse_capabilities(pairs: Pairs<Rule>) -> Result<(Multiline, Capabilities), ParseError> {
         let mut multiline = false;
         let mut capabilities = vec![];
 
@@ -216,10 +291,10 @@ impl MsgCap {
                 _ => return Err(unexpected_rule(pair)),
             }
         }
-        Ok(SubCommand::LS(multiline, capabilities))
+        Ok((multiline, capabilities))
     }
 
-    fn parse_capability(pairs: Pairs<Rule>) -> Result<Capability, Error<Rule>> {
+    fn parse_capability(pairs: Pairs<Rule>) -> Result<Capability, ParseError> {
         let mut disabled = false;
         let mut key = "";
         let mut values = vec![];
@@ -258,8 +333,6 @@ impl Display for MsgCap {
             CapNick::Nick(nick) => nick,
         };
 
-        let sub_command = ""; // TODO:
-
-        write!(f, "CAP {nick} {sub_command}")
+        write!(f, "CAP {nick} {}", self.sub_command)
     }
 }
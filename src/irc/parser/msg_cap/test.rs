@@ -5,7 +5,9 @@ use pest::Parser;
 use crate::irc::parser::{
     capability::Capability,
     grammar::{Grammar, Rule},
+    message::{Message, MessageType},
     msg_cap::{CapNick, SubCommand},
+    MessageBuilderError,
 };
 
 use super::MsgCap;
@@ -14,7 +16,7 @@ fn parse(input: &str) -> MsgCap {
     let res = Grammar::parse(Rule::msg_cap, input);
     match res {
         Err(err) => panic!("{err}"),
-        Ok(mut pairs) => MsgCap::parse(pairs.next().unwrap().into_inner()).unwrap(),
+        Ok(mut pairs) => MsgCap::parse(pairs.next().unwrap()).unwrap(),
     }
 }
 
@@ -54,3 +56,184 @@ fn test_ls() {
         )
     );
 }
+
+/// Parse full message, make sure it is a CAP message and serializes to the
+/// exact same input again
+fn roundtrip(input: &str) -> MsgCap {
+    let msg = match Message::parse(input) {
+        Err(err) => panic!("{err}"),
+        Ok(msg) => msg,
+    };
+    assert_eq!(msg.to_string(), input);
+    match msg.msg_type {
+        MessageType::Capability(cap) => cap,
+        msg_type => panic!("Not a CAP message: {msg_type:?}"),
+    }
+}
+
+#[test]
+fn test_roundtrip_ls() {
+    let msg = roundtrip("CAP * LS * :multi-prefix sasl=PLAIN,EXTERNAL");
+    assert_eq!(
+        msg,
+        MsgCap::builder()
+            .star()
+            .ls(true)
+            .single("multi-prefix")
+            .values("sasl", vec!["PLAIN", "EXTERNAL"])
+            .build()
+            .unwrap()
+    );
+
+    let msg = roundtrip("CAP * LS :");
+    assert_eq!(msg, MsgCap::ls(false).build().unwrap());
+}
+
+#[test]
+fn test_roundtrip_list() {
+    let msg = roundtrip("CAP dan LIST :sasl server-time");
+    assert_eq!(
+        msg,
+        MsgCap::builder()
+            .nick("dan".into())
+            .list(false)
+            .single("sasl")
+            .single("server-time")
+            .build()
+            .unwrap()
+    );
+
+    let msg = roundtrip("CAP dan LIST * :sasl");
+    assert_eq!(
+        msg.sub_command,
+        SubCommand::LIST(true, vec![Capability::new("sasl")])
+    );
+}
+
+#[test]
+fn test_roundtrip_req() {
+    let msg = roundtrip("CAP * REQ :multi-prefix -sasl");
+    assert_eq!(
+        msg,
+        MsgCap::req()
+            .single("multi-prefix")
+            .disabled("sasl")
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_roundtrip_ack() {
+    let msg = roundtrip("CAP dan ACK :sasl -multi-prefix");
+    assert_eq!(
+        msg,
+        MsgCap::builder()
+            .nick("dan".into())
+            .ack()
+            .single("sasl")
+            .disabled("multi-prefix")
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_roundtrip_nak() {
+    let msg = roundtrip("CAP * NAK :sasl");
+    assert_eq!(
+        msg,
+        MsgCap::builder()
+            .star()
+            .nak()
+            .single("sasl")
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_roundtrip_new() {
+    let msg = roundtrip("CAP dan NEW :batch sasl=PLAIN");
+    assert_eq!(
+        msg,
+        MsgCap::builder()
+            .nick("dan".into())
+            .cap_new()
+            .single("batch")
+            .values("sasl", vec!["PLAIN"])
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_roundtrip_del() {
+    let msg = roundtrip("CAP dan DEL :sasl");
+    assert_eq!(
+        msg,
+        MsgCap::builder()
+            .nick("dan".into())
+            .del()
+            .single("sasl")
+            .build()
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_builder_errors() {
+    assert_eq!(
+        MsgCap::builder().star().single("sasl").ack().build(),
+        Err(MessageBuilderError::MissingSubCommand)
+    );
+    assert_eq!(
+        MsgCap::builder().ack().single("sasl").build(),
+        Err(MessageBuilderError::MissingNick)
+    );
+}
+
+#[test]
+fn test_trailing_space() {
+    let msg = parse("CAP * ACK :sasl ");
+    assert_eq!(
+        msg.sub_command,
+        SubCommand::ACK(vec![Capability::new("sasl")])
+    );
+}
+
+#[test]
+fn test_single_capability_without_colon() {
+    let msg = parse("CAP * ACK sasl");
+    assert_eq!(
+        msg.sub_command,
+        SubCommand::ACK(vec![Capability::new("sasl")])
+    );
+
+    let msg = parse("CAP * LS sasl=PLAIN");
+    assert_eq!(
+        msg.sub_command,
+        SubCommand::LS(false, vec![Capability::values("sasl", vec!["PLAIN"])])
+    );
+
+    let msg = parse("CAP * DEL -sasl");
+    assert_eq!(
+        msg.sub_command,
+        SubCommand::DEL(vec![Capability::disabled("sasl")])
+    );
+}
+
+#[test]
+fn test_not_a_cap_message() {
+    // clients send CAP without nick, these are generic messages
+    let msg = Message::parse("CAP LS 302").unwrap();
+    assert!(matches!(msg.msg_type, MessageType::Generic(_)));
+
+    // missing space between command and nick
+    let msg = Message::parse("CAPX LS :sasl").unwrap();
+    assert!(matches!(msg.msg_type, MessageType::Generic(_)));
+
+    // only LS and LIST replies can span multiple lines
+    let msg = Message::parse("CAP * NEW * :sasl").unwrap();
+    assert!(matches!(msg.msg_type, MessageType::Generic(_)));
+}
@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Category {
+    /// Connection registration and server greeting
+    Registration,
+    /// Server statistics and information (LUSERS, ADMIN, STATS, ...)
+    Server,
+    /// Message of the day
+    Motd,
+    /// WHOIS, WHOWAS, WHO and USERHOST replies
+    User,
+    /// Channel topic, names, modes and mask lists
+    Channel,
+    /// Channel LIST replies
+    List,
+    /// Away status
+    Away,
+    /// MONITOR replies
+    Monitor,
+    /// SASL authentication
+    Sasl,
+    /// HELP replies
+    Help,
+    /// Error replies
+    Error,
+}
+
+/// Numeric reply of the catalogue, following the Modern IRC documentation:
+///
+/// https://modern.ircdocs.horse/#numerics
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Numeric {
+    pub code: u32,
+    /// Symbolic name, e.g. `RPL_WELCOME`
+    pub name: &'static str,
+    pub category: Category,
+    /// Expected shape of the parameters, e.g. `<client> <nick> :<text>`
+    pub parameters: &'static str,
+}
+
+impl Numeric {
+    pub fn is_error(&self) -> bool {
+        self.category == Category::Error || self.name.starts_with("ERR_")
+    }
+}
+
+/// Lookup numeric by its code
+pub fn lookup(code: u32) -> Option<&'static Numeric> {
+    NUMERICS
+        .binary_search_by_key(&code, |numeric| numeric.code)
+        .ok()
+        .map(|idx| &NUMERICS[idx])
+}
+
+/// Lookup numeric by its symbolic name, e.g. `ERR_NICKNAMEINUSE`
+pub fn lookup_name(name: &str) -> Option<&'static Numeric> {
+    NUMERICS.iter().find(|numeric| numeric.name == name)
+}
+
+/// Defines a `pub const` for every numeric together with the sorted list of
+/// all known numerics
+macro_rules! numerics {
+    ($($code:literal $name:ident $category:ident $parameters:literal,)*) => {
+        $(pub const $name: u32 = $code;)*
+
+        pub const NUMERICS: &[Numeric] = &[
+            $(Numeric {
+                code: $code,
+                name: stringify!($name),
+                category: Category::$category,
+                parameters: $parameters,
+            },)*
+        ];
+    };
+}
+
+numerics! {
+    1 RPL_WELCOME Registration "<client> :Welcome to the <networkname> Network, <nick>[!<user>@<host>]",
+    2 RPL_YOURHOST Registration "<client> :Your host is <servername>, running version <version>",
+    3 RPL_CREATED Registration "<client> :This server was created <datetime>",
+    4 RPL_MYINFO Registration "<client> <servername> <version> <available user modes> <available channel modes> [<channel modes with a parameter>]",
+    5 RPL_ISUPPORT Registration "<client> <1-13 tokens> :are supported by this server",
+    10 RPL_BOUNCE Registration "<client> <hostname> <port> :<info>",
+    212 RPL_STATSCOMMANDS Server "<client> <command> <count> [<byte count> <remote count>]",
+    219 RPL_ENDOFSTATS Server "<client> <stats letter> :End of /STATS report",
+    221 RPL_UMODEIS User "<client> <user modes>",
+    242 RPL_STATSUPTIME Server "<client> :Server Up <days> days <hours>:<minutes>:<seconds>",
+    251 RPL_LUSERCLIENT Server "<client> :There are <u> users and <i> invisible on <s> servers",
+    252 RPL_LUSEROP Server "<client> <ops> :operator(s) online",
+    253 RPL_LUSERUNKNOWN Server "<client> <connections> :unknown connection(s)",
+    254 RPL_LUSERCHANNELS Server "<client> <channels> :channels formed",
+    255 RPL_LUSERME Server "<client> :I have <c> clients and <s> servers",
+    256 RPL_ADMINME Server "<client> [<server>] :Administrative info",
+    257 RPL_ADMINLOC1 Server "<client> :<info>",
+    258 RPL_ADMINLOC2 Server "<client> :<info>",
+    259 RPL_ADMINEMAIL Server "<client> :<info>",
+    263 RPL_TRYAGAIN Server "<client> <command> :Please wait a while and try again.",
+    265 RPL_LOCALUSERS Server "<client> [<u> <m>] :Current local users <u>, max <m>",
+    266 RPL_GLOBALUSERS Server "<client> [<u> <m>] :Current global users <u>, max <m>",
+    276 RPL_WHOISCERTFP User "<client> <nick> :has client certificate fingerprint <fingerprint>",
+    300 RPL_NONE Server "Undefined format",
+    301 RPL_AWAY Away "<client> <nick> :<message>",
+    302 RPL_USERHOST User "<client> :[<reply>{ <reply>}]",
+    305 RPL_UNAWAY Away "<client> :You are no longer marked as being away",
+    306 RPL_NOWAWAY Away "<client> :You have been marked as being away",
+    307 RPL_WHOISREGNICK User "<client> <nick> :has identified for this nick",
+    311 RPL_WHOISUSER User "<client> <nick> <username> <host> * :<realname>",
+    312 RPL_WHOISSERVER User "<client> <nick> <server> :<server info>",
+    313 RPL_WHOISOPERATOR User "<client> <nick> :is an IRC operator",
+    314 RPL_WHOWASUSER User "<client> <nick> <username> <host> * :<realname>",
+    315 RPL_ENDOFWHO User "<client> <mask> :End of WHO list",
+    317 RPL_WHOISIDLE User "<client> <nick> <secs> <signon> :seconds idle, signon time",
+    318 RPL_ENDOFWHOIS User "<client> <nick> :End of /WHOIS list",
+    319 RPL_WHOISCHANNELS User "<client> <nick> :[prefix]<channel>{ [prefix]<channel>}",
+    320 RPL_WHOISSPECIAL User "<client> <nick> :<text>",
+    321 RPL_LISTSTART List "<client> Channel :Users  Name",
+    322 RPL_LIST List "<client> <channel> <client count> :<topic>",
+    323 RPL_LISTEND List "<client> :End of /LIST",
+    324 RPL_CHANNELMODEIS Channel "<client> <channel> <modestring> <mode arguments>...",
+    329 RPL_CREATIONTIME Channel "<client> <channel> <creationtime>",
+    330 RPL_WHOISACCOUNT User "<client> <nick> <account> :is logged in as",
+    331 RPL_NOTOPIC Channel "<client> <channel> :No topic is set",
+    332 RPL_TOPIC Channel "<client> <channel> :<topic>",
+    333 RPL_TOPICWHOTIME Channel "<client> <channel> <nick> <setat>",
+    336 RPL_INVITELIST Channel "<client> <channel>",
+    337 RPL_ENDOFINVITELIST Channel "<client> :End of /INVITE list",
+    338 RPL_WHOISACTUALLY User "<client> <nick> [<host|ip>] :Is actually using host",
+    341 RPL_INVITING Channel "<client> <nick> <channel>",
+    346 RPL_INVEXLIST Channel "<client> <channel> <mask>",
+    347 RPL_ENDOFINVEXLIST Channel "<client> <channel> :End of Channel Invite Exception List",
+    348 RPL_EXCEPTLIST Channel "<client> <channel> <mask>",
+    349 RPL_ENDOFEXCEPTLIST Channel "<client> <channel> :End of channel exception list",
+    351 RPL_VERSION Server "<client> <version> <server> :<comments>",
+    352 RPL_WHOREPLY User "<client> <channel> <username> <host> <server> <nick> <flags> :<hopcount> <realname>",
+    353 RPL_NAMREPLY Channel "<client> <symbol> <channel> :[prefix]<nick>{ [prefix]<nick>}",
+    354 RPL_WHOSPCRPL User "<client> [<token>] <fields>...",
+    364 RPL_LINKS Server "<client> * <server> :<hopcount> <server info>",
+    365 RPL_ENDOFLINKS Server "<client> * :End of /LINKS list",
+    366 RPL_ENDOFNAMES Channel "<client> <channel> :End of /NAMES list",
+    367 RPL_BANLIST Channel "<client> <channel> <mask> [<who> <set-ts>]",
+    368 RPL_ENDOFBANLIST Channel "<client> <channel> :End of channel ban list",
+    369 RPL_ENDOFWHOWAS User "<client> <nick> :End of WHOWAS",
+    371 RPL_INFO Server "<client> :<string>",
+    372 RPL_MOTD Motd "<client> :<line of the motd>",
+    374 RPL_ENDOFINFO Server "<client> :End of INFO list",
+    375 RPL_MOTDSTART Motd "<client> :- <server> Message of the day - ",
+    376 RPL_ENDOFMOTD Motd "<client> :End of /MOTD command.",
+    378 RPL_WHOISHOST User "<client> <nick> :is connecting from *@localhost 127.0.0.1",
+    379 RPL_WHOISMODES User "<client> <nick> :is using modes +ailosw",
+    381 RPL_YOUREOPER Server "<client> :You are now an IRC operator",
+    382 RPL_REHASHING Server "<client> <config file> :Rehashing",
+    391 RPL_TIME Server "<client> <server> [<timestamp> [<TS offset>]] :<human-readable time>",
+    400 ERR_UNKNOWNERROR Error "<client> <command>{ <subcommand>} :<info>",
+    401 ERR_NOSUCHNICK Error "<client> <nickname> :No such nick/channel",
+    402 ERR_NOSUCHSERVER Error "<client> <server name> :No such server",
+    403 ERR_NOSUCHCHANNEL Error "<client> <channel> :No such channel",
+    404 ERR_CANNOTSENDTOCHAN Error "<client> <channel> :Cannot send to channel",
+    405 ERR_TOOMANYCHANNELS Error "<client> <channel> :You have joined too many channels",
+    406 ERR_WASNOSUCHNICK Error "<client> :There was no such nickname",
+    409 ERR_NOORIGIN Error "<client> :No origin specified",
+    411 ERR_NORECIPIENT Error "<client> :No recipient given (<command>)",
+    412 ERR_NOTEXTTOSEND Error "<client> :No text to send",
+    417 ERR_INPUTTOOLONG Error "<client> :Input line was too long",
+    421 ERR_UNKNOWNCOMMAND Error "<client> <command> :Unknown command",
+    422 ERR_NOMOTD Motd "<client> :MOTD File is missing",
+    431 ERR_NONICKNAMEGIVEN Error "<client> :No nickname given",
+    432 ERR_ERRONEUSNICKNAME Error "<client> <nick> :Erroneus nickname",
+    433 ERR_NICKNAMEINUSE Error "<client> <nick> :Nickname is already in use",
+    436 ERR_NICKCOLLISION Error "<client> <nick> :Nickname collision KILL from <user>@<host>",
+    441 ERR_USERNOTINCHANNEL Error "<client> <nick> <channel> :They aren't on that channel",
+    442 ERR_NOTONCHANNEL Error "<client> <channel> :You're not on that channel",
+    443 ERR_USERONCHANNEL Error "<client> <nick> <channel> :is already on channel",
+    451 ERR_NOTREGISTERED Error "<client> :You have not registered",
+    461 ERR_NEEDMOREPARAMS Error "<client> <command> :Not enough parameters",
+    462 ERR_ALREADYREGISTERED Error "<client> :You may not reregister",
+    464 ERR_PASSWDMISMATCH Error "<client> :Password incorrect",
+    465 ERR_YOUREBANNEDCREEP Error "<client> :You are banned from this server.",
+    471 ERR_CHANNELISFULL Error "<client> <channel> :Cannot join channel (+l)",
+    472 ERR_UNKNOWNMODE Error "<client> <modechar> :is unknown mode char to me",
+    473 ERR_INVITEONLYCHAN Error "<client> <channel> :Cannot join channel (+i)",
+    474 ERR_BANNEDFROMCHAN Error "<client> <channel> :Cannot join channel (+b)",
+    475 ERR_BADCHANNELKEY Error "<client> <channel> :Cannot join channel (+k)",
+    476 ERR_BADCHANMASK Error "<channel> :Bad Channel Mask",
+    481 ERR_NOPRIVILEGES Error "<client> :Permission Denied- You're not an IRC operator",
+    482 ERR_CHANOPRIVSNEEDED Error "<client> <channel> :You're not channel operator",
+    483 ERR_CANTKILLSERVER Error "<client> :You cant kill a server!",
+    491 ERR_NOOPERHOST Error "<client> :No O-lines for your host",
+    501 ERR_UMODEUNKNOWNFLAG Error "<client> :Unknown MODE flag",
+    502 ERR_USERSDONTMATCH Error "<client> :Cant change mode for other users",
+    524 ERR_HELPNOTFOUND Help "<client> <subject> :No help available on this topic",
+    525 ERR_INVALIDKEY Error "<client> <target chan> :Key is not well-formed",
+    670 RPL_STARTTLS Server "<client> :STARTTLS successful, proceed with TLS handshake",
+    671 RPL_WHOISSECURE User "<client> <nick> :is using a secure connection",
+    691 ERR_STARTTLS Error "<client> :STARTTLS failed (Wrong moon phase)",
+    696 ERR_INVALIDMODEPARAM Error "<client> <target chan/user> <mode char> <parameter> :<description>",
+    704 RPL_HELPSTART Help "<client> <subject> :<first line of help section>",
+    705 RPL_HELPTXT Help "<client> <subject> :<line of help text>",
+    706 RPL_ENDOFHELP Help "<client> <subject> :<last line of help text>",
+    723 ERR_NOPRIVS Error "<client> <priv> :Insufficient oper privileges.",
+    730 RPL_MONONLINE Monitor "<client> :<target>[!<user>@<host>]{,<target>[!<user>@<host>]}",
+    731 RPL_MONOFFLINE Monitor "<client> :<target>{,<target>}",
+    732 RPL_MONLIST Monitor "<client> :<target>{,<target>}",
+    733 RPL_ENDOFMONLIST Monitor "<client> :End of MONITOR list",
+    734 ERR_MONLISTFULL Monitor "<client> <limit> <targets> :Monitor list is full.",
+    900 RPL_LOGGEDIN Sasl "<client> <nick>!<user>@<host> <account> :You are now logged in as <username>",
+    901 RPL_LOGGEDOUT Sasl "<client> <nick>!<user>@<host> :You are now logged out",
+    902 ERR_NICKLOCKED Sasl "<client> :You must use a nick assigned to you",
+    903 RPL_SASLSUCCESS Sasl "<client> :SASL authentication successful",
+    904 ERR_SASLFAIL Sasl "<client> :SASL authentication failed",
+    905 ERR_SASLTOOLONG Sasl "<client> :SASL message too long",
+    906 ERR_SASLABORTED Sasl "<client> :SASL authentication aborted",
+    907 ERR_SASLALREADY Sasl "<client> :You have already authenticated using SASL",
+    908 RPL_SASLMECHS Sasl "<client> <mechanisms> :are available SASL mechanisms",
+}
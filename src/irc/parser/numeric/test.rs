@@ -0,0 +1,48 @@
+use crate::irc::parser::Command;
+
+use super::{lookup, lookup_name, Category, ERR_NICKNAMEINUSE, NUMERICS, RPL_WELCOME};
+
+#[test]
+fn test_numerics_sorted_and_unique() {
+    // lookup uses a binary search, so the list has to be sorted
+    for pair in NUMERICS.windows(2) {
+        assert!(pair[0].code < pair[1].code, "{:?}", pair[1]);
+    }
+}
+
+#[test]
+fn test_lookup() {
+    let numeric = lookup(RPL_WELCOME).unwrap();
+    assert_eq!(numeric.name, "RPL_WELCOME");
+    assert_eq!(numeric.category, Category::Registration);
+    assert!(!numeric.is_error());
+
+    let numeric = lookup(433).unwrap();
+    assert_eq!(numeric.code, ERR_NICKNAMEINUSE);
+    assert_eq!(numeric.name, "ERR_NICKNAMEINUSE");
+    assert!(numeric.is_error());
+
+    assert!(lookup(999).is_none());
+}
+
+#[test]
+fn test_lookup_name() {
+    assert_eq!(lookup_name("RPL_NAMREPLY").unwrap().code, 353);
+    assert!(lookup_name("RPL_FOOBAR").is_none());
+}
+
+#[test]
+fn test_command_display() {
+    assert_eq!(Command::Digit3(1).to_string(), "001 RPL_WELCOME");
+    assert_eq!(Command::Digit3(999).to_string(), "999");
+    assert_eq!(Command::Cmd("PING".into()).to_string(), "PING");
+
+    assert_eq!(
+        format!("{:?}", Command::Digit3(433)),
+        "Digit3(433 ERR_NICKNAMEINUSE)"
+    );
+    assert_eq!(
+        format!("{:?}", Command::Cmd("PING".into())),
+        "Cmd(\"PING\")"
+    );
+}
@@ -0,0 +1,321 @@
+use yew::AttrValue;
+
+use super::{
+    generic_message::GenericMessage,
+    numeric::{self, Numeric},
+    params::Params,
+    typed_message::TypedMessageError,
+    Command,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Typed accessors for the most common numeric replies. All replies carry the
+/// `client` parameter, which is the nick name the server assigned to us.
+#[derive(Debug, PartialEq, Clone)]
+pub enum NumericReply {
+    // registration
+    Welcome {
+        client: AttrValue,
+        text: AttrValue,
+    },
+    YourHost {
+        client: AttrValue,
+        text: AttrValue,
+    },
+    Created {
+        client: AttrValue,
+        text: AttrValue,
+    },
+    MyInfo {
+        client: AttrValue,
+        servername: AttrValue,
+        version: AttrValue,
+        user_modes: AttrValue,
+        channel_modes: AttrValue,
+        channel_modes_with_param: Option<AttrValue>,
+    },
+    ISupport {
+        client: AttrValue,
+        /// Raw tokens, e.g. `CHANTYPES=#&` or `-EXCEPTS`
+        tokens: Vec<AttrValue>,
+    },
+
+    // channels
+    Topic {
+        client: AttrValue,
+        channel: AttrValue,
+        topic: AttrValue,
+    },
+    NoTopic {
+        client: AttrValue,
+        channel: AttrValue,
+    },
+    TopicWhoTime {
+        client: AttrValue,
+        channel: AttrValue,
+        setter: AttrValue,
+        set_at: u64,
+    },
+    ChannelModeIs {
+        client: AttrValue,
+        channel: AttrValue,
+        modestring: AttrValue,
+        arguments: Vec<AttrValue>,
+    },
+    CreationTime {
+        client: AttrValue,
+        channel: AttrValue,
+        created: u64,
+    },
+    NamReply {
+        client: AttrValue,
+        /// `=` public, `@` secret or `*` private channel
+        symbol: char,
+        channel: AttrValue,
+        /// Nick names including their membership prefixes, e.g. `@dan`
+        nicks: Vec<AttrValue>,
+    },
+    EndOfNames {
+        client: AttrValue,
+        channel: AttrValue,
+    },
+
+    // whois
+    WhoisUser {
+        client: AttrValue,
+        nick: AttrValue,
+        username: AttrValue,
+        host: AttrValue,
+        realname: AttrValue,
+    },
+    WhoisServer {
+        client: AttrValue,
+        nick: AttrValue,
+        server: AttrValue,
+        info: AttrValue,
+    },
+    WhoisOperator {
+        client: AttrValue,
+        nick: AttrValue,
+    },
+    WhoisIdle {
+        client: AttrValue,
+        nick: AttrValue,
+        idle: u64,
+        signon: Option<u64>,
+    },
+    WhoisChannels {
+        client: AttrValue,
+        nick: AttrValue,
+        /// Channel names including membership prefixes, e.g. `@#rust`
+        channels: Vec<AttrValue>,
+    },
+    WhoisAccount {
+        client: AttrValue,
+        nick: AttrValue,
+        account: AttrValue,
+    },
+    EndOfWhois {
+        client: AttrValue,
+        nick: AttrValue,
+    },
+
+    // message of the day
+    MotdStart {
+        client: AttrValue,
+        text: AttrValue,
+    },
+    Motd {
+        client: AttrValue,
+        line: AttrValue,
+    },
+    EndOfMotd {
+        client: AttrValue,
+    },
+
+    // channel list
+    ListStart {
+        client: AttrValue,
+    },
+    List {
+        client: AttrValue,
+        channel: AttrValue,
+        users: u32,
+        topic: AttrValue,
+    },
+    ListEnd {
+        client: AttrValue,
+    },
+
+    /// Any `ERR_*` numeric, e.g. `ERR_NICKNAMEINUSE`
+    Error {
+        numeric: &'static Numeric,
+        client: AttrValue,
+        /// Parameters between `client` and the description
+        arguments: Vec<AttrValue>,
+        description: AttrValue,
+    },
+}
+
+impl TryFrom<&GenericMessage> for NumericReply {
+    type Error = TypedMessageError;
+
+    fn try_from(msg: &GenericMessage) -> Result<Self, TypedMessageError> {
+        use NumericReply::*;
+
+        let unknown = || TypedMessageError::UnknownCommand(msg.command.clone());
+        let numeric = match msg.command {
+            Command::Digit3(code) => numeric::lookup(code).ok_or_else(unknown)?,
+            Command::Cmd(_) => return Err(unknown()),
+        };
+
+        let p = Params {
+            command: numeric.name.into(),
+            params: &msg.parameters,
+        };
+        let client = p.required(0, "client")?;
+
+        Ok(match numeric.code {
+            numeric::RPL_WELCOME => Welcome {
+                client,
+                text: p.text(1, "text")?,
+            },
+            numeric::RPL_YOURHOST => YourHost {
+                client,
+                text: p.text(1, "text")?,
+            },
+            numeric::RPL_CREATED => Created {
+                client,
+                text: p.text(1, "text")?,
+            },
+            numeric::RPL_MYINFO => MyInfo {
+                client,
+                servername: p.required(1, "servername")?,
+                version: p.required(2, "version")?,
+                user_modes: p.required(3, "available user modes")?,
+                channel_modes: p.required(4, "available channel modes")?,
+                channel_modes_with_param: p.optional(5),
+            },
+            // the last parameter is the human readable "are supported" text
+            numeric::RPL_ISUPPORT => match &msg.parameters[1..] {
+                [tokens @ .., _text] if !tokens.is_empty() => ISupport {
+                    client,
+                    tokens: tokens.to_vec(),
+                },
+                _ => {
+                    return Err(TypedMessageError::MissingParameter {
+                        command: numeric.name.into(),
+                        parameter: "tokens",
+                    })
+                }
+            },
+            numeric::RPL_TOPIC => Topic {
+                client,
+                channel: p.required(1, "channel")?,
+                topic: p.text(2, "topic")?,
+            },
+            numeric::RPL_NOTOPIC => NoTopic {
+                client,
+                channel: p.required(1, "channel")?,
+            },
+            numeric::RPL_TOPICWHOTIME => TopicWhoTime {
+                client,
+                channel: p.required(1, "channel")?,
+                setter: p.required(2, "nick")?,
+                set_at: p.number(3, "setat")?,
+            },
+            numeric::RPL_CHANNELMODEIS => ChannelModeIs {
+                client,
+                channel: p.required(1, "channel")?,
+                modestring: p.required(2, "modestring")?,
+                arguments: p.rest(3),
+            },
+            numeric::RPL_CREATIONTIME => CreationTime {
+                client,
+                channel: p.required(1, "channel")?,
+                created: p.number(2, "creationtime")?,
+            },
+            numeric::RPL_NAMREPLY => NamReply {
+                client,
+                symbol: p.required(1, "symbol")?.chars().next().unwrap_or('='),
+                channel: p.required(2, "channel")?,
+                nicks: p.words(3, "nicks")?,
+            },
+            numeric::RPL_ENDOFNAMES => EndOfNames {
+                client,
+                channel: p.required(1, "channel")?,
+            },
+            numeric::RPL_WHOISUSER => WhoisUser {
+                client,
+                nick: p.required(1, "nick")?,
+                username: p.required(2, "username")?,
+                host: p.required(3, "host")?,
+                realname: p.text(5, "realname")?,
+            },
+            numeric::RPL_WHOISSERVER => WhoisServer {
+                client,
+                nick: p.required(1, "nick")?,
+                server: p.required(2, "server")?,
+                info: p.text(3, "server info")?,
+            },
+            numeric::RPL_WHOISOPERATOR => WhoisOperator {
+                client,
+                nick: p.required(1, "nick")?,
+            },
+            numeric::RPL_WHOISIDLE => WhoisIdle {
+                client,
+                nick: p.required(1, "nick")?,
+                idle: p.number(2, "secs")?,
+                signon: p.number(3, "signon").ok(),
+            },
+            numeric::RPL_WHOISCHANNELS => WhoisChannels {
+                client,
+                nick: p.required(1, "nick")?,
+                channels: p.words(2, "channels")?,
+            },
+            numeric::RPL_WHOISACCOUNT => WhoisAccount {
+                client,
+                nick: p.required(1, "nick")?,
+                account: p.required(2, "account")?,
+            },
+            numeric::RPL_ENDOFWHOIS => EndOfWhois {
+                client,
+                nick: p.required(1, "nick")?,
+            },
+            numeric::RPL_MOTDSTART => MotdStart {
+                client,
+                text: p.text(1, "text")?,
+            },
+            numeric::RPL_MOTD => Motd {
+                client,
+                line: p.text(1, "line of the motd")?,
+            },
+            numeric::RPL_ENDOFMOTD => EndOfMotd { client },
+            numeric::RPL_LISTSTART => ListStart { client },
+            numeric::RPL_LIST => List {
+                client,
+                channel: p.required(1, "channel")?,
+                users: p.number(2, "client count")?,
+                topic: p.text(3, "topic")?,
+            },
+            numeric::RPL_LISTEND => ListEnd { client },
+            _ if numeric.is_error() => match &msg.parameters[1..] {
+                [arguments @ .., description] => Error {
+                    numeric,
+                    client,
+                    arguments: arguments.to_vec(),
+                    description: description.clone(),
+                },
+                [] => Error {
+                    numeric,
+                    client,
+                    arguments: vec![],
+                    description: "".into(),
+                },
+            },
+            _ => return Err(unknown()),
+        })
+    }
+}
@@ -0,0 +1,202 @@
+use crate::irc::parser::{
+    message::Message,
+    numeric::{self, ERR_NICKNAMEINUSE},
+    typed_message::TypedMessageError,
+};
+
+use super::NumericReply;
+
+fn parse(input: &str) -> NumericReply {
+    match Message::parse(input) {
+        Err(err) => panic!("{err}"),
+        Ok(msg) => msg.to_numeric().unwrap(),
+    }
+}
+
+#[test]
+fn test_welcome() {
+    assert_eq!(
+        parse(":irc.example.com 001 dan :Welcome to the Example Network, dan"),
+        NumericReply::Welcome {
+            client: "dan".into(),
+            text: "Welcome to the Example Network, dan".into(),
+        }
+    );
+}
+
+#[test]
+fn test_isupport() {
+    assert_eq!(
+        parse(":irc.example.com 005 dan CHANTYPES=# -EXCEPTS NICKLEN=30 :are supported by this server"),
+        NumericReply::ISupport {
+            client: "dan".into(),
+            tokens: vec!["CHANTYPES=#".into(), "-EXCEPTS".into(), "NICKLEN=30".into()],
+        }
+    );
+
+    let msg = Message::parse(":irc.example.com 005 dan :are supported").unwrap();
+    assert_eq!(
+        msg.to_numeric(),
+        Err(TypedMessageError::MissingParameter {
+            command: "RPL_ISUPPORT".into(),
+            parameter: "tokens"
+        })
+    );
+}
+
+#[test]
+fn test_names() {
+    assert_eq!(
+        parse(":irc.example.com 353 dan = #rust :@alice +bob carol"),
+        NumericReply::NamReply {
+            client: "dan".into(),
+            symbol: '=',
+            channel: "#rust".into(),
+            nicks: vec!["@alice".into(), "+bob".into(), "carol".into()],
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 366 dan #rust :End of /NAMES list"),
+        NumericReply::EndOfNames {
+            client: "dan".into(),
+            channel: "#rust".into(),
+        }
+    );
+}
+
+#[test]
+fn test_topic() {
+    assert_eq!(
+        parse(":irc.example.com 332 dan #rust :Rust programming"),
+        NumericReply::Topic {
+            client: "dan".into(),
+            channel: "#rust".into(),
+            topic: "Rust programming".into(),
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 333 dan #rust alice 1700000000"),
+        NumericReply::TopicWhoTime {
+            client: "dan".into(),
+            channel: "#rust".into(),
+            setter: "alice".into(),
+            set_at: 1700000000,
+        }
+    );
+
+    let msg = Message::parse(":irc.example.com 333 dan #rust alice yesterday").unwrap();
+    assert_eq!(
+        msg.to_numeric(),
+        Err(TypedMessageError::InvalidParameter {
+            command: "RPL_TOPICWHOTIME".into(),
+            parameter: "setat",
+            value: "yesterday".into(),
+        })
+    );
+}
+
+#[test]
+fn test_channel_modes() {
+    assert_eq!(
+        parse(":irc.example.com 324 dan #rust +ntkl key 50"),
+        NumericReply::ChannelModeIs {
+            client: "dan".into(),
+            channel: "#rust".into(),
+            modestring: "+ntkl".into(),
+            arguments: vec!["key".into(), "50".into()],
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 324 dan #rust +nt"),
+        NumericReply::ChannelModeIs {
+            client: "dan".into(),
+            channel: "#rust".into(),
+            modestring: "+nt".into(),
+            arguments: vec![],
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 329 dan #rust 1600000000"),
+        NumericReply::CreationTime {
+            client: "dan".into(),
+            channel: "#rust".into(),
+            created: 1600000000,
+        }
+    );
+}
+
+#[test]
+fn test_whois() {
+    assert_eq!(
+        parse(":irc.example.com 311 dan alice ~a example.org * :Alice A."),
+        NumericReply::WhoisUser {
+            client: "dan".into(),
+            nick: "alice".into(),
+            username: "~a".into(),
+            host: "example.org".into(),
+            realname: "Alice A.".into(),
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 317 dan alice 42 1700000000 :seconds idle, signon time"),
+        NumericReply::WhoisIdle {
+            client: "dan".into(),
+            nick: "alice".into(),
+            idle: 42,
+            signon: Some(1700000000),
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 319 dan alice :@#rust +#irc"),
+        NumericReply::WhoisChannels {
+            client: "dan".into(),
+            nick: "alice".into(),
+            channels: vec!["@#rust".into(), "+#irc".into()],
+        }
+    );
+}
+
+#[test]
+fn test_motd_list() {
+    assert_eq!(
+        parse(":irc.example.com 372 dan :- Be nice"),
+        NumericReply::Motd {
+            client: "dan".into(),
+            line: "- Be nice".into(),
+        }
+    );
+    assert_eq!(
+        parse(":irc.example.com 322 dan #rust 42 :Rust programming"),
+        NumericReply::List {
+            client: "dan".into(),
+            channel: "#rust".into(),
+            users: 42,
+            topic: "Rust programming".into(),
+        }
+    );
+}
+
+#[test]
+fn test_error() {
+    assert_eq!(
+        parse(":irc.example.com 433 * dan :Nickname is already in use"),
+        NumericReply::Error {
+            numeric: numeric::lookup(ERR_NICKNAMEINUSE).unwrap(),
+            client: "*".into(),
+            arguments: vec!["dan".into()],
+            description: "Nickname is already in use".into(),
+        }
+    );
+}
+
+#[test]
+fn test_not_a_numeric_reply() {
+    assert!(Message::parse("PING token").unwrap().to_numeric().is_err());
+    // known numeric without typed accessor
+    assert!(
+        Message::parse(":irc.example.com 251 dan :There are 3 users")
+            .unwrap()
+            .to_numeric()
+            .is_err()
+    );
+}
@@ -0,0 +1,101 @@
+use yew::AttrValue;
+
+use super::typed_message::TypedMessageError;
+
+#[cfg(test)]
+mod test;
+
+/// Parameters of a message by position. Missing parameters are `None` or an
+/// error. Empty parameters are kept, e.g. `TOPIC #rust :` clears the topic,
+/// except for `required` ones, which name something and can not be empty.
+pub struct Params<'a> {
+    /// Name of the command or numeric, for errors
+    pub command: AttrValue,
+    pub params: &'a [AttrValue],
+}
+
+impl Params<'_> {
+    /// Parameter which must not be empty, e.g. a nick or channel
+    pub fn required(
+        &self,
+        idx: usize,
+        parameter: &'static str,
+    ) -> Result<AttrValue, TypedMessageError> {
+        match self.params.get(idx) {
+            Some(param) if !param.is_empty() => Ok(param.clone()),
+            _ => Err(self.missing(parameter)),
+        }
+    }
+
+    /// Parameter which may be empty, e.g. a topic or the text of a reply
+    pub fn text(
+        &self,
+        idx: usize,
+        parameter: &'static str,
+    ) -> Result<AttrValue, TypedMessageError> {
+        self.optional(idx).ok_or_else(|| self.missing(parameter))
+    }
+
+    pub fn optional(&self, idx: usize) -> Option<AttrValue> {
+        self.params.get(idx).cloned()
+    }
+
+    pub fn number<T: std::str::FromStr>(
+        &self,
+        idx: usize,
+        parameter: &'static str,
+    ) -> Result<T, TypedMessageError> {
+        let value = self.required(idx, parameter)?;
+        value
+            .parse()
+            .map_err(|_| TypedMessageError::InvalidParameter {
+                command: self.command.clone(),
+                parameter,
+                value,
+            })
+    }
+
+    /// Comma separated list, e.g. `#a,#b,#c`
+    pub fn list(&self, idx: usize) -> Vec<AttrValue> {
+        self.params
+            .get(idx)
+            .map_or(vec![], |param| split_list(param))
+    }
+
+    /// Space separated list, which may be empty
+    pub fn words(
+        &self,
+        idx: usize,
+        parameter: &'static str,
+    ) -> Result<Vec<AttrValue>, TypedMessageError> {
+        Ok(self
+            .text(idx, parameter)?
+            .split(' ')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_owned().into())
+            .collect())
+    }
+
+    /// All parameters from `idx` on
+    pub fn rest(&self, idx: usize) -> Vec<AttrValue> {
+        self.params
+            .get(idx..)
+            .map_or(vec![], |params| params.to_vec())
+    }
+
+    fn missing(&self, parameter: &'static str) -> TypedMessageError {
+        TypedMessageError::MissingParameter {
+            command: self.command.clone(),
+            parameter,
+        }
+    }
+}
+
+/// Split comma separated parameter lists, e.g. `#a,#b,#c`
+fn split_list(param: &str) -> Vec<AttrValue> {
+    param
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned().into())
+        .collect()
+}
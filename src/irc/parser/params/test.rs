@@ -0,0 +1,42 @@
+use yew::AttrValue;
+
+use crate::irc::parser::typed_message::TypedMessageError;
+
+use super::Params;
+
+#[test]
+fn test_empty_params() {
+    let params: Vec<AttrValue> = vec!["#rust".into(), "".into()];
+    let p = Params {
+        command: "TOPIC".into(),
+        params: &params,
+    };
+    assert_eq!(p.required(0, "channel"), Ok("#rust".into()));
+    assert_eq!(p.text(1, "topic"), Ok("".into()));
+    assert_eq!(p.optional(1), Some("".into()));
+    assert_eq!(p.words(1, "nicks"), Ok(vec![]));
+
+    // required parameters name something, so they can not be empty
+    let missing = |parameter| TypedMessageError::MissingParameter {
+        command: "TOPIC".into(),
+        parameter,
+    };
+    assert_eq!(p.required(1, "topic"), Err(missing("topic")));
+    assert_eq!(p.required(2, "topic"), Err(missing("topic")));
+    assert_eq!(p.text(2, "topic"), Err(missing("topic")));
+    assert_eq!(p.optional(2), None);
+}
+
+#[test]
+fn test_lists() {
+    let params: Vec<AttrValue> = vec!["#a,,#b".into(), "1".into(), "x".into()];
+    let p = Params {
+        command: "JOIN".into(),
+        params: &params,
+    };
+    assert_eq!(p.list(0), vec!["#a", "#b"]);
+    assert_eq!(p.list(3), Vec::<AttrValue>::new());
+    assert_eq!(p.number::<u32>(1, "count"), Ok(1));
+    assert_eq!(p.rest(1), vec!["1", "x"]);
+    assert_eq!(p.rest(4), Vec::<AttrValue>::new());
+}
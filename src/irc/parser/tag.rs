@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod test;
+
+/// Client-only tags are prefixed with a `+`
+pub const CLIENT_PREFIX: char = '+';
+
+/// Check if tag key is a client-only tag, e.g. `+example.com/foo`
+pub fn is_client_only(key: &str) -> bool {
+    key.starts_with(CLIENT_PREFIX)
+}
+
+/// Vendor part of a tag key, e.g. `example.com` for `+example.com/foo`
+pub fn vendor(key: &str) -> Option<&str> {
+    let key = key.strip_prefix(CLIENT_PREFIX).unwrap_or(key);
+    key.rsplit_once('/').map(|(vendor, _)| vendor)
+}
+
+/// Name of a tag key without client prefix and vendor, e.g. `foo` for
+/// `+example.com/foo`
+pub fn name(key: &str) -> &str {
+    let key = key.strip_prefix(CLIENT_PREFIX).unwrap_or(key);
+    key.rsplit_once('/').map_or(key, |(_, name)| name)
+}
+
+/// Escape a raw tag value for serialization, following the spec:
+///
+/// https://ircv3.net/specs/extensions/message-tags.html
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            ';' => escaped.push_str("\\:"),
+            ' ' => escaped.push_str("\\s"),
+            '\\' => escaped.push_str("\\\\"),
+            '\r' => escaped.push_str("\\r"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unescape a tag value as received on the wire.
+///
+/// Invalid escapes drop the backslash (`\b` becomes `b`) and a trailing single
+/// backslash is removed, as required by the spec.
+pub fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some(':') => unescaped.push(';'),
+            Some('s') => unescaped.push(' '),
+            Some('\\') => unescaped.push('\\'),
+            Some('r') => unescaped.push('\r'),
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
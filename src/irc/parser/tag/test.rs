@@ -0,0 +1,47 @@
+use super::{escape, is_client_only, name, unescape, vendor};
+
+#[test]
+fn test_escape() {
+    assert_eq!(escape("a;b c\\d\re\nf"), "a\\:b\\sc\\\\d\\re\\nf");
+    assert_eq!(escape("plain"), "plain");
+    assert_eq!(escape(""), "");
+}
+
+#[test]
+fn test_unescape() {
+    assert_eq!(unescape("a\\:b\\sc\\\\d\\re\\nf"), "a;b c\\d\re\nf");
+    assert_eq!(unescape("plain"), "plain");
+}
+
+#[test]
+fn test_unescape_invalid() {
+    // unknown escapes drop the backslash
+    assert_eq!(unescape("\\b"), "b");
+    // trailing backslash is dropped
+    assert_eq!(unescape("abc\\"), "abc");
+}
+
+#[test]
+fn test_escape_roundtrip() {
+    for value in ["", ";", " ", "\\", "\r\n", "\\s", "a b;c\\d", "ünï cödé"] {
+        assert_eq!(unescape(&escape(value)), value);
+    }
+}
+
+#[test]
+fn test_key_parts() {
+    let key = "+example.com/foo";
+    assert!(is_client_only(key));
+    assert_eq!(vendor(key), Some("example.com"));
+    assert_eq!(name(key), "foo");
+
+    let key = "time";
+    assert!(!is_client_only(key));
+    assert_eq!(vendor(key), None);
+    assert_eq!(name(key), "time");
+
+    let key = "draft/reply";
+    assert!(!is_client_only(key));
+    assert_eq!(vendor(key), Some("draft"));
+    assert_eq!(name(key), "reply");
+}
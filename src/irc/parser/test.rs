@@ -1,6 +1,6 @@
 use core::panic;
 
-use super::{Message, User};
+use super::{grammar::Rule, Message, ParseError, User};
 
 fn parse(input: &str) -> Message {
     match Message::parse(input) {
@@ -122,3 +122,147 @@ fn test_source_user() {
             .build()
     );
 }
+
+#[test]
+fn test_tags_escaped_values() {
+    let input = "@a=semi\\:colon;b=space\\sbar;c=back\\\\slash;d=cr\\rlf\\n TEST";
+    let msg = parse(input);
+    assert_eq!(msg.to_string(), input);
+    assert_eq!(
+        msg,
+        Message::cmd("TEST")
+            .tag("a", Some("semi;colon"))
+            .tag("b", Some("space bar"))
+            .tag("c", Some("back\\slash"))
+            .tag("d", Some("cr\rlf\n"))
+            .build()
+    );
+}
+
+#[test]
+fn test_tags_invalid_escapes() {
+    let msg = parse("@a=\\b;b=trailing\\ TEST");
+    assert_eq!(
+        msg,
+        Message::cmd("TEST")
+            .tag("a", Some("b"))
+            .tag("b", Some("trailing"))
+            .build()
+    );
+}
+
+#[test]
+fn test_tags_escape_on_display() {
+    let msg = Message::cmd("TEST")
+        .tag("+draft/reply", Some("a; b\\c\r\n"))
+        .build();
+    let output = msg.to_string();
+    assert_eq!(output, "@+draft/reply=a\\:\\sb\\\\c\\r\\n TEST");
+    assert_eq!(parse(&output), msg);
+}
+
+#[test]
+fn test_tags_client_prefix_and_vendor() {
+    let input = "@+example.com/foo=bar;+typing=active;example.org/baz TEST";
+    let msg = parse(input);
+    assert_eq!(msg.to_string(), input);
+    assert_eq!(
+        msg,
+        Message::cmd("TEST")
+            .tag("+example.com/foo", Some("bar"))
+            .tag("+typing", Some("active"))
+            .tag("example.org/baz", None)
+            .build()
+    );
+}
+
+/// Lines which are not valid IRC messages and have to be rejected
+const MALFORMED: &[&str] = &[
+    "",
+    " ",
+    "\r\n",
+    ":",
+    "@",
+    "@ TEST",
+    "@;",
+    "@=value TEST",
+    "@a=b",
+    "@a=b :source",
+    ":source",
+    ":source ",
+    ": TEST",
+    ":!user@host TEST",
+    "1",
+    "12",
+    "1234",
+    "12 TEST",
+    "٣٣٣",
+    ":server ١٢٣ nick :unicode digits",
+    "TEST\0",
+    "TEST a\0b",
+    "TEST :a\nb",
+    "TEST\ra",
+    "PRIVMSG #a :b\r\nPRIVMSG #a :c",
+    "TE-ST",
+    "\u{1F600}",
+];
+
+#[test]
+fn test_malformed_corpus() {
+    for input in MALFORMED {
+        let res = Message::parse(input);
+        assert!(res.is_err(), "{input:?} parsed as {res:?}");
+    }
+}
+
+#[test]
+fn test_parse_error_syntax() {
+    let err = Message::parse("@a=b").unwrap_err();
+    assert!(matches!(err, ParseError::Syntax { .. }), "{err:?}");
+    assert_eq!(err.span(), 4..4);
+    assert!(!err.message().is_empty());
+    assert!(err.to_string().contains("4..4"));
+}
+
+#[test]
+fn test_parse_error_invalid_digits() {
+    let err = Message::parse(":server ١٢٣ nick").unwrap_err();
+    assert_eq!(err.rule(), Some(Rule::digit3));
+    assert!(matches!(err, ParseError::InvalidValue { .. }), "{err:?}");
+    // byte offsets of the three 2-byte arabic-indic digits
+    assert_eq!(err.span(), 8..14);
+}
+
+/// Feed pseudo random lines built from characters with special meaning into
+/// the parser. It must never panic and valid messages have to survive a round
+/// trip through `Display` and parse again.
+#[test]
+fn test_parse_never_panics() {
+    const ALPHABET: &[&str] = &[
+        "@", ":", ";", "=", "+", "/", "!", "*", "-", ",", " ", "  ", "\\", "\r", "\n", "\0", "a",
+        "Z", "0", "9", "٣", "ü", "#", "CAP", "LS", "ACK", "NAK", "PRIVMSG", "001", "\x01", "\\s",
+        "\\:",
+    ];
+
+    // simple linear congruential generator, to stay deterministic
+    let mut seed: u64 = 0x5eed;
+    let mut next = move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize
+    };
+
+    for _ in 0..5000 {
+        let len = next() % 16;
+        let input = (0..len)
+            .map(|_| ALPHABET[next() % ALPHABET.len()])
+            .collect::<String>();
+
+        if let Ok(msg) = Message::parse(&input) {
+            let output = msg.to_string();
+            let res = Message::parse(&output);
+            assert!(res.is_ok(), "{input:?} -> {output:?}: {res:?}");
+        }
+    }
+}
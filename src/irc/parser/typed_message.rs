@@ -0,0 +1,583 @@
+use std::fmt::Display;
+
+use yew::AttrValue;
+
+use super::{generic_message::GenericMessage, params::Params, Command};
+
+#[cfg(test)]
+mod test;
+
+/// Strongly typed client and server commands, as defined in RFC 1459, RFC 2812
+/// and the Modern IRC documentation:
+///
+/// https://modern.ircdocs.horse/#client-messages
+///
+/// Numeric replies are not covered here, see `GenericMessage` instead.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypedMessage {
+    // connection messages
+    Pass {
+        password: AttrValue,
+    },
+    Nick {
+        nickname: AttrValue,
+    },
+    User {
+        username: AttrValue,
+        realname: AttrValue,
+    },
+    Oper {
+        name: AttrValue,
+        password: AttrValue,
+    },
+    Quit {
+        reason: Option<AttrValue>,
+    },
+    Error {
+        reason: AttrValue,
+    },
+    Ping {
+        token: AttrValue,
+    },
+    Pong {
+        server: Option<AttrValue>,
+        token: AttrValue,
+    },
+    Authenticate {
+        data: AttrValue,
+    },
+
+    // channel operations
+    Join {
+        channels: Vec<AttrValue>,
+        keys: Vec<AttrValue>,
+        /// Account name, only sent by servers with `extended-join` enabled
+        account: Option<AttrValue>,
+        /// Real name, only sent by servers with `extended-join` enabled
+        realname: Option<AttrValue>,
+    },
+    /// `JOIN 0`, leave all channels
+    PartAll,
+    Part {
+        channels: Vec<AttrValue>,
+        reason: Option<AttrValue>,
+    },
+    Topic {
+        channel: AttrValue,
+        topic: Option<AttrValue>,
+    },
+    Names {
+        channels: Vec<AttrValue>,
+    },
+    List {
+        channels: Vec<AttrValue>,
+        elist: Option<AttrValue>,
+    },
+    Invite {
+        nickname: AttrValue,
+        channel: AttrValue,
+    },
+    Kick {
+        channel: AttrValue,
+        users: Vec<AttrValue>,
+        comment: Option<AttrValue>,
+    },
+    Mode {
+        target: AttrValue,
+        modestring: Option<AttrValue>,
+        arguments: Vec<AttrValue>,
+    },
+
+    // sending messages
+    Privmsg {
+        target: AttrValue,
+        text: AttrValue,
+    },
+    Notice {
+        target: AttrValue,
+        text: AttrValue,
+    },
+    Tagmsg {
+        target: AttrValue,
+    },
+
+    // server queries and commands
+    Motd {
+        server: Option<AttrValue>,
+    },
+    Version {
+        server: Option<AttrValue>,
+    },
+    Admin {
+        server: Option<AttrValue>,
+    },
+    Lusers,
+    Time {
+        server: Option<AttrValue>,
+    },
+    Stats {
+        query: AttrValue,
+        server: Option<AttrValue>,
+    },
+    Info,
+    Help {
+        subject: Option<AttrValue>,
+    },
+    Links,
+    Connect {
+        target: AttrValue,
+        port: Option<AttrValue>,
+        remote: Option<AttrValue>,
+    },
+    Kill {
+        nickname: AttrValue,
+        comment: AttrValue,
+    },
+    Rehash,
+    Restart,
+    Squit {
+        server: AttrValue,
+        comment: AttrValue,
+    },
+
+    // user based queries
+    Who {
+        mask: AttrValue,
+        /// WHOX field selection, e.g. `%cuhnar`
+        fields: Option<AttrValue>,
+    },
+    Whois {
+        server: Option<AttrValue>,
+        nickname: AttrValue,
+    },
+    Whowas {
+        nickname: AttrValue,
+        count: Option<AttrValue>,
+    },
+    Userhost {
+        nicknames: Vec<AttrValue>,
+    },
+    Ison {
+        nicknames: Vec<AttrValue>,
+    },
+
+    // optional messages
+    Away {
+        text: Option<AttrValue>,
+    },
+    Wallops {
+        text: AttrValue,
+    },
+
+    // IRCv3 extensions
+    /// `account-notify`, `None` if the user logged out (`ACCOUNT *`)
+    Account {
+        account: Option<AttrValue>,
+    },
+    Chghost {
+        username: AttrValue,
+        host: AttrValue,
+    },
+    Setname {
+        realname: AttrValue,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypedMessageError {
+    UnknownCommand(Command),
+    MissingParameter {
+        command: AttrValue,
+        parameter: &'static str,
+    },
+    InvalidParameter {
+        command: AttrValue,
+        parameter: &'static str,
+        value: AttrValue,
+    },
+}
+
+impl Display for TypedMessageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => write!(f, "Unknown command: {command}"),
+            Self::MissingParameter { command, parameter } => {
+                write!(f, "{command}: missing parameter <{parameter}>")
+            }
+            Self::InvalidParameter {
+                command,
+                parameter,
+                value,
+            } => write!(f, "{command}: invalid parameter <{parameter}>: {value:?}"),
+        }
+    }
+}
+
+fn join_list(list: &[AttrValue]) -> AttrValue {
+    list.join(",").into()
+}
+
+impl TryFrom<&GenericMessage> for TypedMessage {
+    type Error = TypedMessageError;
+
+    fn try_from(msg: &GenericMessage) -> Result<Self, TypedMessageError> {
+        use TypedMessage::*;
+
+        let cmd = match &msg.command {
+            Command::Cmd(cmd) => cmd.to_ascii_uppercase(),
+            Command::Digit3(_) => {
+                return Err(TypedMessageError::UnknownCommand(msg.command.clone()))
+            }
+        };
+
+        let p = Params {
+            command: cmd.clone().into(),
+            params: &msg.parameters,
+        };
+
+        Ok(match cmd.as_str() {
+            "PASS" => Pass {
+                password: p.required(0, "password")?,
+            },
+            "NICK" => Nick {
+                nickname: p.required(0, "nickname")?,
+            },
+            "USER" => User {
+                username: p.required(0, "username")?,
+                realname: p.required(3, "realname")?,
+            },
+            "OPER" => Oper {
+                name: p.required(0, "name")?,
+                password: p.required(1, "password")?,
+            },
+            "QUIT" => Quit {
+                reason: p.optional(0),
+            },
+            "ERROR" => Error {
+                reason: p.optional(0).unwrap_or_default(),
+            },
+            "PING" => Ping {
+                token: p.required(0, "token")?,
+            },
+            "PONG" if msg.parameters.len() > 1 => Pong {
+                server: p.optional(0),
+                token: p.required(1, "token")?,
+            },
+            "PONG" => Pong {
+                server: None,
+                token: p.required(0, "token")?,
+            },
+            "AUTHENTICATE" => Authenticate {
+                data: p.required(0, "data")?,
+            },
+            "JOIN" if p.optional(0).as_deref() == Some("0") => PartAll,
+            "JOIN" => {
+                let channels = p.list(0);
+                if channels.is_empty() {
+                    p.required(0, "channel")?;
+                }
+                // extended-join sends account name and real name instead of keys
+                if msg.parameters.len() == 3 {
+                    Join {
+                        channels,
+                        keys: vec![],
+                        account: p.optional(1).filter(|account| account != "*"),
+                        realname: p.optional(2),
+                    }
+                } else {
+                    Join {
+                        channels,
+                        keys: p.list(1),
+                        account: None,
+                        realname: None,
+                    }
+                }
+            }
+            "PART" => {
+                let channels = p.list(0);
+                if channels.is_empty() {
+                    p.required(0, "channel")?;
+                }
+                Part {
+                    channels,
+                    reason: p.optional(1),
+                }
+            }
+            "TOPIC" => Topic {
+                channel: p.required(0, "channel")?,
+                topic: p.optional(1),
+            },
+            "NAMES" => Names {
+                channels: p.list(0),
+            },
+            "LIST" => List {
+                channels: p.list(0),
+                elist: p.optional(1),
+            },
+            "INVITE" => Invite {
+                nickname: p.required(0, "nickname")?,
+                channel: p.required(1, "channel")?,
+            },
+            "KICK" => {
+                let channel = p.required(0, "channel")?;
+                let users = p.list(1);
+                if users.is_empty() {
+                    p.required(1, "user")?;
+                }
+                Kick {
+                    channel,
+                    users,
+                    comment: p.optional(2),
+                }
+            }
+            "MODE" => Mode {
+                target: p.required(0, "target")?,
+                modestring: p.optional(1),
+                arguments: p.rest(2),
+            },
+            "PRIVMSG" => Privmsg {
+                target: p.required(0, "target")?,
+                text: p.required(1, "text")?,
+            },
+            "NOTICE" => Notice {
+                target: p.required(0, "target")?,
+                text: p.required(1, "text")?,
+            },
+            "TAGMSG" => Tagmsg {
+                target: p.required(0, "target")?,
+            },
+            "MOTD" => Motd {
+                server: p.optional(0),
+            },
+            "VERSION" => Version {
+                server: p.optional(0),
+            },
+            "ADMIN" => Admin {
+                server: p.optional(0),
+            },
+            "LUSERS" => Lusers,
+            "TIME" => Time {
+                server: p.optional(0),
+            },
+            "STATS" => Stats {
+                query: p.required(0, "query")?,
+                server: p.optional(1),
+            },
+            "INFO" => Info,
+            "HELP" => Help {
+                subject: p.optional(0),
+            },
+            "LINKS" => Links,
+            "CONNECT" => Connect {
+                target: p.required(0, "target server")?,
+                port: p.optional(1),
+                remote: p.optional(2),
+            },
+            "KILL" => Kill {
+                nickname: p.required(0, "nickname")?,
+                comment: p.required(1, "comment")?,
+            },
+            "REHASH" => Rehash,
+            "RESTART" => Restart,
+            "SQUIT" => Squit {
+                server: p.required(0, "server")?,
+                comment: p.required(1, "comment")?,
+            },
+            "WHO" => Who {
+                mask: p.required(0, "mask")?,
+                fields: p.optional(1),
+            },
+            "WHOIS" if msg.parameters.len() > 1 => Whois {
+                server: p.optional(0),
+                nickname: p.required(1, "nick")?,
+            },
+            "WHOIS" => Whois {
+                server: None,
+                nickname: p.required(0, "nick")?,
+            },
+            "WHOWAS" => Whowas {
+                nickname: p.required(0, "nick")?,
+                count: p.optional(1),
+            },
+            "USERHOST" => Userhost {
+                nicknames: p.rest(0),
+            },
+            "ISON" => Ison {
+                nicknames: p.rest(0),
+            },
+            "AWAY" => Away {
+                text: p.optional(0).filter(|text| !text.is_empty()),
+            },
+            "WALLOPS" => Wallops {
+                text: p.required(0, "text")?,
+            },
+            "ACCOUNT" => Account {
+                account: Some(p.required(0, "accountname")?).filter(|account| account != "*"),
+            },
+            "CHGHOST" => Chghost {
+                username: p.required(0, "new_username")?,
+                host: p.required(1, "new_hostname")?,
+            },
+            "SETNAME" => Setname {
+                realname: p.required(0, "realname")?,
+            },
+            _ => return Err(TypedMessageError::UnknownCommand(msg.command.clone())),
+        })
+    }
+}
+
+impl TypedMessage {
+    /// Wire name of the command
+    pub fn command(&self) -> &'static str {
+        use TypedMessage::*;
+        match self {
+            Pass { .. } => "PASS",
+            Nick { .. } => "NICK",
+            User { .. } => "USER",
+            Oper { .. } => "OPER",
+            Quit { .. } => "QUIT",
+            Error { .. } => "ERROR",
+            Ping { .. } => "PING",
+            Pong { .. } => "PONG",
+            Authenticate { .. } => "AUTHENTICATE",
+            Join { .. } | PartAll => "JOIN",
+            Part { .. } => "PART",
+            Topic { .. } => "TOPIC",
+            Names { .. } => "NAMES",
+            List { .. } => "LIST",
+            Invite { .. } => "INVITE",
+            Kick { .. } => "KICK",
+            Mode { .. } => "MODE",
+            Privmsg { .. } => "PRIVMSG",
+            Notice { .. } => "NOTICE",
+            Tagmsg { .. } => "TAGMSG",
+            Motd { .. } => "MOTD",
+            Version { .. } => "VERSION",
+            Admin { .. } => "ADMIN",
+            Lusers => "LUSERS",
+            Time { .. } => "TIME",
+            Stats { .. } => "STATS",
+            Info => "INFO",
+            Help { .. } => "HELP",
+            Links => "LINKS",
+            Connect { .. } => "CONNECT",
+            Kill { .. } => "KILL",
+            Rehash => "REHASH",
+            Restart => "RESTART",
+            Squit { .. } => "SQUIT",
+            Who { .. } => "WHO",
+            Whois { .. } => "WHOIS",
+            Whowas { .. } => "WHOWAS",
+            Userhost { .. } => "USERHOST",
+            Ison { .. } => "ISON",
+            Away { .. } => "AWAY",
+            Wallops { .. } => "WALLOPS",
+            Account { .. } => "ACCOUNT",
+            Chghost { .. } => "CHGHOST",
+            Setname { .. } => "SETNAME",
+        }
+    }
+
+    /// List of wire parameters
+    pub fn parameters(&self) -> Vec<AttrValue> {
+        use TypedMessage::*;
+
+        fn opt(param: &Option<AttrValue>) -> Vec<AttrValue> {
+            param.iter().cloned().collect()
+        }
+
+        match self {
+            Pass { password } => vec![password.clone()],
+            Nick { nickname } => vec![nickname.clone()],
+            User { username, realname } => {
+                vec![username.clone(), "0".into(), "*".into(), realname.clone()]
+            }
+            Oper { name, password } => vec![name.clone(), password.clone()],
+            Quit { reason } => opt(reason),
+            Error { reason } => vec![reason.clone()],
+            Ping { token } => vec![token.clone()],
+            Pong { server, token } => [opt(server), vec![token.clone()]].concat(),
+            Authenticate { data } => vec![data.clone()],
+            Join {
+                channels,
+                keys,
+                account,
+                realname,
+            } => match (account, realname) {
+                (None, None) if keys.is_empty() => vec![join_list(channels)],
+                (None, None) => vec![join_list(channels), join_list(keys)],
+                (account, realname) => vec![
+                    join_list(channels),
+                    account.clone().unwrap_or("*".into()),
+                    realname.clone().unwrap_or_default(),
+                ],
+            },
+            PartAll => vec!["0".into()],
+            Part { channels, reason } => [vec![join_list(channels)], opt(reason)].concat(),
+            Topic { channel, topic } => [vec![channel.clone()], opt(topic)].concat(),
+            Names { channels } if channels.is_empty() => vec![],
+            Names { channels } => vec![join_list(channels)],
+            // an empty channel list is left out, not sent as empty parameter
+            List { channels, elist } if channels.is_empty() => opt(elist),
+            List { channels, elist } => [vec![join_list(channels)], opt(elist)].concat(),
+            Invite { nickname, channel } => vec![nickname.clone(), channel.clone()],
+            Kick {
+                channel,
+                users,
+                comment,
+            } => [vec![channel.clone(), join_list(users)], opt(comment)].concat(),
+            Mode {
+                target,
+                modestring,
+                arguments,
+            } => [vec![target.clone()], opt(modestring), arguments.clone()].concat(),
+            Privmsg { target, text } | Notice { target, text } => {
+                vec![target.clone(), text.clone()]
+            }
+            Tagmsg { target } => vec![target.clone()],
+            Motd { server } | Version { server } | Admin { server } | Time { server } => {
+                opt(server)
+            }
+            Stats { query, server } => [vec![query.clone()], opt(server)].concat(),
+            Help { subject } => opt(subject),
+            Connect {
+                target,
+                port,
+                remote,
+            } => [vec![target.clone()], opt(port), opt(remote)].concat(),
+            Kill { nickname, comment } => vec![nickname.clone(), comment.clone()],
+            Squit { server, comment } => vec![server.clone(), comment.clone()],
+            Who { mask, fields } => [vec![mask.clone()], opt(fields)].concat(),
+            Whois { server, nickname } => [opt(server), vec![nickname.clone()]].concat(),
+            Whowas { nickname, count } => [vec![nickname.clone()], opt(count)].concat(),
+            Userhost { nicknames } | Ison { nicknames } => nicknames.clone(),
+            Away { text } => opt(text),
+            Wallops { text } => vec![text.clone()],
+            Account { account } => vec![account.clone().unwrap_or("*".into())],
+            Chghost { username, host } => vec![username.clone(), host.clone()],
+            Setname { realname } => vec![realname.clone()],
+            Lusers | Info | Links | Rehash | Restart => vec![],
+        }
+    }
+}
+
+impl From<&TypedMessage> for GenericMessage {
+    fn from(msg: &TypedMessage) -> Self {
+        GenericMessage {
+            command: Command::Cmd(msg.command().into()),
+            parameters: msg.parameters(),
+        }
+    }
+}
+
+impl From<TypedMessage> for GenericMessage {
+    fn from(msg: TypedMessage) -> Self {
+        GenericMessage::from(&msg)
+    }
+}
+
+impl Display for TypedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", GenericMessage::from(self))
+    }
+}
@@ -0,0 +1,257 @@
+use crate::irc::parser::{message::Message, Command};
+
+use super::{TypedMessage, TypedMessageError};
+
+fn parse(input: &str) -> TypedMessage {
+    match Message::parse(input) {
+        Err(err) => panic!("{err}"),
+        Ok(msg) => msg.to_typed().unwrap(),
+    }
+}
+
+/// Parse input, compare with expected message and make sure it serializes to
+/// the same input again
+fn roundtrip(input: &str, expected: TypedMessage) {
+    let msg = parse(input);
+    assert_eq!(msg, expected);
+    assert_eq!(msg.to_string(), input);
+}
+
+#[test]
+fn test_privmsg() {
+    roundtrip(
+        "PRIVMSG #rust :hello world",
+        TypedMessage::Privmsg {
+            target: "#rust".into(),
+            text: "hello world".into(),
+        },
+    );
+}
+
+#[test]
+fn test_privmsg_trailing_colon() {
+    roundtrip(
+        "PRIVMSG #rust ::)",
+        TypedMessage::Privmsg {
+            target: "#rust".into(),
+            text: ":)".into(),
+        },
+    );
+}
+
+#[test]
+fn test_lower_case_command() {
+    assert_eq!(
+        parse("notice nick text"),
+        TypedMessage::Notice {
+            target: "nick".into(),
+            text: "text".into()
+        }
+    );
+}
+
+#[test]
+fn test_missing_parameter() {
+    let msg = Message::parse("PRIVMSG #rust").unwrap();
+    assert_eq!(
+        msg.to_typed(),
+        Err(TypedMessageError::MissingParameter {
+            command: "PRIVMSG".into(),
+            parameter: "text"
+        })
+    );
+}
+
+#[test]
+fn test_unknown_command() {
+    let msg = Message::parse("FOOBAR a b").unwrap();
+    assert_eq!(
+        msg.to_typed(),
+        Err(TypedMessageError::UnknownCommand(Command::Cmd(
+            "FOOBAR".into()
+        )))
+    );
+
+    let msg = Message::parse("001 nick :Welcome").unwrap();
+    assert_eq!(
+        msg.to_typed(),
+        Err(TypedMessageError::UnknownCommand(Command::Digit3(1)))
+    );
+}
+
+#[test]
+fn test_join() {
+    roundtrip(
+        "JOIN #a,#b key",
+        TypedMessage::Join {
+            channels: vec!["#a".into(), "#b".into()],
+            keys: vec!["key".into()],
+            account: None,
+            realname: None,
+        },
+    );
+    roundtrip("JOIN 0", TypedMessage::PartAll);
+}
+
+#[test]
+fn test_extended_join() {
+    roundtrip(
+        "JOIN #a account :Real Name",
+        TypedMessage::Join {
+            channels: vec!["#a".into()],
+            keys: vec![],
+            account: Some("account".into()),
+            realname: Some("Real Name".into()),
+        },
+    );
+    assert_eq!(
+        parse("JOIN #a * :Real Name"),
+        TypedMessage::Join {
+            channels: vec!["#a".into()],
+            keys: vec![],
+            account: None,
+            realname: Some("Real Name".into()),
+        },
+    );
+}
+
+#[test]
+fn test_part_kick() {
+    roundtrip(
+        "PART #a,#b :see you",
+        TypedMessage::Part {
+            channels: vec!["#a".into(), "#b".into()],
+            reason: Some("see you".into()),
+        },
+    );
+    roundtrip(
+        "KICK #a dan,bob :bye bye",
+        TypedMessage::Kick {
+            channel: "#a".into(),
+            users: vec!["dan".into(), "bob".into()],
+            comment: Some("bye bye".into()),
+        },
+    );
+}
+
+#[test]
+fn test_topic() {
+    roundtrip(
+        "TOPIC #a",
+        TypedMessage::Topic {
+            channel: "#a".into(),
+            topic: None,
+        },
+    );
+    // clearing the topic must keep the empty trailing parameter
+    roundtrip(
+        "TOPIC #a :",
+        TypedMessage::Topic {
+            channel: "#a".into(),
+            topic: Some("".into()),
+        },
+    );
+}
+
+#[test]
+fn test_list() {
+    roundtrip(
+        "LIST #a,#b",
+        TypedMessage::List {
+            channels: vec!["#a".into(), "#b".into()],
+            elist: None,
+        },
+    );
+    let list = TypedMessage::List {
+        channels: vec![],
+        elist: Some(">3".into()),
+    };
+    assert_eq!(list.to_string(), "LIST >3");
+}
+
+#[test]
+fn test_mode() {
+    roundtrip(
+        "MODE #a +ov-k dan bob key",
+        TypedMessage::Mode {
+            target: "#a".into(),
+            modestring: Some("+ov-k".into()),
+            arguments: vec!["dan".into(), "bob".into(), "key".into()],
+        },
+    );
+}
+
+#[test]
+fn test_user() {
+    roundtrip(
+        "USER guest 0 * :Real Name",
+        TypedMessage::User {
+            username: "guest".into(),
+            realname: "Real Name".into(),
+        },
+    );
+}
+
+#[test]
+fn test_ping_pong() {
+    roundtrip(
+        "PING token",
+        TypedMessage::Ping {
+            token: "token".into(),
+        },
+    );
+    roundtrip(
+        "PONG irc.example.com token",
+        TypedMessage::Pong {
+            server: Some("irc.example.com".into()),
+            token: "token".into(),
+        },
+    );
+    roundtrip(
+        "PONG token",
+        TypedMessage::Pong {
+            server: None,
+            token: "token".into(),
+        },
+    );
+}
+
+#[test]
+fn test_quit_error_away() {
+    roundtrip(
+        "QUIT :Gone to lunch",
+        TypedMessage::Quit {
+            reason: Some("Gone to lunch".into()),
+        },
+    );
+    roundtrip("QUIT", TypedMessage::Quit { reason: None });
+    roundtrip(
+        "ERROR :Closing Link",
+        TypedMessage::Error {
+            reason: "Closing Link".into(),
+        },
+    );
+    roundtrip("AWAY", TypedMessage::Away { text: None });
+}
+
+#[test]
+fn test_account() {
+    roundtrip(
+        "ACCOUNT dan",
+        TypedMessage::Account {
+            account: Some("dan".into()),
+        },
+    );
+    roundtrip("ACCOUNT *", TypedMessage::Account { account: None });
+}
+
+#[test]
+fn test_message_builder() {
+    let msg = Message::typed(TypedMessage::Whois {
+        server: None,
+        nickname: "dan".into(),
+    })
+    .host("irc.example.com")
+    .build();
+    assert_eq!(msg.to_string(), ":irc.example.com WHOIS dan");
+}
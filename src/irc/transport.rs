@@ -0,0 +1,70 @@
+use yew::AttrValue;
+
+use super::parser::message::Message;
+
+pub mod memory;
+pub mod websocket;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Connection is not open (yet)
+    NotOpen,
+    /// Line contains a line break or NUL, which would split the message
+    InvalidLine(AttrValue),
+    /// Error reported by the underlying connection
+    Connection(AttrValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Connecting,
+    Open,
+    Closing,
+    Closed,
+}
+
+/// Events emitted by a transport to its `Callback<Event>`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Connection has been established
+    Open,
+    /// IRC line received from the server
+    Line(AttrValue),
+    /// Connection reported an error, usually followed by `Closed`
+    Error(AttrValue),
+    /// Connection has been closed. `code` is the WebSocket close code, 1000
+    /// for a normal closure.
+    Closed { code: u16, reason: AttrValue },
+}
+
+/// Connection to an IRC server, which sends and receives single IRC lines,
+/// without line endings
+pub trait Transport {
+    /// Send a single IRC line, without line ending
+    fn send(&self, line: &str) -> Result<(), Error>;
+
+    fn close(&self);
+
+    fn state(&self) -> State;
+
+    fn send_message(&self, message: &Message) -> Result<(), Error> {
+        self.send(&message.to_string())
+    }
+}
+
+/// Make sure `line` is a single IRC message
+fn validate_line(line: &str) -> Result<(), Error> {
+    if line.contains(['\r', '\n', '\0']) {
+        return Err(Error::InvalidLine(line.to_owned().into()));
+    }
+    Ok(())
+}
+
+/// Strip line ending of a received line. Servers should not send them, but
+/// some do.
+fn strip_line_ending(line: &str) -> &str {
+    line.trim_end_matches(['\r', '\n'])
+}
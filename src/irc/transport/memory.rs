@@ -0,0 +1,85 @@
+use std::{cell::RefCell, rc::Rc};
+
+use yew::{AttrValue, Callback};
+
+use super::{strip_line_ending, validate_line, Error, Event, State, Transport};
+
+#[derive(Debug)]
+struct Inner {
+    state: State,
+    sent: Vec<AttrValue>,
+}
+
+/// In-memory transport for tests. Lines sent by the client are recorded and
+/// server lines are injected with `receive`.
+#[derive(Debug, Clone)]
+pub struct MemoryTransport {
+    inner: Rc<RefCell<Inner>>,
+    on_event: Callback<Event>,
+}
+
+impl MemoryTransport {
+    /// New transport in `Connecting` state, call `open` to establish it
+    pub fn new(on_event: Callback<Event>) -> Self {
+        MemoryTransport {
+            inner: Rc::new(RefCell::new(Inner {
+                state: State::Connecting,
+                sent: vec![],
+            })),
+            on_event,
+        }
+    }
+
+    pub fn open(&self) {
+        self.inner.borrow_mut().state = State::Open;
+        self.on_event.emit(Event::Open);
+    }
+
+    /// Simulate a line sent by the server
+    pub fn receive(&self, line: &str) {
+        self.on_event
+            .emit(Event::Line(strip_line_ending(line).to_owned().into()));
+    }
+
+    /// Simulate an error of the connection, which closes it
+    pub fn fail(&self, error: &str) {
+        self.on_event.emit(Event::Error(error.to_owned().into()));
+        self.closed(1006, "");
+    }
+
+    /// Take all lines sent so far
+    pub fn take_sent(&self) -> Vec<AttrValue> {
+        std::mem::take(&mut self.inner.borrow_mut().sent)
+    }
+
+    fn closed(&self, code: u16, reason: &str) {
+        self.inner.borrow_mut().state = State::Closed;
+        self.on_event.emit(Event::Closed {
+            code,
+            reason: reason.to_owned().into(),
+        });
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&self, line: &str) -> Result<(), Error> {
+        validate_line(line)?;
+
+        let mut inner = self.inner.borrow_mut();
+        if inner.state != State::Open {
+            return Err(Error::NotOpen);
+        }
+        inner.sent.push(line.to_owned().into());
+        Ok(())
+    }
+
+    fn close(&self) {
+        if self.state() != State::Closed {
+            self.closed(1000, "");
+        }
+    }
+
+    fn state(&self) -> State {
+        self.inner.borrow().state
+    }
+}
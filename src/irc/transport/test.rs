@@ -0,0 +1,116 @@
+use std::{cell::RefCell, rc::Rc};
+
+use yew::{AttrValue, Callback};
+
+use crate::irc::parser::message::Message;
+
+use super::{memory::MemoryTransport, Error, Event, State, Transport};
+
+fn transport() -> (MemoryTransport, Rc<RefCell<Vec<Event>>>) {
+    let events = Rc::new(RefCell::new(vec![]));
+    let on_event = Callback::from({
+        let events = events.clone();
+        move |event| events.borrow_mut().push(event)
+    });
+    (MemoryTransport::new(on_event), events)
+}
+
+#[test]
+fn test_open() {
+    let (transport, events) = transport();
+    assert_eq!(transport.state(), State::Connecting);
+    assert_eq!(transport.send("NICK dan"), Err(Error::NotOpen));
+
+    transport.open();
+    assert_eq!(transport.state(), State::Open);
+    assert_eq!(*events.borrow(), vec![Event::Open]);
+}
+
+#[test]
+fn test_send() {
+    let (transport, _) = transport();
+    transport.open();
+
+    transport.send("NICK dan").unwrap();
+    transport
+        .send_message(&Message::parse("USER dan 0 * :Dan").unwrap())
+        .unwrap();
+    assert_eq!(
+        transport.take_sent(),
+        vec![
+            AttrValue::from("NICK dan"),
+            AttrValue::from("USER dan 0 * Dan")
+        ]
+    );
+    assert!(transport.take_sent().is_empty());
+}
+
+#[test]
+fn test_send_single_line() {
+    let (transport, _) = transport();
+    transport.open();
+
+    for line in ["NICK dan\r\n", "NICK dan\nQUIT", "NICK\0dan"] {
+        assert_eq!(
+            transport.send(line),
+            Err(Error::InvalidLine(line.to_owned().into()))
+        );
+    }
+    assert!(transport.take_sent().is_empty());
+}
+
+#[test]
+fn test_receive() {
+    let (transport, events) = transport();
+    transport.open();
+    transport.receive(":server 001 dan :Welcome");
+    transport.receive("PING :token\r\n");
+
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            Event::Open,
+            Event::Line(":server 001 dan :Welcome".into()),
+            Event::Line("PING :token".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_close() {
+    let (transport, events) = transport();
+    transport.open();
+    transport.close();
+    transport.close();
+
+    assert_eq!(transport.state(), State::Closed);
+    assert_eq!(transport.send("QUIT"), Err(Error::NotOpen));
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            Event::Open,
+            Event::Closed {
+                code: 1000,
+                reason: "".into()
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_fail() {
+    let (transport, events) = transport();
+    transport.fail("connection reset");
+
+    assert_eq!(transport.state(), State::Closed);
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            Event::Error("connection reset".into()),
+            Event::Closed {
+                code: 1006,
+                reason: "".into()
+            }
+        ]
+    );
+}
@@ -0,0 +1,140 @@
+use js_sys::{Array, ArrayBuffer, Uint8Array};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{BinaryType, CloseEvent, MessageEvent, WebSocket};
+use yew::Callback;
+
+use super::{strip_line_ending, validate_line, Error, Event, State, Transport};
+
+/// Subprotocol for UTF-8 text messages
+pub const TEXT_PROTOCOL: &str = "text.ircv3.net";
+/// Subprotocol for binary messages, which may contain invalid UTF-8
+pub const BINARY_PROTOCOL: &str = "binary.ircv3.net";
+
+/// Transport using `web_sys::WebSocket`, following the spec:
+///
+/// https://ircv3.net/specs/extensions/websocket
+///
+/// Every WebSocket message carries a single IRC line.
+pub struct WebSocketTransport {
+    socket: WebSocket,
+    // handlers have to be kept alive as long as the socket is in use
+    _on_open: Closure<dyn FnMut(web_sys::Event)>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(web_sys::Event)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+fn js_error(value: JsValue) -> Error {
+    Error::Connection(format!("{value:?}").into())
+}
+
+impl WebSocketTransport {
+    /// Open a connection to `url`, e.g. `wss://irc.example.com`. Both IRCv3
+    /// subprotocols are offered, the binary one is preferred.
+    pub fn connect(url: &str, on_event: Callback<Event>) -> Result<Self, Error> {
+        let protocols = Array::of2(&BINARY_PROTOCOL.into(), &TEXT_PROTOCOL.into());
+        let socket = WebSocket::new_with_str_sequence(url, &protocols).map_err(js_error)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let on_open = Closure::<dyn FnMut(_)>::new({
+            let on_event = on_event.clone();
+            move |_: web_sys::Event| on_event.emit(Event::Open)
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+        let on_message = Closure::<dyn FnMut(_)>::new({
+            let on_event = on_event.clone();
+            move |event: MessageEvent| {
+                if let Some(line) = Self::decode(event.data()) {
+                    on_event.emit(Event::Line(strip_line_ending(&line).to_owned().into()));
+                }
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        let on_error = Closure::<dyn FnMut(_)>::new({
+            let on_event = on_event.clone();
+            move |_: web_sys::Event| on_event.emit(Event::Error("WebSocket error".into()))
+        });
+        socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+        let on_close = Closure::<dyn FnMut(_)>::new(move |event: CloseEvent| {
+            on_event.emit(Event::Closed {
+                code: event.code(),
+                reason: event.reason().into(),
+            })
+        });
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(WebSocketTransport {
+            socket,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_error: on_error,
+            _on_close: on_close,
+        })
+    }
+
+    /// Subprotocol selected by the server, empty if none was selected
+    pub fn protocol(&self) -> String {
+        self.socket.protocol()
+    }
+
+    /// Without a negotiated subprotocol, messages are sent as text
+    fn is_binary(&self) -> bool {
+        self.protocol() == BINARY_PROTOCOL
+    }
+
+    /// Text messages are strings, binary messages array buffers. Invalid
+    /// UTF-8 is replaced.
+    fn decode(data: JsValue) -> Option<String> {
+        if let Some(text) = data.as_string() {
+            return Some(text);
+        }
+        let buffer = data.dyn_into::<ArrayBuffer>().ok()?;
+        let bytes = Uint8Array::new(&buffer).to_vec();
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn send(&self, line: &str) -> Result<(), Error> {
+        validate_line(line)?;
+
+        if self.state() != State::Open {
+            return Err(Error::NotOpen);
+        }
+
+        if self.is_binary() {
+            self.socket.send_with_u8_array(line.as_bytes())
+        } else {
+            self.socket.send_with_str(line)
+        }
+        .map_err(js_error)
+    }
+
+    fn close(&self) {
+        // closing an already closed socket is not an error
+        let _ = self.socket.close();
+    }
+
+    fn state(&self) -> State {
+        match self.socket.ready_state() {
+            WebSocket::CONNECTING => State::Connecting,
+            WebSocket::OPEN => State::Open,
+            WebSocket::CLOSING => State::Closing,
+            _ => State::Closed,
+        }
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        // handlers are freed with the transport
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onerror(None);
+        self.socket.set_onclose(None);
+        self.close();
+    }
+}
@@ -1,20 +1,29 @@
 mod buffer_view;
 mod channel_list;
+mod connection_status;
+mod formatted_text;
+mod nick_color;
 mod user_list;
 
+use std::{rc::Rc, time::Duration};
+
+use gloo::timers::callback::Interval;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
 use crate::irc::{
     client::{
-        buffer::{Buffer, Line},
-        Client,
+        buffer::{Buffer, BufferName},
+        server_info::ServerInfo,
+        Client, Config,
     },
-    parser::{Source, User},
+    connection::{Connect, Connection, Status},
+    transport::{websocket::WebSocketTransport, Transport},
 };
 
 use buffer_view::BufferView;
 use channel_list::Channels;
+use connection_status::ConnectionStatus;
 use user_list::Users;
 
 use super::{Route, Settings};
@@ -27,53 +36,162 @@ pub struct HomeProps {
     pub settings: Option<Settings>,
 }
 
-fn example_buffers() -> Vec<Buffer> {
-    vec![
-        Buffer {
-            id: 0,
-            name: "#helloworld".into(),
-            motd: Some("Hello world! This is a strange place to be.".into()),
-            lines: vec![
-                Line {
-                    id: 0,
-                    source: Source::Host("localhost".into()),
-                    message: "moep".into(),
-                },
-                Line {
-                    id: 1,
-                    source: Source::User(User {
-                        nick: "McManiaC".into(),
-                        user: None,
-                        host: None,
-                    }),
-                    message: "Hello world!".into(),
-                },
-            ],
-        },
-        Buffer {
-            id: 1,
-            name: "#test".into(),
-            motd: None,
-            lines: vec![],
-        },
-    ]
+/// Client for the configured nick name, which starts with the buffer of the
+/// server only
+fn new_client(settings: &Settings) -> Client {
+    let mut client = Client::new(Config::new(&settings.nickname));
+    let name = BufferName::Server(settings.host.clone().into());
+    client.buffers.push(Buffer::new(0, name));
+    client
+}
+
+/// Current time for the timers of the connection
+fn now() -> Duration {
+    Duration::from_millis(js_sys::Date::now() as u64)
+}
+
+/// Offset of the local time zone to UTC in minutes
+fn utc_offset() -> i32 {
+    -(js_sys::Date::new_0().get_timezone_offset() as i32)
 }
 
 #[function_component]
 pub fn HomePage(props: &HomeProps) -> Html {
     let nav = use_navigator().unwrap();
 
-    let client = use_state_eq(|| {
-        let mut c = Client::new();
-        c.buffers.append(&mut example_buffers());
-        c
+    // the connection is updated from transport events and timers, which
+    // trigger a re-render afterwards
+    let connection = use_mut_ref(|| None::<Connection>);
+    let update = use_force_update();
+
+    use_effect_with(props.settings.clone(), {
+        let connection = connection.clone();
+        let update = update.clone();
+        move |settings: &Option<Settings>| {
+            let interval = settings.as_ref().map(|settings| {
+                let on_event = Callback::from({
+                    let connection = connection.clone();
+                    let update = update.clone();
+                    move |event| {
+                        if let Some(connection) = connection.borrow_mut().as_mut() {
+                            connection.event(event, now());
+                        }
+                        update.force_update();
+                    }
+                });
+
+                let host = settings.host.clone();
+                let connect: Connect = Rc::new(move || {
+                    WebSocketTransport::connect(&host, on_event.clone())
+                        .map(|transport| Box::new(transport) as Box<dyn Transport>)
+                });
+
+                let mut new_connection = Connection::new(new_client(settings), connect);
+                new_connection.connect(now());
+                *connection.borrow_mut() = Some(new_connection);
+
+                Interval::new(1000, {
+                    let connection = connection.clone();
+                    move || {
+                        if let Some(connection) = connection.borrow_mut().as_mut() {
+                            connection.tick(now());
+                        }
+                        update.force_update();
+                    }
+                })
+            });
+
+            move || {
+                drop(interval);
+                if let Some(mut connection) = connection.borrow_mut().take() {
+                    connection.disconnect(None);
+                }
+            }
+        }
+    });
+
+    let shared = connection.clone();
+    let connection = connection.borrow();
+    let (buffers, selected, server_info, status, lag, error) = match connection.as_ref() {
+        Some(connection) => (
+            connection.client.buffers.clone(),
+            connection.client.selected().cloned(),
+            connection.client.server_info().clone(),
+            connection.status().clone(),
+            connection.client.lag(),
+            connection.last_error().cloned(),
+        ),
+        None => (
+            vec![],
+            None,
+            ServerInfo::default(),
+            Status::Disconnected,
+            None,
+            None,
+        ),
+    };
+
+    let channels = channel_list::entries(&buffers, selected.as_ref().map(|buffer| buffer.id));
+
+    let users = selected
+        .as_ref()
+        .and_then(|buffer| buffer.channel.as_ref())
+        .map(|channel| user_list::entries(channel, &server_info))
+        .unwrap_or_default();
+
+    let on_select = Callback::from({
+        let connection = shared.clone();
+        let update = update.clone();
+        move |buffer_id: u64| {
+            if let Some(connection) = connection.borrow_mut().as_mut() {
+                connection.client.select(buffer_id);
+            }
+            update.force_update();
+        }
+    });
+
+    let on_load_history = Callback::from({
+        let connection = shared.clone();
+        let update = update.clone();
+        let buffer_id = selected.as_ref().map(|buffer| buffer.id);
+        move |_| {
+            if let (Some(connection), Some(buffer_id)) =
+                (connection.borrow_mut().as_mut(), buffer_id)
+            {
+                connection.load_history(buffer_id);
+            }
+            update.force_update();
+        }
+    });
+
+    let on_input = Callback::from({
+        let connection = shared;
+        let buffer_id = selected.as_ref().map(|buffer| buffer.id);
+        move |input: AttrValue| {
+            if let (Some(connection), Some(buffer_id)) =
+                (connection.borrow_mut().as_mut(), buffer_id)
+            {
+                connection.command(buffer_id, &input, now());
+            }
+            update.force_update();
+        }
     });
 
-    let channel_names = client
-        .buffers
-        .iter()
-        .map(|b| b.name.clone())
-        .collect::<Vec<AttrValue>>();
+    let buffer_view = match (selected, &props.settings) {
+        (Some(buffer), Some(settings)) => {
+            html! {
+                <BufferView
+                    {buffer}
+                    {on_input}
+                    {on_load_history}
+                    casemapping={server_info.casemapping}
+                    timestamp_format={settings.timestamp_format.clone()}
+                    utc_offset={utc_offset()}
+                />
+            }
+        }
+        _ => html! { <div class="grow"></div> },
+    };
 
     if props.settings.is_none() {
         nav.push(&Route::Settings);
@@ -85,9 +203,12 @@ pub fn HomePage(props: &HomeProps) -> Html {
             <main
                 class="flex flex-row w-full h-full"
                 >
-                <Channels names={channel_names}/>
-                <BufferView buffer={client.buffers[0].clone()} />
-                <Users />
+                <div class="flex flex-col">
+                    <ConnectionStatus {status} {lag} {error} />
+                    <Channels {channels} {on_select} />
+                </div>
+                { buffer_view }
+                <Users {users} />
             </main>
         }
     }
@@ -1,16 +1,55 @@
 #[cfg(test)]
 mod test;
 
+use web_sys::{Element, HtmlInputElement};
 use yew::prelude::*;
 
 use crate::irc::{
-    client::buffer::{Buffer, Line},
+    client::{
+        buffer::{Buffer, Line, LineKind},
+        casemapping::{CaseMapping, Nickname},
+        timestamp,
+    },
+    formatting,
     parser::Source,
 };
 
+use super::{
+    formatted_text::FormattedText,
+    nick_color::{default_palette, nick_color},
+};
+
 #[derive(Debug, PartialEq, Properties)]
 pub struct BufferViewProps {
     pub buffer: Buffer,
+    /// Text entered into the input, e.g. a message or `/join #rust`
+    #[prop_or_default]
+    pub on_input: Callback<AttrValue>,
+    /// The lines were scrolled to the top, so older ones should be loaded
+    #[prop_or_default]
+    pub on_load_history: Callback<()>,
+    /// Classes to colour nick names with
+    #[prop_or_else(default_palette)]
+    pub nick_palette: Vec<AttrValue>,
+    /// Case mapping of the server, nick names equal with it get one colour
+    #[prop_or_default]
+    pub casemapping: CaseMapping,
+    /// Format of the time of a line, see `timestamp::format`
+    #[prop_or(AttrValue::Static("%H:%M"))]
+    pub timestamp_format: AttrValue,
+    /// Local offset to UTC in minutes
+    #[prop_or_default]
+    pub utc_offset: i32,
+}
+
+/// Format of the separators between lines of different days
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Check if `line` was sent on another day than the line before
+fn day_change(previous: Option<&Line>, line: &Line, utc_offset: i32) -> bool {
+    previous.is_some_and(|previous| {
+        timestamp::day(previous.time, utc_offset) != timestamp::day(line.time, utc_offset)
+    })
 }
 
 #[function_component]
@@ -19,26 +58,101 @@ pub fn BufferView(props: &BufferViewProps) -> Html {
         .buffer
         .lines
         .iter()
-        .map(|line| html! { <LineItem line={line.clone()} /> })
+        .enumerate()
+        .map(|(idx, line)| {
+            let color = match &line.source {
+                Some(Source::User(user)) => {
+                    let nick = Nickname::new(&user.nick, props.casemapping);
+                    nick_color(&nick, &props.nick_palette).cloned()
+                }
+                _ => None,
+            };
+            let time: AttrValue =
+                timestamp::format(line.time, props.utc_offset, &props.timestamp_format).into();
+            let previous = idx.checked_sub(1).map(|idx| &props.buffer.lines[idx]);
+            let separator = match day_change(previous, line, props.utc_offset) {
+                true => html! {
+                    <p class="text-center text-slate-500 border-b border-slate-700 my-2">
+                        { timestamp::format(line.time, props.utc_offset, DAY_FORMAT) }
+                    </p>
+                },
+                false => html! {},
+            };
+            html! {
+                <>
+                    { separator }
+                    <LineItem line={line.clone()} {color} {time} />
+                </>
+            }
+        })
         .collect::<Html>();
 
-    let onclick = Callback::from(move |_| {});
+    let view = use_node_ref();
+    let onscroll = Callback::from({
+        let view = view.clone();
+        let on_load_history = props.on_load_history.clone();
+        move |_: Event| {
+            if view
+                .cast::<Element>()
+                .is_some_and(|view| view.scroll_top() <= 0)
+            {
+                on_load_history.emit(());
+            }
+        }
+    });
+
+    let input = use_node_ref();
+    let submit = {
+        let input = input.clone();
+        let on_input = props.on_input.clone();
+        move || {
+            let Some(input) = input.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let value = input.value();
+            if !value.is_empty() {
+                on_input.emit(value.into());
+                input.set_value("");
+            }
+        }
+    };
+    let onclick = Callback::from({
+        let submit = submit.clone();
+        move |_| submit()
+    });
+    let onkeydown = Callback::from({
+        let input = input.clone();
+        move |event: KeyboardEvent| {
+            if event.key() == "Enter" {
+                submit();
+            } else if let Some(code) = event
+                .ctrl_key()
+                .then(|| formatting::shortcut(&event.key()))
+                .flatten()
+            {
+                event.prevent_default();
+                if let Some(input) = input.cast::<HtmlInputElement>() {
+                    insert(&input, code);
+                }
+            }
+        }
+    });
 
     html! {
         <div
             class="grow flex flex-col"
             >
             // buffer title
-            <Title name={props.buffer.name.clone()} motd={props.buffer.motd.clone()} />
+            <Title name={props.buffer.name.as_attr_value()} motd={props.buffer.motd.clone()} />
             // buffer line view
-            <div
-                class="grow"
+            <div ref={view} {onscroll}
+                class="grow overflow-y-auto"
                 >
                 { lines }
             </div>
             // buffer input
             <div class="flex flex-row">
-                <input type="text"
+                <input type="text" ref={input} {onkeydown}
                     class="grow bg-slate-900 p-2 rounded-md outline-none focus:ring-slate-600 focus:ring-1"
                     />
                 <button {onclick} value="Send"
@@ -55,6 +169,31 @@ pub fn BufferView(props: &BufferViewProps) -> Html {
     }
 }
 
+/// Replace the selection of the input with a formatting code
+fn insert(input: &HtmlInputElement, code: char) {
+    let value = input.value();
+    let length = value.encode_utf16().count() as u32;
+    let start = input.selection_start().ok().flatten().unwrap_or(length);
+    let end = input.selection_end().ok().flatten().unwrap_or(start);
+
+    let (value, cursor) = insert_at(&value, start as usize, end as usize, code);
+    input.set_value(&value);
+    let _ = input.set_selection_range(cursor as u32, cursor as u32);
+}
+
+/// Replace `start..end` of `value` with `code`, positions are in UTF-16 code
+/// units as in the DOM. Returns the new value and cursor position.
+fn insert_at(value: &str, start: usize, end: usize, code: char) -> (String, usize) {
+    let units = value.encode_utf16().collect::<Vec<_>>();
+    let end = end.min(units.len());
+    let start = start.min(end);
+
+    let mut result = String::from_utf16_lossy(&units[..start]);
+    result.push(code);
+    result.push_str(&String::from_utf16_lossy(&units[end..]));
+    (result, start + code.len_utf16())
+}
+
 #[derive(Debug, PartialEq, Properties)]
 struct TitleProps {
     name: AttrValue,
@@ -69,7 +208,7 @@ fn Title(props: &TitleProps) -> Html {
     };
 
     let motd = match &props.motd {
-        Some(motd) => html! { <span class="text-slate-400">{ motd }</span> },
+        Some(motd) => html! { <span class="text-slate-400"><FormattedText text={motd} /></span> },
         None => html! {},
     };
 
@@ -84,23 +223,82 @@ fn Title(props: &TitleProps) -> Html {
 #[derive(Debug, PartialEq, Properties)]
 struct LineItemProps {
     line: Line,
+    /// Class of the nick name
+    #[prop_or_default]
+    color: Option<AttrValue>,
+    /// Formatted time of the line
+    time: AttrValue,
+}
+
+/// Name of the sender, the server name for messages of the server
+fn sender(source: &Option<Source>) -> AttrValue {
+    match source {
+        Some(Source::User(user)) => user.nick.clone(),
+        Some(Source::Host(host)) => host.clone(),
+        None => "".into(),
+    }
+}
+
+/// Text of events like joins, e.g. `dan left (bye)`
+fn describe(line: &Line) -> String {
+    let sender = sender(&line.source);
+    let reason = match line.message.is_empty() {
+        true => String::new(),
+        false => format!(" ({})", line.message),
+    };
+    match &line.kind {
+        LineKind::Join => format!("{sender} joined"),
+        LineKind::Part => format!("{sender} left{reason}"),
+        LineKind::Quit => format!("{sender} quit{reason}"),
+        LineKind::Kick { nick } => format!("{sender} kicked {nick}{reason}"),
+        LineKind::Mode => format!("{sender} set mode {}", line.message),
+        LineKind::Topic => format!("{sender} changed the topic to: {}", line.message),
+        LineKind::Nick { nickname } => format!("{sender} is now known as {nickname}"),
+        LineKind::Netsplit { nicks } => {
+            format!("{} quit (netsplit {})", nicks.join(", "), line.message)
+        }
+        LineKind::Netjoin { nicks } => {
+            format!("{} rejoined (netjoin {})", nicks.join(", "), line.message)
+        }
+        LineKind::Ctcp { command } => format!("{sender} sent CTCP {command}{reason}"),
+        LineKind::CtcpReply { command } => {
+            format!("CTCP {command} reply from {sender}: {}", line.message)
+        }
+        LineKind::Message | LineKind::Action | LineKind::Notice | LineKind::Error => {
+            line.message.to_string()
+        }
+    }
 }
 
 #[function_component]
 fn LineItem(props: &LineItemProps) -> Html {
-    let inner = match &props.line.source {
-        Source::Host(name) => html! {
-            <p class="text-slate-500">{ name }{ ": "}{ props.line.message.clone() }</p>
+    let line = &props.line;
+    let sender = sender(&line.source);
+    let nick = html! { <span class={props.color.clone()}>{ sender.clone() }</span> };
+    let inner = match (&line.kind, &line.source) {
+        (LineKind::Message, Some(Source::User(_))) => html! {
+            <p>{ "<" }{ nick }{ "> " }<FormattedText text={line.message.clone()} /></p>
+        },
+        (LineKind::Message, _) => html! {
+            <p class="text-slate-500">{ sender }{ ": " }<FormattedText text={line.message.clone()} /></p>
+        },
+        (LineKind::Action, _) => html! {
+            <p class="italic">{ "* " }{ nick }{ " " }<FormattedText text={line.message.clone()} /></p>
+        },
+        (LineKind::Notice, _) => html! {
+            <p class="text-slate-400">{ "-" }{ sender }{ "- " }<FormattedText text={line.message.clone()} /></p>
+        },
+        (LineKind::Error, _) => html! {
+            <p class="text-red-400">{ line.message.clone() }</p>
+        },
+        _ => html! {
+            <p class="text-slate-500"><FormattedText text={AttrValue::from(describe(line))} /></p>
         },
-        Source::User(user) => {
-            html! {
-                <p>{ "<" }{ user.nick.clone() }{ "> " }{ props.line.message.clone() } </p>
-            }
-        }
     };
 
     html! {
-        <div key={props.line.id}>
+        <div key={line.id} class={classes!("flex", "flex-row", "whitespace-pre-wrap", line.highlight.then_some("bg-amber-900/40"))}>
+            <span class="text-slate-500 font-mono mr-2">{ props.time.clone() }</span>
             { inner }
         </div>
     }
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use crate::irc::client::buffer::{Line, LineKind};
+
+use super::{day_change, insert_at};
+
+#[test]
+fn test_insert_at() {
+    assert_eq!(insert_at("hello", 5, 5, '\x02'), ("hello\x02".into(), 6));
+    // replaces the selection
+    assert_eq!(insert_at("hello", 1, 4, '\x02'), ("h\x02o".into(), 2));
+    // positions count UTF-16 code units
+    assert_eq!(insert_at("🦀a", 2, 2, '\x1d'), ("🦀\x1da".into(), 3));
+    assert_eq!(insert_at("a", 7, 9, '\x1f'), ("a\x1f".into(), 2));
+}
+
+#[test]
+fn test_day_change() {
+    let line = |secs| Line {
+        source: None,
+        id: 0,
+        kind: LineKind::Message,
+        message: "".into(),
+        time: Duration::from_secs(secs),
+        highlight: false,
+        msgid: None,
+    };
+    // 1970-01-01 23:00 and 1970-01-02 01:00 UTC
+    let (late, early) = (line(23 * 3600), line(25 * 3600));
+    assert!(!day_change(None, &late, 0));
+    assert!(day_change(Some(&late), &early, 0));
+    assert!(!day_change(Some(&late), &early, 120));
+    assert!(!day_change(Some(&early), &early, 0));
+}
@@ -1,19 +1,60 @@
 use yew::prelude::*;
 
+use crate::irc::client::buffer::{Activity, Buffer, BufferName};
+
 #[cfg(test)]
 mod test;
 
+/// Entry of the buffer list, e.g. `#rust` with 2 unread highlights
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelEntry {
+    pub id: u64,
+    pub name: AttrValue,
+    /// The server buffer, which heads the buffers of its network
+    pub is_server: bool,
+    pub unread: usize,
+    pub highlights: usize,
+    pub activity: Activity,
+    pub selected: bool,
+}
+
+/// Buffers grouped by type: the server, channels and queries, each sorted by
+/// name
+pub fn entries(buffers: &[Buffer], selected: Option<u64>) -> Vec<ChannelEntry> {
+    let mut buffers = buffers.iter().collect::<Vec<_>>();
+    buffers.sort_by_key(|buffer| match &buffer.name {
+        BufferName::Server(name) => (0, name.to_string()),
+        BufferName::Channel(channel) => (1, channel.folded().to_owned()),
+        BufferName::Query(nick) => (2, nick.folded().to_owned()),
+    });
+    buffers
+        .into_iter()
+        .map(|buffer| ChannelEntry {
+            id: buffer.id,
+            name: buffer.name.as_attr_value(),
+            is_server: matches!(buffer.name, BufferName::Server(_)),
+            unread: buffer.unread,
+            highlights: buffer.highlights,
+            activity: buffer.activity,
+            selected: Some(buffer.id) == selected,
+        })
+        .collect()
+}
+
 #[derive(Debug, PartialEq, Properties)]
 pub struct ChannelsProps {
-    pub names: Vec<AttrValue>,
+    pub channels: Vec<ChannelEntry>,
+    /// Called with the id of the clicked buffer
+    #[prop_or_default]
+    pub on_select: Callback<u64>,
 }
 
 #[function_component]
 pub fn Channels(props: &ChannelsProps) -> Html {
     let names = props
-        .names
+        .channels
         .iter()
-        .map(|s| html!(<ChannelName name={s.clone()} />))
+        .map(|entry| html!(<ChannelName entry={entry.clone()} on_select={props.on_select.clone()} />))
         .collect::<Vec<Html>>();
 
     html!(
@@ -25,12 +66,47 @@ pub fn Channels(props: &ChannelsProps) -> Html {
 
 #[derive(Debug, PartialEq, Properties)]
 struct ChannelNameProps {
-    name: AttrValue,
+    entry: ChannelEntry,
+    on_select: Callback<u64>,
+}
+
+/// Text colour by activity, so buffers with new messages stand out
+fn activity_class(activity: Activity) -> &'static str {
+    match activity {
+        Activity::None => "text-slate-400",
+        Activity::Events => "text-slate-200",
+        Activity::Messages => "text-white font-bold",
+        Activity::Highlight => "text-amber-400 font-bold",
+    }
 }
 
 #[function_component]
 fn ChannelName(props: &ChannelNameProps) -> Html {
+    let entry = &props.entry;
+    let onclick = Callback::from({
+        let on_select = props.on_select.clone();
+        let id = entry.id;
+        move |_| on_select.emit(id)
+    });
+
+    let badge = match (entry.highlights, entry.unread) {
+        (0, 0) => html!(),
+        (0, unread) => html!(
+            <span class="ml-2 px-2 rounded-full bg-slate-600 text-sm">{ unread }</span>
+        ),
+        (highlights, _) => html!(
+            <span class="ml-2 px-2 rounded-full bg-amber-600 text-sm">{ highlights }</span>
+        ),
+    };
+    let class = classes!(
+        "cursor-pointer",
+        "rounded-md",
+        "px-2",
+        activity_class(entry.activity),
+        (!entry.is_server).then_some("ml-2"),
+        entry.selected.then_some("bg-slate-700"),
+    );
     html!(
-        <p>{ props.name.clone() }</p>
+        <p {class} {onclick}>{ entry.name.clone() }{ badge }</p>
     )
 }
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use crate::irc::client::{
+    buffer::{Activity, Buffer, BufferName, LineKind},
+    casemapping::{CaseMapping, ChannelName, Nickname},
+};
+
+use super::entries;
+
+#[test]
+fn test_entries() {
+    let channel = |name| BufferName::Channel(ChannelName::new(name, CaseMapping::default()));
+    let mut rust = Buffer::new(1, channel("#Rust"));
+    rust.push(None, LineKind::Message, "hi".into(), Duration::ZERO);
+    let buffers = vec![
+        Buffer::new(
+            3,
+            BufferName::Query(Nickname::new("alice", CaseMapping::default())),
+        ),
+        rust,
+        Buffer::new(2, channel("#rand")),
+        Buffer::new(0, BufferName::Server("irc.example.com".into())),
+    ];
+
+    let entries = entries(&buffers, Some(2));
+    let names = entries
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["irc.example.com", "#rand", "#Rust", "alice"]);
+
+    assert!(entries[0].is_server);
+    assert!(entries[1].selected);
+    assert_eq!(entries[2].unread, 1);
+    assert_eq!(entries[2].activity, Activity::Messages);
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use yew::prelude::*;
+
+use crate::irc::connection::Status;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct ConnectionStatusProps {
+    pub status: Status,
+    #[prop_or_default]
+    pub lag: Option<Duration>,
+    #[prop_or_default]
+    pub error: Option<AttrValue>,
+}
+
+/// Human readable connection status
+fn describe(status: &Status, lag: Option<Duration>) -> String {
+    match (status, lag) {
+        (Status::Disconnected, _) => "Disconnected".into(),
+        (Status::Connecting, _) => "Connecting…".into(),
+        (Status::Registering, _) => "Registering…".into(),
+        (Status::Connected, Some(lag)) => format!("Connected ({} ms)", lag.as_millis()),
+        (Status::Connected, None) => "Connected".into(),
+        (Status::Reconnecting { attempt, .. }, _) => {
+            format!("Reconnecting (attempt {attempt})…")
+        }
+    }
+}
+
+#[function_component]
+pub fn ConnectionStatus(props: &ConnectionStatusProps) -> Html {
+    let color = match props.status {
+        Status::Connected => "bg-green-500",
+        Status::Connecting | Status::Registering => "bg-yellow-500",
+        Status::Reconnecting { .. } => "bg-orange-500",
+        Status::Disconnected => "bg-red-500",
+    };
+
+    html! {
+        <div
+            class="flex flex-row items-center m-4 text-sm text-slate-400"
+            title={props.error.clone()}
+            >
+            <span class={classes!("inline-block", "size-2", "rounded-full", "mr-2", color)}></span>
+            { describe(&props.status, props.lag) }
+        </div>
+    }
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use crate::irc::connection::Status;
+
+use super::describe;
+
+#[test]
+fn test_describe() {
+    assert_eq!(describe(&Status::Disconnected, None), "Disconnected");
+    assert_eq!(describe(&Status::Connected, None), "Connected");
+    assert_eq!(
+        describe(&Status::Connected, Some(Duration::from_millis(42))),
+        "Connected (42 ms)"
+    );
+    assert_eq!(
+        describe(
+            &Status::Reconnecting {
+                attempt: 3,
+                at: Duration::ZERO
+            },
+            None
+        ),
+        "Reconnecting (attempt 3)…"
+    );
+}
@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod test;
+
+use yew::prelude::*;
+
+use crate::irc::formatting::{self, Color, Style};
+
+/// Tailwind classes of the 99 colours for text and background. The first 16
+/// are approximated with the Tailwind palette, the others are exact.
+const PALETTE: [(&str, &str); 99] = [
+    ("text-white", "bg-white"),
+    ("text-black", "bg-black"),
+    ("text-blue-900", "bg-blue-900"),
+    ("text-green-600", "bg-green-600"),
+    ("text-red-500", "bg-red-500"),
+    ("text-red-900", "bg-red-900"),
+    ("text-purple-700", "bg-purple-700"),
+    ("text-orange-500", "bg-orange-500"),
+    ("text-yellow-300", "bg-yellow-300"),
+    ("text-lime-400", "bg-lime-400"),
+    ("text-teal-600", "bg-teal-600"),
+    ("text-cyan-300", "bg-cyan-300"),
+    ("text-blue-500", "bg-blue-500"),
+    ("text-pink-500", "bg-pink-500"),
+    ("text-gray-500", "bg-gray-500"),
+    ("text-gray-300", "bg-gray-300"),
+    ("text-[#470000]", "bg-[#470000]"),
+    ("text-[#472100]", "bg-[#472100]"),
+    ("text-[#474700]", "bg-[#474700]"),
+    ("text-[#324700]", "bg-[#324700]"),
+    ("text-[#004700]", "bg-[#004700]"),
+    ("text-[#00472c]", "bg-[#00472c]"),
+    ("text-[#004747]", "bg-[#004747]"),
+    ("text-[#002747]", "bg-[#002747]"),
+    ("text-[#000047]", "bg-[#000047]"),
+    ("text-[#2e0047]", "bg-[#2e0047]"),
+    ("text-[#470047]", "bg-[#470047]"),
+    ("text-[#47002a]", "bg-[#47002a]"),
+    ("text-[#740000]", "bg-[#740000]"),
+    ("text-[#743a00]", "bg-[#743a00]"),
+    ("text-[#747400]", "bg-[#747400]"),
+    ("text-[#517400]", "bg-[#517400]"),
+    ("text-[#007400]", "bg-[#007400]"),
+    ("text-[#007449]", "bg-[#007449]"),
+    ("text-[#007474]", "bg-[#007474]"),
+    ("text-[#004074]", "bg-[#004074]"),
+    ("text-[#000074]", "bg-[#000074]"),
+    ("text-[#4b0074]", "bg-[#4b0074]"),
+    ("text-[#740074]", "bg-[#740074]"),
+    ("text-[#740045]", "bg-[#740045]"),
+    ("text-[#b50000]", "bg-[#b50000]"),
+    ("text-[#b56300]", "bg-[#b56300]"),
+    ("text-[#b5b500]", "bg-[#b5b500]"),
+    ("text-[#7db500]", "bg-[#7db500]"),
+    ("text-[#00b500]", "bg-[#00b500]"),
+    ("text-[#00b571]", "bg-[#00b571]"),
+    ("text-[#00b5b5]", "bg-[#00b5b5]"),
+    ("text-[#0063b5]", "bg-[#0063b5]"),
+    ("text-[#0000b5]", "bg-[#0000b5]"),
+    ("text-[#7500b5]", "bg-[#7500b5]"),
+    ("text-[#b500b5]", "bg-[#b500b5]"),
+    ("text-[#b5006b]", "bg-[#b5006b]"),
+    ("text-[#ff0000]", "bg-[#ff0000]"),
+    ("text-[#ff8c00]", "bg-[#ff8c00]"),
+    ("text-[#ffff00]", "bg-[#ffff00]"),
+    ("text-[#b2ff00]", "bg-[#b2ff00]"),
+    ("text-[#00ff00]", "bg-[#00ff00]"),
+    ("text-[#00ffa0]", "bg-[#00ffa0]"),
+    ("text-[#00ffff]", "bg-[#00ffff]"),
+    ("text-[#008cff]", "bg-[#008cff]"),
+    ("text-[#0000ff]", "bg-[#0000ff]"),
+    ("text-[#a500ff]", "bg-[#a500ff]"),
+    ("text-[#ff00ff]", "bg-[#ff00ff]"),
+    ("text-[#ff0098]", "bg-[#ff0098]"),
+    ("text-[#ff5959]", "bg-[#ff5959]"),
+    ("text-[#ffb459]", "bg-[#ffb459]"),
+    ("text-[#ffff71]", "bg-[#ffff71]"),
+    ("text-[#cfff60]", "bg-[#cfff60]"),
+    ("text-[#6fff6f]", "bg-[#6fff6f]"),
+    ("text-[#65ffc9]", "bg-[#65ffc9]"),
+    ("text-[#6dffff]", "bg-[#6dffff]"),
+    ("text-[#59b4ff]", "bg-[#59b4ff]"),
+    ("text-[#5959ff]", "bg-[#5959ff]"),
+    ("text-[#c459ff]", "bg-[#c459ff]"),
+    ("text-[#ff66ff]", "bg-[#ff66ff]"),
+    ("text-[#ff59bc]", "bg-[#ff59bc]"),
+    ("text-[#ff9c9c]", "bg-[#ff9c9c]"),
+    ("text-[#ffd39c]", "bg-[#ffd39c]"),
+    ("text-[#ffff9c]", "bg-[#ffff9c]"),
+    ("text-[#e2ff9c]", "bg-[#e2ff9c]"),
+    ("text-[#9cff9c]", "bg-[#9cff9c]"),
+    ("text-[#9cffdb]", "bg-[#9cffdb]"),
+    ("text-[#9cffff]", "bg-[#9cffff]"),
+    ("text-[#9cd3ff]", "bg-[#9cd3ff]"),
+    ("text-[#9c9cff]", "bg-[#9c9cff]"),
+    ("text-[#dc9cff]", "bg-[#dc9cff]"),
+    ("text-[#ff9cff]", "bg-[#ff9cff]"),
+    ("text-[#ff94d3]", "bg-[#ff94d3]"),
+    ("text-[#000000]", "bg-[#000000]"),
+    ("text-[#131313]", "bg-[#131313]"),
+    ("text-[#282828]", "bg-[#282828]"),
+    ("text-[#363636]", "bg-[#363636]"),
+    ("text-[#4d4d4d]", "bg-[#4d4d4d]"),
+    ("text-[#656565]", "bg-[#656565]"),
+    ("text-[#818181]", "bg-[#818181]"),
+    ("text-[#9f9f9f]", "bg-[#9f9f9f]"),
+    ("text-[#bcbcbc]", "bg-[#bcbcbc]"),
+    ("text-[#e2e2e2]", "bg-[#e2e2e2]"),
+    ("text-[#ffffff]", "bg-[#ffffff]"),
+];
+
+/// Foreground and background colour, swapped if reversed
+fn colors(style: &Style) -> [(Option<Color>, bool); 2] {
+    let (foreground, background) = match style.reverse {
+        true => (style.background, style.foreground),
+        false => (style.foreground, style.background),
+    };
+    [(foreground, false), (background, true)]
+}
+
+/// Classes of a style, e.g. `font-bold italic text-red-500`
+pub fn style_classes(style: &Style) -> Classes {
+    let mut classes = classes!(
+        style.bold.then_some("font-bold"),
+        style.italic.then_some("italic"),
+        style.underline.then_some("underline"),
+        style.strikethrough.then_some("line-through"),
+        style.monospace.then_some("font-mono"),
+    );
+    for (color, is_background) in colors(style) {
+        if let Some(&(text, bg)) = match color {
+            Some(Color::Palette(code)) => PALETTE.get(code as usize),
+            _ => None,
+        } {
+            classes.push(if is_background { bg } else { text });
+        }
+    }
+
+    // reverse without colours swaps the default colours
+    if style.reverse && style.foreground.is_none() && style.background.is_none() {
+        classes.push("text-slate-900 bg-slate-200");
+    }
+    classes
+}
+
+/// Inline style for hex colours, which have no classes
+fn inline_style(style: &Style) -> Option<AttrValue> {
+    let css = colors(style)
+        .into_iter()
+        .filter_map(|(color, is_background)| match color {
+            Some(Color::Rgb(r, g, b)) => {
+                let property = if is_background {
+                    "background-color"
+                } else {
+                    "color"
+                };
+                Some(format!("{property}: #{r:02x}{g:02x}{b:02x}"))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    (!css.is_empty()).then(|| css.join("; ").into())
+}
+
+#[derive(Debug, PartialEq, Properties)]
+pub struct FormattedTextProps {
+    pub text: AttrValue,
+}
+
+/// Text with mIRC formatting codes
+#[function_component]
+pub fn FormattedText(props: &FormattedTextProps) -> Html {
+    formatting::parse(&props.text)
+        .into_iter()
+        .map(|span| {
+            html! {
+                <span class={style_classes(&span.style)} style={inline_style(&span.style)}>
+                    { span.text }
+                </span>
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,46 @@
+use yew::classes;
+
+use crate::irc::formatting::{Color, Style};
+
+use super::{inline_style, style_classes, PALETTE};
+
+#[test]
+fn test_classes() {
+    let style = Style {
+        bold: true,
+        underline: true,
+        foreground: Some(Color::Palette(4)),
+        background: Some(Color::Palette(52)),
+        ..Style::default()
+    };
+    assert_eq!(
+        style_classes(&style),
+        classes!("font-bold", "underline", "text-red-500", "bg-[#ff0000]")
+    );
+
+    // reverse swaps the colours
+    assert_eq!(
+        style_classes(&Style {
+            reverse: true,
+            ..style
+        }),
+        classes!("font-bold", "underline", "text-[#ff0000]", "bg-red-500")
+    );
+}
+
+#[test]
+fn test_palette() {
+    assert_eq!(PALETTE[0], ("text-white", "bg-white"));
+    assert_eq!(PALETTE[98], ("text-[#ffffff]", "bg-[#ffffff]"));
+}
+
+#[test]
+fn test_inline_style() {
+    let style = Style {
+        foreground: Some(Color::Rgb(255, 128, 0)),
+        ..Style::default()
+    };
+    assert_eq!(inline_style(&style), Some("color: #ff8000".into()));
+    assert_eq!(style_classes(&style), classes!());
+    assert_eq!(inline_style(&Style::default()), None);
+}
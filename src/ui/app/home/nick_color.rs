@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test;
+
+use yew::AttrValue;
+
+use crate::irc::client::casemapping::Nickname;
+
+/// Text colours for nick names, readable on the dark background
+pub const DEFAULT_PALETTE: &[&str] = &[
+    "text-red-400",
+    "text-orange-400",
+    "text-amber-400",
+    "text-yellow-300",
+    "text-lime-400",
+    "text-green-400",
+    "text-emerald-400",
+    "text-teal-300",
+    "text-cyan-400",
+    "text-sky-400",
+    "text-blue-400",
+    "text-indigo-300",
+    "text-violet-400",
+    "text-purple-400",
+    "text-fuchsia-400",
+    "text-pink-400",
+];
+
+/// Palette used to colour nick names
+pub fn default_palette() -> Vec<AttrValue> {
+    DEFAULT_PALETTE
+        .iter()
+        .map(|class| AttrValue::Static(class))
+        .collect()
+}
+
+/// Colour of a nick name, which stays the same across sessions. Nick names
+/// which are equal with the case mapping of the server get the same colour.
+pub fn nick_color<'a>(nick: &Nickname, palette: &'a [AttrValue]) -> Option<&'a AttrValue> {
+    if palette.is_empty() {
+        return None;
+    }
+    // FNV-1a, unlike `DefaultHasher` it is stable
+    let hash = nick.folded().bytes().fold(0x811c9dc5_u32, |hash, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x01000193)
+    });
+    palette.get(hash as usize % palette.len())
+}
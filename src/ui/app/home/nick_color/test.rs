@@ -0,0 +1,33 @@
+use yew::AttrValue;
+
+use crate::irc::client::casemapping::{CaseMapping, Nickname};
+
+use super::{default_palette, nick_color};
+
+fn nick(name: &str) -> Nickname {
+    Nickname::new(name, CaseMapping::Rfc1459)
+}
+
+#[test]
+fn test_nick_color() {
+    let palette = default_palette();
+    assert_eq!(
+        nick_color(&nick("dan"), &palette),
+        nick_color(&nick("DAN"), &palette)
+    );
+    assert_eq!(nick_color(&nick("dan"), &palette), Some(&palette[8]));
+    assert_eq!(nick_color(&nick("alice"), &palette), Some(&palette[7]));
+    assert_eq!(nick_color(&nick("dan"), &[]), None);
+
+    let single = [AttrValue::from("text-white")];
+    assert_eq!(nick_color(&nick("alice"), &single), Some(&single[0]));
+}
+
+#[test]
+fn test_nick_color_casemapping() {
+    let palette = default_palette();
+    assert_eq!(
+        nick_color(&nick("dan[m]"), &palette),
+        nick_color(&nick("DAN{m}"), &palette)
+    );
+}
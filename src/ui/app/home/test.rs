@@ -0,0 +1,20 @@
+use crate::{irc::client::buffer::BufferName, ui::app::Settings};
+
+use super::new_client;
+
+#[test]
+fn test_new_client() {
+    let settings = Settings {
+        host: "irc.example.com".into(),
+        nickname: "dan".into(),
+        timestamp_format: "%H:%M".into(),
+    };
+    let client = new_client(&settings);
+    assert_eq!(client.nickname(), "dan");
+    let names = client
+        .buffers
+        .iter()
+        .map(|buffer| &buffer.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![&BufferName::Server("irc.example.com".into())]);
+}
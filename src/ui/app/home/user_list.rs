@@ -3,13 +3,51 @@ mod test;
 
 use yew::prelude::*;
 
+use crate::irc::client::{channel::Channel, server_info::ServerInfo};
+
+/// Entry of the nick list, e.g. `@` and `dan`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserEntry {
+    pub prefix: Option<char>,
+    pub nick: AttrValue,
+}
+
+/// Members of a channel, sorted by their rank
+pub fn entries(channel: &Channel, server_info: &ServerInfo) -> Vec<UserEntry> {
+    channel
+        .sorted_members(server_info)
+        .into_iter()
+        .map(|member| UserEntry {
+            prefix: member.prefix(server_info),
+            nick: member.nick.as_attr_value(),
+        })
+        .collect()
+}
+
 #[derive(Debug, PartialEq, Properties)]
-pub struct UsersProps {}
+pub struct UsersProps {
+    #[prop_or_default]
+    pub users: Vec<UserEntry>,
+}
 
 #[function_component]
-pub fn Users(_props: &UsersProps) -> Html {
+pub fn Users(props: &UsersProps) -> Html {
+    let users = props
+        .users
+        .iter()
+        .map(|user| {
+            html!(
+                <p>
+                    <span class="inline-block w-3 text-slate-400">{ user.prefix }</span>
+                    { user.nick.clone() }
+                </p>
+            )
+        })
+        .collect::<Vec<Html>>();
+
     html!(
-        <>
-        </>
+        <div class="m-4">
+            { users }
+        </div>
     )
 }
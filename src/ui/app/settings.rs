@@ -7,6 +7,19 @@ use yew_router::prelude::*;
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Settings {
     pub host: String,
+    #[serde(default = "default_nickname")]
+    pub nickname: String,
+    /// Format of the time of a line, see `timestamp::format`
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+}
+
+fn default_nickname() -> String {
+    "guest".to_owned()
+}
+
+fn default_timestamp_format() -> String {
+    "%H:%M".to_owned()
 }
 
 #[derive(Debug, PartialEq, Clone, Properties)]
@@ -20,22 +33,46 @@ pub struct SettingsProps {
 #[function_component]
 pub fn SettingsPage(props: &SettingsProps) -> Html {
     let host_ref = use_node_ref();
+    let nickname_ref = use_node_ref();
+    let timestamp_format_ref = use_node_ref();
 
     let onsubmit = Callback::from({
         let host_ref = host_ref.clone();
+        let nickname_ref = nickname_ref.clone();
+        let timestamp_format_ref = timestamp_format_ref.clone();
         let set_settings = props.set_settings.clone();
         let nav = use_navigator().unwrap();
         move |e: SubmitEvent| {
             e.prevent_default();
             let host = host_ref.cast::<HtmlInputElement>().unwrap().value();
-            set_settings.emit(Settings { host });
+            let nickname = match nickname_ref.cast::<HtmlInputElement>().unwrap().value() {
+                nickname if nickname.is_empty() => default_nickname(),
+                nickname => nickname,
+            };
+            let timestamp_format = match timestamp_format_ref
+                .cast::<HtmlInputElement>()
+                .unwrap()
+                .value()
+            {
+                format if format.is_empty() => default_timestamp_format(),
+                format => format,
+            };
+            set_settings.emit(Settings {
+                host,
+                nickname,
+                timestamp_format,
+            });
             nav.push(&Route::Home);
         }
     });
 
-    let host = match &props.settings {
-        Some(settings) => settings.host.clone(),
-        _ => "".into(),
+    let (host, nickname, timestamp_format) = match &props.settings {
+        Some(settings) => (
+            settings.host.clone(),
+            settings.nickname.clone(),
+            settings.timestamp_format.clone(),
+        ),
+        _ => ("".into(), default_nickname(), default_timestamp_format()),
     };
 
     html! {
@@ -50,6 +87,21 @@ pub fn SettingsPage(props: &SettingsProps) -> Html {
                 class="bg-slate-900 p-2 rounded-md border-slate-50 outline-none focus:ring-slate-600 focus:ring-1"
                 ref={host_ref} id="host" type="text" value={host}
                 />
+            <label
+                class="mr-4 ml-4"
+                for="nickname">{ "Nick name:" }</label>
+            <input
+                class="bg-slate-900 p-2 rounded-md border-slate-50 outline-none focus:ring-slate-600 focus:ring-1"
+                ref={nickname_ref} id="nickname" type="text" value={nickname}
+                />
+            <label
+                class="mr-4 ml-4"
+                for="timestamp_format">{ "Time format:" }</label>
+            <input
+                class="bg-slate-900 p-2 rounded-md border-slate-50 outline-none focus:ring-slate-600 focus:ring-1"
+                ref={timestamp_format_ref} id="timestamp_format" type="text" value={timestamp_format}
+                title="e.g. %H:%M:%S"
+                />
             <input
                 class="block rounded-md bg-slate-600 shadow-red-50 py-2 px-4 mt-2 text-white font-bold cursor-pointer hover:bg-slate-500"
                 type="submit"